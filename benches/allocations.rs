@@ -0,0 +1,91 @@
+//! Counts the allocations made parsing command lines against a large set of definitions, which timing alone doesn't show.
+//! Short command lines are parsed both from scratch and through a [`Parser`] that is reused for all of them.
+//!
+//! Run with `cargo bench --bench allocations`, and see the `parse` bench for timings.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use flagged_cl_args::{Args, FlagDefinition, Parser, RepeatPolicy, VariantFlag};
+
+const DEFINITIONS: usize = 120;
+const TOKENS: usize = 5000;
+const ROUNDS: usize = 10;
+
+/// Counts allocations on the way to the system allocator.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn main() {
+    let mut flags: Vec<FlagDefinition> = (0..DEFINITIONS)
+        .map(|index| {
+            FlagDefinition::new(format!("option-{index}"), VariantFlag::string())
+                .alias(format!("alias-{index}"))
+                .repeat(RepeatPolicy::Collect)
+        })
+        .collect();
+    flags.push(FlagDefinition::new("verbose", VariantFlag::count()).abbrev('v'));
+
+    let mut args = vec!["bench".to_string()];
+    while args.len() < TOKENS {
+        let index = args.len() % DEFINITIONS;
+        args.push(format!("--option-{}", DEFINITIONS - 1 - index));
+        args.push(format!("value-{index}"));
+        args.push(format!("--alias-{index}=value"));
+        args.push("-vv".to_string());
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let label = format!(
+        "parse {} tokens against {} definitions",
+        args.len(),
+        flags.len()
+    );
+    count(&label, || {
+        Args::from_slice(black_box(&args), &[], &flags).unwrap();
+    });
+
+    let short = ["bench", "--option-7", "value", "-vv"];
+    let label = format!(
+        "parse {} tokens against {} definitions",
+        short.len(),
+        flags.len()
+    );
+    count(&label, || {
+        Args::from_slice(black_box(&short), &[], &flags).unwrap();
+    });
+    let parser = flags
+        .iter()
+        .cloned()
+        .fold(Parser::new("bench"), Parser::flag);
+    count(&format!("{label}, reusing a Parser"), || {
+        parser.parse_from(black_box(short)).unwrap();
+    });
+}
+
+/// Runs `parse` a few times, then prints how many allocations each made on average.
+fn count(label: &str, mut parse: impl FnMut()) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..ROUNDS {
+        parse();
+    }
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!("{label}: {} allocations per parse", allocations / ROUNDS);
+}
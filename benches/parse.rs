@@ -0,0 +1,68 @@
+//! Times parsing command lines against a large set of definitions, so a slower way of finding flags shows up as a regression.
+//! Short command lines are parsed both from scratch and through a [`Parser`] that is reused for all of them.
+//!
+//! Run with `cargo bench --bench parse`, and see the `allocations` bench for how much each parse allocates.
+
+use std::hint::black_box;
+
+use criterion::Criterion;
+use flagged_cl_args::{Args, FlagDefinition, Parser, RepeatPolicy, VariantFlag};
+
+const DEFINITIONS: usize = 120;
+const TOKENS: usize = 5000;
+
+/// Many flags that take values and have an alias, and one counted flag.
+fn definitions() -> Vec<FlagDefinition> {
+    let mut flags: Vec<FlagDefinition> = (0..DEFINITIONS)
+        .map(|index| {
+            FlagDefinition::new(format!("option-{index}"), VariantFlag::string())
+                .alias(format!("alias-{index}"))
+                .repeat(RepeatPolicy::Collect)
+        })
+        .collect();
+    flags.push(FlagDefinition::new("verbose", VariantFlag::count()).abbrev('v'));
+    flags
+}
+
+/// A long command line, like one generated by a build system, using every definition.
+fn long_command_line() -> Vec<String> {
+    let mut args = vec!["bench".to_string()];
+    while args.len() < TOKENS {
+        let index = args.len() % DEFINITIONS;
+        // The last definitions are the slowest to find by searching in order
+        args.push(format!("--option-{}", DEFINITIONS - 1 - index));
+        args.push(format!("value-{index}"));
+        args.push(format!("--alias-{index}=value"));
+        args.push("-vv".to_string());
+    }
+    args
+}
+
+fn parse(c: &mut Criterion) {
+    let flags = definitions();
+    let args = long_command_line();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let mut group = c.benchmark_group(format!("{} definitions", flags.len()));
+    group.bench_function(format!("{} tokens", args.len()), |b| {
+        b.iter(|| Args::from_slice(black_box(&args), &[], &flags).unwrap());
+    });
+
+    let short = ["bench", "--option-7", "value", "-vv"];
+    group.bench_function(format!("{} tokens", short.len()), |b| {
+        b.iter(|| Args::from_slice(black_box(&short), &[], &flags).unwrap());
+    });
+    let parser = flags
+        .iter()
+        .cloned()
+        .fold(Parser::new("bench"), Parser::flag);
+    group.bench_function(format!("{} tokens, reusing a Parser", short.len()), |b| {
+        b.iter(|| parser.parse_from(black_box(short)).unwrap());
+    });
+    group.finish();
+}
+
+fn main() {
+    let mut c = Criterion::default().configure_from_args();
+    parse(&mut c);
+    c.final_summary();
+}
@@ -0,0 +1,326 @@
+//! The `#[derive(FromArgs)]` macro for [flagged_cl_args](https://docs.rs/flagged_cl_args).
+//!
+//! Use it through the `derive` feature of flagged_cl_args, which documents the attributes it accepts.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Error, Field, Fields, GenericArgument, LitChar, LitStr, PathArguments, Type,
+    parse_macro_input,
+};
+
+/// Implements `flagged_cl_args::FromArgs` for a struct with named fields.
+#[proc_macro_derive(FromArgs, attributes(flag, positional))]
+pub fn derive_from_args(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The types that are stored directly in a `Variant`.
+#[derive(Clone, Copy)]
+enum Scalar {
+    Bool,
+    Int,
+    Float,
+    Socket,
+    Path,
+    String,
+}
+
+impl Scalar {
+    fn from_type(ty: &Type) -> Option<Scalar> {
+        let Type::Path(path) = ty else {
+            return None;
+        };
+        let segment = path.path.segments.last()?;
+        if !segment.arguments.is_none() {
+            return None;
+        }
+        match segment.ident.to_string().as_str() {
+            "bool" => Some(Scalar::Bool),
+            "i32" => Some(Scalar::Int),
+            "f32" => Some(Scalar::Float),
+            "SocketAddr" => Some(Scalar::Socket),
+            "PathBuf" => Some(Scalar::Path),
+            "String" => Some(Scalar::String),
+            _ => None,
+        }
+    }
+
+    /// The name used by the `VariantFlag` constructor and the typed getters.
+    fn name(self) -> &'static str {
+        match self {
+            Scalar::Bool => "bool",
+            Scalar::Int => "int",
+            Scalar::Float => "float",
+            Scalar::Socket => "socket",
+            Scalar::Path => "path",
+            Scalar::String => "string",
+        }
+    }
+
+    fn allowed_type(self) -> TokenStream2 {
+        let constructor = format_ident!("{}", self.name());
+        quote!(::flagged_cl_args::VariantFlag::#constructor())
+    }
+
+    /// Turns the borrowed value from a typed getter into the field's type.
+    fn to_owned(self) -> TokenStream2 {
+        match self {
+            Scalar::Path => quote!(.cloned()),
+            Scalar::String => quote!(.map(::std::string::ToString::to_string)),
+            _ => TokenStream2::new(),
+        }
+    }
+}
+
+/// How a field's type maps onto an argument.
+enum Shape {
+    /// `bool`, a unit flag that is false unless given
+    Unit,
+    Required(Scalar),
+    Optional(Scalar),
+    Repeated(Scalar),
+}
+
+impl Shape {
+    fn from_type(ty: &Type) -> syn::Result<Shape> {
+        let shape = if let Some(inner) = generic_argument(ty, "Option") {
+            Scalar::from_type(inner).map(Shape::Optional)
+        } else if let Some(inner) = generic_argument(ty, "Vec") {
+            Scalar::from_type(inner).map(Shape::Repeated)
+        } else {
+            match Scalar::from_type(ty) {
+                Some(Scalar::Bool) => Some(Shape::Unit),
+                scalar => scalar.map(Shape::Required),
+            }
+        };
+        shape.ok_or_else(|| {
+            Error::new_spanned(
+                ty,
+                "unsupported field type, expected bool, i32, f32, SocketAddr, PathBuf, String, or an Option or Vec of one of those",
+            )
+        })
+    }
+}
+
+/// The `T` in `Option<T>` or `Vec<T>`, when `ty` is `wrapper<T>`.
+fn generic_argument<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(inner) if arguments.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// Metadata from `#[flag(...)]` or `#[positional(...)]`.
+#[derive(Default)]
+struct Attributes {
+    positional: bool,
+    name: Option<LitStr>,
+    abbrev: Option<LitChar>,
+    help: Option<LitStr>,
+}
+
+impl Attributes {
+    fn from_field(field: &Field) -> syn::Result<Attributes> {
+        let mut attributes = Attributes::default();
+        for attr in &field.attrs {
+            let positional = attr.path().is_ident("positional");
+            if !positional && !attr.path().is_ident("flag") {
+                continue;
+            }
+            attributes.positional |= positional;
+            if matches!(attr.meta, syn::Meta::Path(_)) {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("help") {
+                    attributes.help = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("abbrev") && !positional {
+                    attributes.abbrev = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("name") && !positional {
+                    attributes.name = Some(meta.value()?.parse()?);
+                } else if positional {
+                    return Err(meta.error("unknown positional attribute, expected help"));
+                } else {
+                    return Err(
+                        meta.error("unknown flag attribute, expected abbrev, help, or name")
+                    );
+                }
+                Ok(())
+            })?;
+        }
+        Ok(attributes)
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "FromArgs can only be derived for structs with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "FromArgs can only be derived for structs",
+            ));
+        }
+    };
+
+    let mut positional_definitions = Vec::new();
+    let mut flag_definitions = Vec::new();
+    let mut initializers = Vec::new();
+    // Whether an earlier positional argument is an Option, or a Vec that takes the rest
+    let mut optional_taken = false;
+    let mut rest_taken = false;
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named fields have names");
+        let attributes = Attributes::from_field(field)?;
+        let shape = Shape::from_type(&field.ty)?;
+        let help = match &attributes.help {
+            Some(help) => quote!(::std::option::Option::Some(#help.to_string())),
+            None => quote!(::std::option::Option::None),
+        };
+
+        if attributes.positional {
+            if rest_taken {
+                return Err(Error::new_spanned(
+                    ident,
+                    "a Vec positional argument takes every remaining value, so it must be the last one",
+                ));
+            }
+            let index = positional_definitions.len();
+            let (scalar, required, rest, initializer) = match shape {
+                Shape::Unit => (Scalar::Bool, true, false, None),
+                Shape::Required(scalar) => (scalar, true, false, None),
+                Shape::Optional(scalar) => {
+                    let getter = format_ident!("get_positional_{}", scalar.name());
+                    let to_owned = scalar.to_owned();
+                    let initializer = quote!(#ident: args.#getter(#index)#to_owned);
+                    (scalar, false, false, Some(initializer))
+                }
+                Shape::Repeated(scalar) => {
+                    rest_taken = true;
+                    let initializer =
+                        quote!(#ident: ::flagged_cl_args::__private::get_rest(args, #index)?);
+                    (scalar, true, true, Some(initializer))
+                }
+            };
+            if required && !rest && optional_taken {
+                return Err(Error::new_spanned(
+                    ident,
+                    "a required positional argument can't come after an Option one",
+                ));
+            }
+            optional_taken |= !required;
+            let allowed_type = scalar.allowed_type();
+            let arity = if rest {
+                quote!(ZeroOrMore)
+            } else {
+                quote!(One)
+            };
+            positional_definitions.push(quote! {
+                ::flagged_cl_args::PositionalDefinition {
+                    allowed_type: #allowed_type,
+                    required: #required,
+                    help: #help,
+                    arity: ::flagged_cl_args::PositionalArity::#arity,
+                    ..::std::default::Default::default()
+                }
+            });
+            initializers.push(initializer.unwrap_or_else(|| {
+                let getter = format_ident!("get_positional_{}", scalar.name());
+                let to_owned = scalar.to_owned();
+                quote! {
+                    #ident: args.#getter(#index)#to_owned.ok_or_else(|| ::flagged_cl_args::__private::missing_positional(#index))?
+                }
+            }));
+            continue;
+        }
+
+        let name = match &attributes.name {
+            Some(name) => name.value(),
+            None => ident.to_string().trim_start_matches("r#").replace('_', "-"),
+        };
+        let allowed_type = match shape {
+            Shape::Unit => quote!(::flagged_cl_args::VariantFlag::new_unit()),
+            Shape::Required(scalar) | Shape::Optional(scalar) | Shape::Repeated(scalar) => {
+                scalar.allowed_type()
+            }
+        };
+        let mut definition = quote!(::flagged_cl_args::FlagDefinition::new(#name, #allowed_type));
+        if let Some(abbrev) = &attributes.abbrev {
+            definition.extend(quote!(.abbrev(#abbrev)));
+        }
+        if let Shape::Repeated(_) = shape {
+            definition.extend(quote!(.repeat(::flagged_cl_args::RepeatPolicy::Collect)));
+        }
+        if let Some(help) = &attributes.help {
+            definition.extend(quote!(.help(#help)));
+        }
+        flag_definitions.push(definition);
+        initializers.push(match shape {
+            Shape::Unit => quote! {
+                #ident: args.try_get_named_bool(#name)?.unwrap_or(false)
+            },
+            Shape::Required(scalar) => {
+                let getter = format_ident!("try_get_named_{}", scalar.name());
+                let to_owned = scalar.to_owned();
+                quote! {
+                    #ident: args.#getter(#name)?#to_owned.ok_or_else(|| ::flagged_cl_args::__private::missing_flag(#name))?
+                }
+            }
+            Shape::Optional(scalar) => {
+                let getter = format_ident!("try_get_named_{}", scalar.name());
+                let to_owned = scalar.to_owned();
+                quote! {
+                    #ident: args.#getter(#name)?#to_owned
+                }
+            }
+            Shape::Repeated(_) => quote! {
+                #ident: ::flagged_cl_args::__private::get_all(args, #name)?
+            },
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::flagged_cl_args::FromArgs for #ident #ty_generics #where_clause {
+            fn positional_definitions() -> ::std::vec::Vec<::flagged_cl_args::PositionalDefinition> {
+                ::std::vec![#(#positional_definitions),*]
+            }
+
+            fn flag_definitions() -> ::std::vec::Vec<::flagged_cl_args::FlagDefinition> {
+                ::std::vec![#(#flag_definitions),*]
+            }
+
+            fn from_parsed(
+                args: &::flagged_cl_args::Args,
+            ) -> ::std::result::Result<Self, ::flagged_cl_args::ArgumentError> {
+                ::std::result::Result::Ok(Self {
+                    #(#initializers),*
+                })
+            }
+        }
+    })
+}
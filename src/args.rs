@@ -1,12 +1,51 @@
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    ffi::{OsStr, OsString},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use crate::{ArgumentError, FlagDefinition, Variant, VariantFlag};
+#[cfg(any(feature = "toml", feature = "json"))]
+use crate::config_file::ConfigValue;
+use crate::{
+    ArgEvent, ArgumentError, ArgumentErrorKind, FlagDefinition, FromVariant, NoPrompt,
+    ParseFailure, ParserConfig, PositionalArity, PositionalDefinition, PromptSource, RepeatPolicy,
+    ReservedNames, Subcommand, TerminalPrompt, Validator, ValueSource, Variant, VariantFlag,
+    VariantKind,
+    command_line::{expand_response_files, quote},
+    help,
+};
 
 /// Contains the name of the binary, a list of arguments, and a hashmap of arguments.
+#[derive(Clone)]
 pub struct Args {
     binary: String,
     positional: Vec<Variant>,
-    named: HashMap<String, Variant>,
+    /// Keyed by names shared with the parse that made them, so matching a flag doesn't copy its name
+    named: HashMap<Arc<str>, Variant>,
+    trailing: Vec<OsString>,
+    /// The trailing arguments as text, with anything that isn't valid UTF-8 replaced, see [`Args::trailing`]
+    trailing_lossy: Vec<String>,
+    /// Where the values of a positional argument that takes any number of them start
+    rest_start: Option<usize>,
+    /// The names of the positional arguments, see [`PositionalDefinition::name`]
+    positional_names: Vec<Option<String>>,
+    unknown: Vec<String>,
+    warnings: Vec<String>,
+    /// Where each named argument's value came from, see [`Args::source_of`]
+    sources: HashMap<Arc<str>, ValueSource>,
+    /// Where each positional argument's value came from
+    positional_sources: Vec<ValueSource>,
+    /// Everything given on the command line, in order, see [`Args::events`]
+    events: Vec<ArgEvent>,
+    /// The text named values were parsed from, see [`Args::get_named_raw`]
+    raw_named: HashMap<Arc<str>, String>,
+    /// The text positional values were parsed from, see [`Args::get_positional_raw`]
+    raw_positional: Vec<Option<String>>,
+    /// The names of flags whose values are left out of Debug output, see [`FlagDefinition::sensitive`]
+    sensitive: HashSet<Arc<str>>,
 }
 
 impl Args {
@@ -14,10 +53,11 @@ impl Args {
     ///
     /// `positional_types` is a list of types (stored as [`VariantFlag`]s) your program is expecting.
     /// You do not need to include the name of the binary, it is put into a separate field.
-    /// Positional arguments are always required. If the wrong number of positional arguments are supplied, an Err value will be returned.
+    /// Each of these is a required positional argument, and if the wrong number of positional arguments are supplied, an Err value will be returned.
+    /// Positional arguments that are optional, have a default, or take any number of values are described with [`PositionalDefinition`]s instead, see [`Args::new_with_positionals`].
     ///
     /// `flag_definitions` should contain a list of named arguments (stored as [`FlagDefinition`]s) your program is expecting.
-    /// Named arguments are always optional. If a named argument is not supplied, it will simply not be included in the internal HashMap.
+    /// Named arguments are always optional. If a named argument is not supplied, it will use its environment variable or default value, or simply not be included in the internal HashMap.
     ///
     /// # Errors
     ///
@@ -28,73 +68,1469 @@ impl Args {
     /// - A named argument wasn't listed in the flag definitions
     /// - A non-unit named argument didn't have another argument after it
     /// - A value could not be parsed into any of the types it is allowed to become
+    /// - A default value is not one of the types its flag is allowed to become
+    /// - An environment variable could not be parsed into any of the types its flag is allowed to become
+    /// - The definitions themselves have a problem, see [`crate::validate_definitions`], or a positional argument is a unit flag
+    /// - Flags that conflict with each other were both given, or a flag was given without a flag it requires
+    /// - A validator rejected a value
+    /// - A flag is named `help`, `version`, or `no-<name>` of a negatable flag, or abbreviated `h` or `V`, while [`ParserConfig::reserved_names`] is [`ReservedNames::Reject`](crate::ReservedNames::Reject)
     pub fn new(
         positional_types: &[VariantFlag],
         flag_definitions: &[FlagDefinition],
     ) -> Result<Args, ArgumentError> {
-        Args::from_iter(std::env::args(), positional_types, flag_definitions)
+        Args::from_iter_with_env(
+            std::env::args(),
+            &legacy_config(),
+            &positional_definitions(positional_types),
+            flag_definitions,
+            |name| std::env::var_os(name),
+            &mut TerminalPrompt,
+        )
+    }
+
+    /// Like [`Args::new`], but arguments don't need to be valid UTF-8.
+    ///
+    /// [`std::env::args`] panics when an argument isn't valid UTF-8, which can happen for paths on some platforms.
+    /// Using this instead, path values are kept exactly as they were given, and only values that must be parsed as other types need to be valid UTF-8.
+    /// Invalid UTF-8 in those values results in an Err value rather than a panic.
+    ///
+    /// # Errors
+    ///
+    /// See [`Args::new`].
+    pub fn new_os(
+        positional_types: &[VariantFlag],
+        flag_definitions: &[FlagDefinition],
+    ) -> Result<Args, ArgumentError> {
+        Args::from_iter_os(
+            std::env::args_os(),
+            &positional_definitions(positional_types),
+            flag_definitions,
+            &mut TerminalPrompt,
+        )
+    }
+
+    /// Like [`Args::new`], but every named argument without an explicit [`FlagDefinition::env_var`] falls back to an environment variable derived from its name.
+    /// This is the same as setting [`ParserConfig::env_prefix`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Args::new`].
+    pub fn new_with_env_prefix(
+        env_prefix: &str,
+        positional_types: &[VariantFlag],
+        flag_definitions: &[FlagDefinition],
+    ) -> Result<Args, ArgumentError> {
+        let config = ParserConfig {
+            env_prefix: Some(env_prefix.to_string()),
+            ..legacy_config()
+        };
+        Args::new_with_config(
+            &config,
+            &positional_definitions(positional_types),
+            flag_definitions,
+        )
+    }
+
+    /// Like [`Args::new`], but each positional argument is described by a [`PositionalDefinition`] instead of just its type.
+    ///
+    /// # Errors
+    ///
+    /// See [`Args::new`].
+    pub fn new_with_positionals(
+        positional_definitions: &[PositionalDefinition],
+        flag_definitions: &[FlagDefinition],
+    ) -> Result<Args, ArgumentError> {
+        Args::from_iter_with_env(
+            std::env::args(),
+            &legacy_config(),
+            positional_definitions,
+            flag_definitions,
+            |name| std::env::var_os(name),
+            &mut TerminalPrompt,
+        )
+    }
+
+    /// Like [`Args::new_with_positionals`], with behavior that isn't specific to a single argument controlled by `config`.
+    /// Arguments don't need to be valid UTF-8, see [`Args::new_os`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Args::new`].
+    pub fn new_with_config(
+        config: &ParserConfig,
+        positional_definitions: &[PositionalDefinition],
+        flag_definitions: &[FlagDefinition],
+    ) -> Result<Args, ArgumentError> {
+        Args::from_iter_os_with_env(
+            std::env::args_os(),
+            config,
+            positional_definitions,
+            flag_definitions,
+            |name| std::env::var_os(name),
+            &mut TerminalPrompt,
+        )
+    }
+
+    /// Renders help text describing the arguments your program accepts, to show your end user when [`Args::help_requested`].
+    ///
+    /// It starts with the line from [`Args::usage`], followed by a table of positional arguments and a table of flags.
+    /// The flag table lists each flag's abbreviations, names, accepted types, and help, along with its default value and environment variable if it has them.
+    /// Help is wrapped to fit within [`ParserConfig::help_width`] columns.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, ParserConfig, PositionalDefinition, Variant, VariantFlag};
+    /// let positionals = [PositionalDefinition {
+    ///     allowed_type: VariantFlag::path(),
+    ///     help: Some("The file to read".to_string()),
+    ///     ..Default::default()
+    /// }];
+    /// let flags = [
+    ///     FlagDefinition::new("threads", VariantFlag::int())
+    ///         .abbrev('t')
+    ///         .default(Variant::Int(4))
+    ///         .help("How many threads to read the file with"),
+    ///     FlagDefinition::new("cache", VariantFlag::new_unit())
+    ///         .negatable()
+    ///         .help("Cache what was read"),
+    /// ];
+    /// let config = ParserConfig {
+    ///     help_width: 50,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(
+    ///     Args::help_text("mytool", &config, &positionals, &flags),
+    ///     "\
+    /// usage: mytool <path> [--threads <int>] [--cache]
+    ///
+    /// Arguments:
+    ///   <POS1:path>          The file to read
+    ///
+    /// Flags:
+    ///   -t, --threads <int>  How many threads to read
+    ///                        the file with [default: 4]
+    ///       --[no-]cache     Cache what was read
+    ///   -h, --help           Print this help
+    /// "
+    /// );
+    /// ```
+    #[must_use]
+    pub fn help_text(
+        binary: &str,
+        config: &ParserConfig,
+        positional_definitions: &[PositionalDefinition],
+        flag_definitions: &[FlagDefinition],
+    ) -> String {
+        help::help_text(binary, config, positional_definitions, flag_definitions)
+    }
+
+    /// Renders a one line summary of how to run your program, like `usage: mytool <bool|int> <path> [--output <path>] [-v]`.
+    ///
+    /// Unit and counted flags are shown by their abbreviation if they have one, other flags by their name and accepted types.
+    /// Flags that can be given more than once are followed by `...`.
+    /// Set [`ParserConfig::usage_in_errors`] to add this to every [`ArgumentError`].
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, VariantFlag};
+    /// let flags = [
+    ///     FlagDefinition::new("output", VariantFlag::path()),
+    ///     FlagDefinition::new("verbose", VariantFlag::new_unit()).abbrev('v'),
+    /// ];
+    /// assert_eq!(
+    ///     Args::usage("mytool", &[VariantFlag::bool().or_int(), VariantFlag::path()], &flags),
+    ///     "usage: mytool <bool|int> <path> [--output <path>] [-v]"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn usage(
+        binary: &str,
+        positional_types: &[VariantFlag],
+        flag_definitions: &[FlagDefinition],
+    ) -> String {
+        help::usage(
+            binary,
+            &positional_definitions(positional_types),
+            flag_definitions,
+        )
+    }
+
+    /// Parses arguments for a program with subcommands, like `binary --verbose push <path> --force`.
+    ///
+    /// `global_flag_definitions` are given before the subcommand, and there are no global positional arguments.
+    /// The first argument that isn't a global flag, or a global flag's value, selects the subcommand by its name or one of its aliases.
+    /// Everything after it is parsed with the subcommand's definitions.
+    ///
+    /// Returns the global arguments, along with the subcommand's name and arguments.
+    /// When no subcommand was given, there is no error and the subcommand is None, so you can decide whether that's a mistake.
+    /// The subcommand's [`Args::binary`] is the binary followed by the subcommand's name, like `binary push`, which suits [`Args::help_text`].
+    /// Positions in its errors and [`ValueSource`]s count from the subcommand, like they would if `binary push` were a program of its own.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, ParserConfig, Subcommand, VariantFlag};
+    /// let subcommands = [
+    ///     Subcommand {
+    ///         name: "fetch".to_string(),
+    ///         positional_definitions: vec![VariantFlag::string().into()],
+    ///         ..Default::default()
+    ///     },
+    ///     Subcommand {
+    ///         name: "push".to_string(),
+    ///         positional_definitions: vec![VariantFlag::path().into()],
+    ///         ..Default::default()
+    ///     },
+    /// ];
+    /// match Args::new_with_subcommands(&ParserConfig::default(), &[], &subcommands) {
+    ///     Ok((_, Some((name, args)))) if name == "fetch" => { /* fetch args.get_positional(0) */ }
+    ///     Ok((_, Some((_, args)))) => { /* push args.get_positional(0) */ }
+    ///     Ok((_, None)) => eprintln!("expected a subcommand, fetch or push"),
+    ///     Err(error) => eprintln!("{error}"),
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See [`Args::new`], which applies to the global arguments and the subcommand's arguments alike.
+    /// It is also an error when the subcommand isn't one of `subcommands`.
+    /// The error suggests the closest subcommand name or alias, when one is spelled similarly.
+    pub fn new_with_subcommands(
+        config: &ParserConfig,
+        global_flag_definitions: &[FlagDefinition],
+        subcommands: &[Subcommand],
+    ) -> Result<(Args, Option<(String, Args)>), ArgumentError> {
+        Args::from_iter_subcommands(
+            std::env::args_os(),
+            config,
+            global_flag_definitions,
+            subcommands,
+            |name| std::env::var_os(name),
+            &mut TerminalPrompt,
+        )
+    }
+
+    /// Like [`Args::new_with_subcommands`], but parses `args` instead of the arguments your program was run with, which is handy for testing.
+    /// The first element is consumed as the name of the binary, and [`FlagDefinition::prompt`] flags are never asked for.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, ParserConfig, Subcommand, VariantFlag};
+    /// let subcommands = [Subcommand {
+    ///     name: "push".to_string(),
+    ///     flag_definitions: vec![FlagDefinition::new("force", VariantFlag::new_unit())],
+    ///     ..Default::default()
+    /// }];
+    /// let (_, subcommand) = Args::from_iter_with_subcommands(
+    ///     ["my-tool", "push", "--force"].map(String::from).into_iter(),
+    ///     &ParserConfig::default(),
+    ///     &[],
+    ///     &subcommands,
+    /// )
+    /// .unwrap();
+    /// let (name, args) = subcommand.unwrap();
+    /// assert_eq!(name, "push");
+    /// assert_eq!(args.binary(), "my-tool push");
+    /// assert_eq!(args.get_named_bool("force"), Some(true));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See [`Args::new_with_subcommands`].
+    pub fn from_iter_with_subcommands(
+        args: impl Iterator<Item = String>,
+        config: &ParserConfig,
+        global_flag_definitions: &[FlagDefinition],
+        subcommands: &[Subcommand],
+    ) -> Result<(Args, Option<(String, Args)>), ArgumentError> {
+        Args::from_iter_subcommands(
+            args.map(OsString::from),
+            config,
+            global_flag_definitions,
+            subcommands,
+            |name| std::env::var_os(name),
+            &mut NoPrompt,
+        )
     }
 
-    pub(crate) fn from_iter(
+    /// Prints [`Args::help_text`] to stdout.
+    pub fn print_help(
+        binary: &str,
+        config: &ParserConfig,
+        positional_definitions: &[PositionalDefinition],
+        flag_definitions: &[FlagDefinition],
+    ) {
+        print!(
+            "{}",
+            Args::help_text(binary, config, positional_definitions, flag_definitions)
+        );
+    }
+
+    /// Parses `args` instead of the arguments your program was run with, which is handy for testing.
+    /// The first element is consumed as the name of the binary, so it isn't a positional argument.
+    /// [`FlagDefinition::prompt`] flags are never asked for, see [`Args::from_iter_with_env`] to answer them.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, PositionalDefinition, Variant, VariantFlag};
+    /// let args = Args::from_iter(
+    ///     ["my-tool", "in.txt", "--threads", "4"].map(String::from).into_iter(),
+    ///     &[PositionalDefinition::from(VariantFlag::path())],
+    ///     &[FlagDefinition::new("threads", VariantFlag::int())],
+    /// )
+    /// .unwrap();
+    /// assert_eq!(args.binary(), "my-tool");
+    /// assert_eq!(args.get_named_int("threads"), Some(4));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See [`Args::new`].
+    pub fn from_iter(
         args: impl Iterator<Item = String>,
+        positional_definitions: &[PositionalDefinition],
+        flag_definitions: &[FlagDefinition],
+    ) -> Result<Args, ArgumentError> {
+        Args::from_iter_with_env(
+            args,
+            &legacy_config(),
+            positional_definitions,
+            flag_definitions,
+            |name| std::env::var_os(name),
+            &mut NoPrompt,
+        )
+    }
+
+    /// Like [`Args::from_iter`], but for string slices, like `&["my-tool", "--flag", "x"]`.
+    /// The first element is consumed as the name of the binary.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, VariantFlag};
+    /// let flags = [FlagDefinition::new("verbose", VariantFlag::new_unit()).abbrev('v')];
+    /// let args = Args::from_slice(&["my-tool", "-v", "in.txt"], &[VariantFlag::path()], &flags).unwrap();
+    /// assert_eq!(args.get_named_bool("verbose"), Some(true));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See [`Args::new`].
+    pub fn from_slice(
+        args: &[&str],
+        positional_types: &[VariantFlag],
+        flag_definitions: &[FlagDefinition],
+    ) -> Result<Args, ArgumentError> {
+        Args::from_iter(
+            args.iter().map(ToString::to_string),
+            &positional_definitions(positional_types),
+            flag_definitions,
+        )
+    }
+
+    /// Parses a whole command line, like one read from a file or typed at a prompt, split into arguments by [`crate::split_command_line`].
+    /// The first argument is consumed as the name of the binary, like in a shell.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, VariantFlag};
+    /// let flags = [FlagDefinition::new("message", VariantFlag::string()).abbrev('m')];
+    /// let args = Args::from_command_line(r#"commit -m "fix the thing""#, &[], &flags).unwrap();
+    /// assert_eq!(args.get_named_string("message"), Some("fix the thing"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::split_command_line`] and [`Args::new`].
+    pub fn from_command_line(
+        line: &str,
         positional_types: &[VariantFlag],
         flag_definitions: &[FlagDefinition],
     ) -> Result<Args, ArgumentError> {
-        let mut args = args.enumerate();
-        let (_, binary) = args
-            .next()
-            .ok_or(ArgumentError::new("Argument count is 0"))?;
+        Args::from_iter(
+            crate::split_command_line(line)?.into_iter(),
+            &positional_definitions(positional_types),
+            flag_definitions,
+        )
+    }
+
+    /// Looks for a single flag without parsing anything else, like finding `--config` before the definitions it loads are known.
+    ///
+    /// Flags that aren't `definition` are ignored, and nothing is checked, so this never fails.
+    /// It handles `--name value`, `--name=value`, `-a value`, `-a=value`, and `-avalue`, stopping at a bare `--`.
+    /// A value that doesn't parse is treated as missing, leaving the full parse to report it.
+    /// The environment and [`FlagDefinition::default`] aren't looked at.
+    ///
+    /// `args` starts with the binary, like [`Args::from_iter`], and is only borrowed, so it can be passed to the full parse afterward.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, Parser, VariantFlag};
+    /// let config = FlagDefinition::new("config", VariantFlag::path()).abbrev('c');
+    /// let args = ["my-tool", "--threads", "4", "-c", "tool.toml"];
+    ///
+    /// // Only --config is known so far, --threads is ignored
+    /// let path = Args::peek_flag(&args, &config).unwrap();
+    /// assert_eq!(path.as_path().unwrap().to_str(), Some("tool.toml"));
+    /// assert!(Args::peek_flag(&["my-tool", "--config=tool.toml"], &config).is_some());
+    /// assert!(Args::peek_flag(&["my-tool", "-ctool.toml"], &config).is_some());
+    ///
+    /// // Then the definitions it holds are loaded, and everything is parsed
+    /// let parser = Parser::new("my-tool")
+    ///     .flag(config)
+    ///     .flag(FlagDefinition::new("threads", VariantFlag::int()));
+    /// let parsed = parser.parse_from(args).unwrap();
+    /// assert_eq!(parsed.get_named_int("threads"), Some(4));
+    /// ```
+    pub fn peek_flag(
+        args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+        definition: &FlagDefinition,
+    ) -> Option<Variant> {
+        let config = ParserConfig::default();
+        let lookup = FlagLookup::new(&config, std::slice::from_ref(definition));
+        let mut args = args.into_iter().enumerate().skip(1);
+        // Counted flags add up every occurrence, see `VariantFlag::count`
+        let mut count = 0;
+        while let Some((index, arg)) = args.next() {
+            let Some(arg) = arg.as_ref().to_str() else {
+                continue;
+            };
+            if arg == "--" {
+                break;
+            }
+            // Anything that isn't this flag is an error here, which is what makes it safe to skip
+            let Ok(flag_matches) = match_flag_definition(&config, &lookup, arg, index) else {
+                continue;
+            };
+            let Some(flag_match) = flag_matches.into_iter().last() else {
+                continue;
+            };
+            let allowed_type = &definition.allowed_type;
+            if flag_match.negated || allowed_type.is_unit() {
+                let present = match flag_match.value {
+                    Some(value) => allowed_type.parse_unit_value(value)?,
+                    None => true,
+                };
+                return Some(Variant::Bool(present && !flag_match.negated));
+            }
+            if allowed_type.is_count() {
+                count += 1;
+                continue;
+            }
+            if definition.num_values > 1 {
+                let values = flag_match
+                    .value
+                    .map(OsString::from)
+                    .into_iter()
+                    .chain(
+                        args.by_ref()
+                            .map(|(_, value)| value.as_ref().to_os_string()),
+                    )
+                    .take(definition.num_values)
+                    .map(|value| allowed_type.parse_os_or_reason(&value).ok())
+                    .collect::<Option<Vec<_>>>()?;
+                return (values.len() == definition.num_values).then_some(Variant::List(values));
+            }
+            return match flag_match.value {
+                Some(value) => {
+                    let value = value_from_file(definition, index, OsStr::new(value)).ok()?;
+                    allowed_type.parse_os_or_reason(&value).ok()
+                }
+                None if definition.optional_value.is_some() => definition.optional_value.clone(),
+                None => {
+                    let (index, value) = args.next()?;
+                    let value = value_from_file(definition, index, value.as_ref()).ok()?;
+                    allowed_type.parse_os_or_reason(&value).ok()
+                }
+            };
+        }
+        (count > 0).then_some(Variant::Int(count))
+    }
+
+    /// Builds already parsed arguments directly, without any definitions, so tests of code that uses [`Args`] can make up whatever it expects.
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use flagged_cl_args::{Args, Variant};
+    /// let args = Args::from_map(
+    ///     "my-tool",
+    ///     vec![Variant::String("in.txt".to_string())],
+    ///     HashMap::from([("threads".to_string(), Variant::Int(4))]),
+    /// );
+    /// assert_eq!(args.get_positional_string(0), Some("in.txt"));
+    /// assert_eq!(args.get_named_int("threads"), Some(4));
+    /// ```
+    #[must_use]
+    pub fn from_map(
+        binary: &str,
+        positional: Vec<Variant>,
+        named: HashMap<String, Variant>,
+    ) -> Args {
+        Args {
+            binary: binary.to_string(),
+            positional,
+            named: named
+                .into_iter()
+                .map(|(name, value)| (Arc::from(name), value))
+                .collect(),
+            trailing: Vec::new(),
+            trailing_lossy: Vec::new(),
+            rest_start: None,
+            positional_names: Vec::new(),
+            unknown: Vec::new(),
+            warnings: Vec::new(),
+            sources: HashMap::new(),
+            positional_sources: Vec::new(),
+            events: Vec::new(),
+            raw_named: HashMap::new(),
+            raw_positional: Vec::new(),
+            sensitive: HashSet::new(),
+        }
+    }
+
+    /// Layers `overlay` on top of these arguments, like a real command line over one recorded in a config file.
+    ///
+    /// - Each named argument in `overlay` replaces the one here, except when it only came from its [`FlagDefinition::default`] and this one didn't
+    /// - Lists of [`RepeatPolicy::Collect`] arguments are replaced as a whole, not added to
+    /// - The positional arguments in `overlay` replace all of these, unless it has none, and the same goes for [`Args::trailing`] arguments
+    /// - The binary of `overlay` is kept
+    /// - [`Args::unknown`] arguments, [`Args::warnings`], and [`Args::events`] from both are kept, these first
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, RepeatPolicy, Variant, VariantFlag};
+    /// let flags = [
+    ///     FlagDefinition::new("threads", VariantFlag::int()).default(Variant::Int(1)),
+    ///     FlagDefinition::new("include", VariantFlag::path()).repeat(RepeatPolicy::Collect),
+    ///     FlagDefinition::new("verbose", VariantFlag::new_unit()),
+    /// ];
+    /// let recorded = Args::from_slice(
+    ///     &["old-tool", "base.txt", "--threads", "8", "--include", "a", "--include", "b", "--verbose"],
+    ///     &[VariantFlag::path()],
+    ///     &flags,
+    /// )
+    /// .unwrap();
+    /// let given = Args::from_slice(&["my-tool", "--include", "c"], &[], &flags).unwrap();
+    ///
+    /// let args = recorded.merge(given);
+    /// assert_eq!(args.binary(), "my-tool");
+    /// // The recorded 8 wins over the default of 1
+    /// assert_eq!(args.get_named_int("threads"), Some(8));
+    /// assert_eq!(args.get_named("include"), Some(&Variant::List(vec![Variant::Path("c".into())])));
+    /// assert_eq!(args.get_named_bool("verbose"), Some(true));
+    /// assert_eq!(args.get_positional_path(0).unwrap().to_str(), Some("base.txt"));
+    /// ```
+    #[must_use]
+    pub fn merge(mut self, overlay: Args) -> Args {
+        for (name, value) in overlay.named {
+            let source = overlay.sources.get(&name).cloned();
+            if source == Some(ValueSource::Default)
+                && self.named.contains_key(&name)
+                && self.sources.get(&name) != Some(&ValueSource::Default)
+            {
+                continue;
+            }
+            match source {
+                Some(source) => self.sources.insert(name.clone(), source),
+                None => self.sources.remove(&name),
+            };
+            match overlay.raw_named.get(&name) {
+                Some(raw) => self.raw_named.insert(name.clone(), raw.clone()),
+                None => self.raw_named.remove(&name),
+            };
+            self.named.insert(name, value);
+        }
+        if !overlay.positional.is_empty() {
+            self.positional = overlay.positional;
+            self.positional_sources = overlay.positional_sources;
+            self.raw_positional = overlay.raw_positional;
+            self.rest_start = overlay.rest_start;
+            self.positional_names = overlay.positional_names;
+        }
+        if !overlay.trailing.is_empty() {
+            self.trailing = overlay.trailing;
+            self.trailing_lossy = overlay.trailing_lossy;
+        }
+        self.sensitive.extend(overlay.sensitive);
+        self.binary = overlay.binary;
+        self.unknown.extend(overlay.unknown);
+        self.warnings.extend(overlay.warnings);
+        self.events.extend(overlay.events);
+        self
+    }
+
+    /// Fills in whatever these arguments are missing from `defaults`, the opposite of [`Args::merge`], so the values here are kept.
+    /// Values that only came from a [`FlagDefinition::default`] still give way to ones that didn't.
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use flagged_cl_args::{Args, Variant};
+    /// let given = Args::from_map("my-tool", vec![], HashMap::from([("threads".to_string(), Variant::Int(2))]));
+    /// let fallback = Args::from_map(
+    ///     "my-tool",
+    ///     vec![Variant::String("in.txt".to_string())],
+    ///     HashMap::from([("threads".to_string(), Variant::Int(8)), ("quiet".to_string(), Variant::Bool(true))]),
+    /// );
+    ///
+    /// let args = given.merge_defaults(fallback);
+    /// assert_eq!(args.get_named_int("threads"), Some(2));
+    /// assert_eq!(args.get_named_bool("quiet"), Some(true));
+    /// assert_eq!(args.get_positional_string(0), Some("in.txt"));
+    /// ```
+    #[must_use]
+    pub fn merge_defaults(self, defaults: Args) -> Args {
+        defaults.merge(self)
+    }
+
+    pub(crate) fn from_iter_os(
+        args: impl Iterator<Item = OsString>,
+        positional_definitions: &[PositionalDefinition],
+        flag_definitions: &[FlagDefinition],
+        prompt: &mut dyn PromptSource,
+    ) -> Result<Args, ArgumentError> {
+        Args::from_iter_os_with_env(
+            args,
+            &legacy_config(),
+            positional_definitions,
+            flag_definitions,
+            |name| std::env::var_os(name),
+            prompt,
+        )
+    }
+
+    /// Like [`Args::from_iter`], but environment variables are looked up with `env` instead of the process environment, and [`FlagDefinition::prompt`] flags are asked for with `prompt`, so tests don't need to change either.
+    ///
+    /// ```
+    /// # use std::ffi::OsString;
+    /// # use flagged_cl_args::{Args, FlagDefinition, NoPrompt, ParserConfig, VariantFlag};
+    /// let flags = [FlagDefinition::new("database-url", VariantFlag::string()).env_var("DATABASE_URL")];
+    /// let env = |name: &str| (name == "DATABASE_URL").then(|| OsString::from("postgres://localhost"));
+    /// let args = Args::from_iter_with_env(["my-tool"].map(String::from).into_iter(), &ParserConfig::default(), &[], &flags, env, &mut NoPrompt).unwrap();
+    /// assert_eq!(args.get_named_string("database-url"), Some("postgres://localhost"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See [`Args::new`].
+    pub fn from_iter_with_env(
+        args: impl Iterator<Item = String>,
+        config: &ParserConfig,
+        positional_definitions: &[PositionalDefinition],
+        flag_definitions: &[FlagDefinition],
+        env: impl Fn(&str) -> Option<OsString>,
+        prompt: &mut dyn PromptSource,
+    ) -> Result<Args, ArgumentError> {
+        Args::from_iter_os_with_env(
+            args.map(OsString::from),
+            config,
+            positional_definitions,
+            flag_definitions,
+            env,
+            prompt,
+        )
+    }
+
+    pub(crate) fn from_iter_os_with_env(
+        args: impl Iterator<Item = OsString>,
+        config: &ParserConfig,
+        positional_definitions: &[PositionalDefinition],
+        flag_definitions: &[FlagDefinition],
+        env: impl Fn(&str) -> Option<OsString>,
+        prompt: &mut dyn PromptSource,
+    ) -> Result<Args, ArgumentError> {
+        Args::from_iter_os_with_index(
+            args,
+            config,
+            positional_definitions,
+            flag_definitions,
+            None,
+            env,
+            prompt,
+        )
+    }
+
+    /// Like [`Args::from_iter_os_with_env`], but with an `index` already built for definitions that were already checked.
+    pub(crate) fn from_iter_os_with_index(
+        args: impl Iterator<Item = OsString>,
+        config: &ParserConfig,
+        positional_definitions: &[PositionalDefinition],
+        flag_definitions: &[FlagDefinition],
+        index: Option<&FlagIndex>,
+        env: impl Fn(&str) -> Option<OsString>,
+        prompt: &mut dyn PromptSource,
+    ) -> Result<Args, ArgumentError> {
+        let mut args: Vec<OsString> = args.collect();
+        if let Some(format) = config.response_files {
+            let lookup = FlagLookup::new(config, flag_definitions);
+            args = expand_response_files(args, format, &|arg| {
+                file_values_after(config, &lookup, arg)
+            })?;
+        }
+        Args::from_args(
+            &args,
+            config,
+            positional_definitions,
+            flag_definitions,
+            index,
+            env,
+            prompt,
+        )
+        .map_err(|error| match args.first() {
+            Some(binary) if config.usage_in_errors => error.with_usage(&help::usage(
+                &binary.to_string_lossy(),
+                positional_definitions,
+                flag_definitions,
+            )),
+            _ => error,
+        })
+    }
+
+    pub(crate) fn from_iter_subcommands(
+        args: impl Iterator<Item = OsString>,
+        config: &ParserConfig,
+        global_flag_definitions: &[FlagDefinition],
+        subcommands: &[Subcommand],
+        env: impl Fn(&str) -> Option<OsString>,
+        prompt: &mut dyn PromptSource,
+    ) -> Result<(Args, Option<(String, Args)>), ArgumentError> {
+        let mut args: Vec<OsString> = args.collect();
+        if let Some(format) = config.response_files {
+            // Which flags are before the subcommand isn't known yet, so any of them could be
+            let lookups: Vec<FlagLookup> = std::iter::once(global_flag_definitions)
+                .chain(
+                    subcommands
+                        .iter()
+                        .map(|subcommand| subcommand.flag_definitions.as_slice()),
+                )
+                .map(|flag_definitions| FlagLookup::new(config, flag_definitions))
+                .collect();
+            args = expand_response_files(args, format, &|arg| {
+                lookups
+                    .iter()
+                    .map(|lookup| file_values_after(config, lookup, arg))
+                    .max()
+                    .unwrap_or(0)
+            })?;
+        }
+        // Everything is expanded already, so `@@` isn't unescaped twice
+        let config = &ParserConfig {
+            response_files: None,
+            ..config.clone()
+        };
+        let Some(position) = find_subcommand(&args, config, global_flag_definitions) else {
+            let global = Args::from_iter_os_with_env(
+                args.into_iter(),
+                config,
+                &[],
+                global_flag_definitions,
+                &env,
+                prompt,
+            )?;
+            return Ok((global, None));
+        };
+        let global = Args::from_iter_os_with_env(
+            args[..position].iter().cloned(),
+            config,
+            &[],
+            global_flag_definitions,
+            &env,
+            prompt,
+        )?;
+        let input_name = args[position].to_string_lossy();
+        let subcommand = subcommands
+            .iter()
+            .find(|subcommand| {
+                subcommand.name == input_name
+                    || subcommand.aliases.iter().any(|alias| *alias == input_name)
+            })
+            .ok_or_else(|| {
+                let valid: Vec<&str> = subcommands
+                    .iter()
+                    .map(|subcommand| subcommand.name.as_str())
+                    .collect();
+                let suggestion = closest(
+                    &input_name,
+                    subcommands.iter().flat_map(|subcommand| {
+                        std::iter::once(subcommand.name.as_str())
+                            .chain(subcommand.aliases.iter().map(String::as_str))
+                    }),
+                )
+                .map(|name| format!(" (did you mean {name}?)"))
+                .unwrap_or_default();
+                ArgumentError::new(&format!(
+                    "Unknown subcommand {input_name} at position {position}, expected one of: {}{suggestion}",
+                    valid.join(", ")
+                ))
+            })?;
+        let binary = OsString::from(format!("{} {}", global.binary, subcommand.name));
+        let parsed = Args::from_iter_os_with_env(
+            std::iter::once(binary).chain(args[position + 1..].iter().cloned()),
+            config,
+            &subcommand.positional_definitions,
+            &subcommand.flag_definitions,
+            &env,
+            prompt,
+        )?;
+        Ok((global, Some((subcommand.name.clone(), parsed))))
+    }
+
+    fn from_args(
+        args: &[OsString],
+        config: &ParserConfig,
+        positional_definitions: &[PositionalDefinition],
+        flag_definitions: &[FlagDefinition],
+        index: Option<&FlagIndex>,
+        env: impl Fn(&str) -> Option<OsString>,
+        prompt: &mut dyn PromptSource,
+    ) -> Result<Args, ArgumentError> {
+        let lookup = match index {
+            Some(index) => FlagLookup {
+                definitions: flag_definitions,
+                index: Cow::Borrowed(index),
+            },
+            None => {
+                check_definitions(config, positional_definitions, flag_definitions)?;
+                FlagLookup::new(config, flag_definitions)
+            }
+        };
+        let binary = args
+            .first()
+            .ok_or(ArgumentError::new("Argument count is 0"))?
+            .to_string_lossy()
+            .into_owned();
+        if config.auto_help && help::help_requested(&args[1..], config, flag_definitions) {
+            // Nothing else matters when the user is asking for help, even if it's wrong
+            return Ok(Args {
+                binary,
+                positional: Vec::new(),
+                named: HashMap::from([(Arc::from("help"), Variant::Bool(true))]),
+                trailing: Vec::new(),
+                trailing_lossy: Vec::new(),
+                rest_start: None,
+                positional_names: Vec::new(),
+                unknown: Vec::new(),
+                warnings: Vec::new(),
+                sources: HashMap::new(),
+                positional_sources: Vec::new(),
+                events: Vec::new(),
+                raw_named: HashMap::new(),
+                raw_positional: Vec::new(),
+                sensitive: HashSet::new(),
+            });
+        }
+        if let Some(version) = &config.version
+            && help::version_requested(&args[1..], config, flag_definitions)
+        {
+            return Ok(Args {
+                binary,
+                positional: Vec::new(),
+                named: HashMap::from([(Arc::from("version"), Variant::String(version.clone()))]),
+                trailing: Vec::new(),
+                trailing_lossy: Vec::new(),
+                rest_start: None,
+                positional_names: Vec::new(),
+                unknown: Vec::new(),
+                warnings: Vec::new(),
+                sources: HashMap::new(),
+                positional_sources: Vec::new(),
+                events: Vec::new(),
+                raw_named: HashMap::new(),
+                raw_positional: Vec::new(),
+                sensitive: HashSet::new(),
+            });
+        }
+        let mut args = args.iter().cloned().enumerate().skip(1);
         let mut named = HashMap::new();
         let mut positional = Vec::new();
+        let mut trailing = Vec::new();
+        let mut unknown = Vec::new();
+        let mut warnings = Vec::new();
+        let mut sources = HashMap::new();
+        let mut positional_sources = Vec::new();
+        let mut events = Vec::new();
+        let mut raw_named = HashMap::new();
+        let mut raw_positional = Vec::new();
+        let mut after_separator = false;
+        // The last positional argument takes every remaining value
+        let variadic = positional_definitions
+            .last()
+            .is_some_and(|definition| definition.arity != PositionalArity::One);
+        let mut extra_positionals = 0;
+        // Where the error about extra positional arguments goes among the others
+        let mut too_many_at = 0;
+        let mut errors = Vec::new();
+        // Names of negatable flags, and whether they were given in their `--no-` spelling
+        let mut spellings: HashMap<&str, bool> = HashMap::new();
         while let Some((index, arg)) = args.next() {
-            // Determine if the given flag matches a flag definition
-            if let Some(matched_definition) = match_flag_definition(flag_definitions, &arg)? {
+            if !after_separator && arg == "--" {
+                // Everything after a bare `--` is positional, even if it looks like a flag
+                after_separator = true;
+                continue;
+            }
+            // Determine if the given flag matches a flag definition, flags are always valid UTF-8
+            let flag_matches = match arg.to_str() {
+                Some(arg) if !after_separator => {
+                    match match_flag_definition(config, &lookup, arg, index) {
+                        Ok(flag_matches) => flag_matches,
+                        Err(error)
+                            if config.allow_unknown_flags
+                                && matches!(
+                                    error.kind(),
+                                    ArgumentErrorKind::UnknownFlag { .. }
+                                ) =>
+                        {
+                            // The whole token is kept, along with a value attached with `=`
+                            unknown.push(arg.to_string());
+                            continue;
+                        }
+                        Err(error) => {
+                            recover(config, &mut errors, error)?;
+                            continue;
+                        }
+                    }
+                }
+                _ => Vec::new(),
+            };
+            let is_positional = flag_matches.is_empty();
+            for FlagMatch {
+                definition: matched_definition,
+                value: attached_value,
+                negated,
+                spelling,
+            } in flag_matches
+            {
+                if let Some(message) = &matched_definition.deprecated {
+                    warnings.push(format!(
+                        "{spelling} at position {index} is deprecated: {message}"
+                    ));
+                }
+                if matched_definition.negatable
+                    && *spellings
+                        .entry(matched_definition.name.as_str())
+                        .or_insert(negated)
+                        != negated
+                {
+                    recover(
+                        config,
+                        &mut errors,
+                        ArgumentError::new(&format!(
+                            "--{0} and --no-{0} cannot both be given",
+                            matched_definition.name
+                        )),
+                    )?;
+                    continue;
+                }
                 // If the argument is named, we will put it into the hashmap.
-                if matched_definition.allowed_type.is_unit() {
-                    // There is no next arg, this flag is either present or not present
-                    named.insert(matched_definition.name.clone(), Variant::Bool(true));
+                // The text of the value, for flags that take one
+                let mut raw = None;
+                let given: Result<Variant, ArgumentError> = if negated {
+                    if attached_value.is_some() {
+                        recover(
+                            config,
+                            &mut errors,
+                            ArgumentError::new(&format!(
+                                "--no-{} at position {index} does not take a value",
+                                matched_definition.name
+                            )),
+                        )?;
+                        continue;
+                    }
+                    Ok(Variant::Bool(false))
+                } else if matched_definition.allowed_type.is_count() {
+                    if attached_value.is_some() {
+                        recover(
+                            config,
+                            &mut errors,
+                            ArgumentError::new(&format!(
+                                "--{} at position {index} does not take a value",
+                                matched_definition.name
+                            )),
+                        )?;
+                        continue;
+                    }
+                    // Every occurrence adds to the count, regardless of whether the name or abbreviation was used
+                    let count = named
+                        .entry(lookup.key(matched_definition))
+                        .or_insert(Variant::Int(0));
+                    if let Variant::Int(count) = count {
+                        *count = count.saturating_add(1);
+                    }
+                    Ok(Variant::Int(1))
+                } else if matched_definition.allowed_type.is_unit() {
+                    // There is no next arg, this flag is either present or not present, unless it was explicitly set with `=`
+                    let present = match attached_value
+                        .map(|value| matched_definition.allowed_type.parse_unit_value(value))
+                    {
+                        None => true,
+                        Some(Some(present)) => present,
+                        Some(None) => {
+                            recover(
+                                config,
+                                &mut errors,
+                                ArgumentError::new(&format!(
+                                    "--{0} at position {index} does not take a value other than true or false, like --{0}=false",
+                                    matched_definition.name
+                                )),
+                            )?;
+                            continue;
+                        }
+                    };
+                    Ok(Variant::Bool(present))
+                } else if let (None, Some(bare)) =
+                    (attached_value, &matched_definition.optional_value)
+                {
+                    // The next argument is never the value of a flag whose value is optional
+                    Ok(bare.clone())
+                } else if matched_definition.num_values > 1 {
+                    let values = take_values(&mut args, matched_definition, index, attached_value)?;
+                    let raw_values: Vec<_> = values
+                        .iter()
+                        .map(|(_, value)| value.to_string_lossy())
+                        .collect();
+                    raw = Some(raw_values.join(" "));
+                    values
+                        .iter()
+                        .map(|(index, value)| {
+                            let contents = value_from_file(matched_definition, *index, value)?;
+                            matched_definition
+                                .allowed_type
+                                .try_parse_os(&contents)
+                                .map_err(|failure| {
+                                    flag_parse_failed(matched_definition, *index, value, &failure)
+                                })
+                        })
+                        .collect::<Result<_, _>>()
+                        .map(Variant::List)
                 } else {
-                    // The next argument is a value for this flag
-                    let (index, value) = args.next().ok_or(ArgumentError::new(&format!(
-                        "Unexpected end of arguments, {} needs a value",
-                        matched_definition.name
-                    )))?;
-                    named.insert(
-                        matched_definition.name.clone(),
+                    // The value is either attached with `=`, or the next argument
+                    // Running out of arguments can't be recovered from, but there is nothing left to parse anyway
+                    let (index, value) = match attached_value {
+                        Some(value) => (index, OsString::from(value)),
+                        None => args.next().ok_or_else(|| ArgumentErrorKind::MissingValue {
+                            flag: matched_definition.name.clone(),
+                        })?,
+                    };
+                    raw = Some(value.to_string_lossy().into_owned());
+                    value_from_file(matched_definition, index, &value).and_then(|contents| {
                         matched_definition
                             .allowed_type
-                            .parse(&value)
-                            .ok_or(ArgumentError::new(&format!(
-                                "Argument {value} at position {index} is not a valid type for --{}",
-                                matched_definition.name
-                            )))?,
-                    );
+                            .try_parse_os(&contents)
+                            .map_err(|failure| {
+                                flag_parse_failed(matched_definition, index, &value, &failure)
+                            })
+                    })
+                };
+                let key = lookup.key(matched_definition);
+                let inserted = given.and_then(|value| {
+                    // Counts were already added to
+                    if !matched_definition.allowed_type.is_count() {
+                        insert_named(&mut named, &key, matched_definition, value.clone())?;
+                    }
+                    Ok(value)
+                });
+                match inserted {
+                    Ok(value) => {
+                        sources.insert(key.clone(), ValueSource::CommandLine { index });
+                        match raw {
+                            Some(raw) => raw_named.insert(key.clone(), raw),
+                            None => raw_named.remove(&key),
+                        };
+                        events.push(ArgEvent::Named {
+                            index,
+                            name: key,
+                            value,
+                            sensitive: matched_definition.sensitive,
+                        });
+                    }
+                    Err(error) => recover(config, &mut errors, error)?,
                 }
-            } else {
+            }
+            if is_positional
+                && after_separator
+                && !variadic
+                && positional.len() == positional_definitions.len()
+            {
+                // Extra arguments after `--` are kept as they are
+                trailing.push(arg.clone());
+            } else if is_positional {
                 // If the argument is not named, it must be positional!
                 let pos_index = positional.len();
-                let allowed_types = positional_types.get(pos_index).ok_or(ArgumentError::new(
-                    "There are too many positional arguments",
-                ))?;
-                positional.push(allowed_types.parse(&arg).ok_or(ArgumentError::new(&format!(
-                    "Positional argument {pos_index} at position {index} cannot be parsed as type {allowed_types}"
-                )))?);
+                let definition = positional_definitions
+                    .get(pos_index)
+                    .or(positional_definitions.last().filter(|_| variadic));
+                let Some(definition) = definition else {
+                    // Keep going, so the error can say how many there were
+                    if extra_positionals == 0 {
+                        too_many_at = errors.len();
+                    }
+                    extra_positionals += 1;
+                    continue;
+                };
+                let allowed_types = &definition.allowed_type;
+                let parsed = allowed_types
+                    .try_parse_os(&arg)
+                    .map_err(|failure| {
+                        parse_failed(
+                            positional_label(pos_index, definition),
+                            index,
+                            &arg,
+                            allowed_types,
+                            &failure,
+                        )
+                    })
+                    .and_then(|parsed| {
+                        validate(definition.validator.as_deref(), &parsed, false, || {
+                            format!(
+                                "{} at position {index}",
+                                positional_label(pos_index, definition)
+                            )
+                        })
+                        .map(|()| parsed)
+                    });
+                match parsed {
+                    Ok(parsed) => {
+                        events.push(ArgEvent::Positional {
+                            index,
+                            value: parsed.clone(),
+                        });
+                        positional.push(parsed);
+                    }
+                    Err(error) => {
+                        recover(config, &mut errors, error)?;
+                        // Nothing is returned once there are errors, this keeps later positional arguments numbered correctly
+                        positional.push(Variant::String(arg.to_string_lossy().into_owned()));
+                    }
+                }
+                positional_sources.push(ValueSource::CommandLine { index });
+                raw_positional.push(Some(arg.to_string_lossy().into_owned()));
             }
         }
 
-        if positional.len() != positional_types.len() {
-            return Err(ArgumentError::new(
-                "Not enough positional arguments were supplied",
-            ));
+        if extra_positionals > 0 {
+            let error = ArgumentErrorKind::TooManyPositionals {
+                max: positional_definitions.len(),
+                got: positional_definitions.len() + extra_positionals,
+            };
+            if !config.collect_errors {
+                return Err(error.into());
+            }
+            // Reported where the first one too many was given
+            errors.insert(too_many_at, error.into());
+        }
+        let required = positional_definitions
+            .iter()
+            .take_while(|definition| !definition.is_optional())
+            .count();
+        if positional.len() < required {
+            recover(
+                config,
+                &mut errors,
+                ArgumentErrorKind::NotEnoughPositionals {
+                    expected: required,
+                    got: positional.len(),
+                    missing: (positional.len()..required)
+                        .map(|index| help::positional_column(index, &positional_definitions[index]))
+                        .collect(),
+                },
+            )?;
+        }
+
+        match errors.len() {
+            0 => {}
+            1 => return Err(errors.remove(0)),
+            _ => return Err(ArgumentErrorKind::Multiple(errors).into()),
+        }
+
+        for definition in flag_definitions {
+            if named.contains_key(definition.name.as_str()) {
+                continue;
+            }
+            // The environment takes precedence over the default
+            let env_var = match (&definition.env_var, &config.env_prefix) {
+                _ if definition.skip_env => None,
+                (Some(env_var), _) => Some(env_var.clone()),
+                (None, Some(env_prefix)) => Some(derive_env_var(env_prefix, &definition.name)),
+                (None, None) => None,
+            };
+            if let Some(env_var) = env_var
+                && let Some(value) = env(&env_var)
+            {
+                let parsed = parse_env_value(definition, &value).map_err(|reason| {
+                    let value = value.to_string_lossy();
+                    ArgumentError::new(&match reason {
+                        _ if definition.sensitive => format!(
+                            "Environment variable {env_var} for --{} (redacted) could not be parsed as type {}",
+                            definition.name, definition.allowed_type
+                        ),
+                        Some(reason) => format!(
+                            "Environment variable {env_var} for --{}: {reason}",
+                            definition.name
+                        ),
+                        None => format!(
+                            "Environment variable {env_var}={value} is not a valid type for --{}",
+                            definition.name
+                        ),
+                    })
+                })?;
+                let key = lookup.key(definition);
+                insert_named(&mut named, &key, definition, parsed)?;
+                if !definition.allowed_type.is_unit() && !definition.allowed_type.is_count() {
+                    raw_named.insert(key.clone(), value.to_string_lossy().into_owned());
+                }
+                sources.insert(key, ValueSource::Environment { var: env_var });
+                continue;
+            }
+            // Then asking, which an empty answer skips when there is a default
+            if let Some(text) = &definition.prompt {
+                if !prompt.is_interactive() {
+                    if definition.default.is_none() {
+                        return Err(ArgumentError::new(&format!(
+                            "--{} was not given, and there is no terminal to ask for it",
+                            definition.name
+                        )));
+                    }
+                } else {
+                    let answer = prompt
+                        .read_line(text, definition.sensitive)
+                        .map_err(|error| {
+                            ArgumentError::new(&format!(
+                                "Could not read an answer for --{}: {error}",
+                                definition.name
+                            ))
+                            .with_source(Arc::new(error))
+                        })?;
+                    if !answer.is_empty() || definition.default.is_none() {
+                        let parsed = parse_env_value(definition, OsStr::new(&answer)).map_err(
+                            |reason| {
+                                ArgumentError::new(&match reason {
+                                    _ if definition.sensitive => format!(
+                                        "The answer for --{} (redacted) could not be parsed as type {}",
+                                        definition.name, definition.allowed_type
+                                    ),
+                                    Some(reason) => {
+                                        format!("The answer for --{}: {reason}", definition.name)
+                                    }
+                                    None => format!(
+                                        "The answer '{answer}' is not a valid type for --{}",
+                                        definition.name
+                                    ),
+                                })
+                            },
+                        )?;
+                        let key = lookup.key(definition);
+                        insert_named(&mut named, &key, definition, parsed)?;
+                        if !definition.allowed_type.is_unit() && !definition.allowed_type.is_count()
+                        {
+                            raw_named.insert(key.clone(), answer);
+                        }
+                        sources.insert(key, ValueSource::Prompt);
+                        continue;
+                    }
+                }
+            }
+            if definition.default.is_none() {
+                continue;
+            }
+            let default = flag_default(definition).expect("defaults were checked");
+            let key = lookup.key(definition);
+            named.insert(key.clone(), default);
+            sources.insert(key, ValueSource::Default);
+        }
+
+        // After the environment and answers, which count as given too
+        check_constraints(flag_definitions, &sources)?;
+
+        // Optional positional arguments that weren't given take their defaults
+        for (index, definition) in positional_definitions
+            .iter()
+            .enumerate()
+            .skip(positional.len())
+        {
+            let Some(default) = &definition.default else {
+                break;
+            };
+            debug_assert!(
+                definition.allowed_type.allows_value(default),
+                "defaults were checked"
+            );
+            validate(definition.validator.as_deref(), default, false, || {
+                positional_label(index, definition)
+            })?;
+            positional.push(default.clone());
+            positional_sources.push(ValueSource::Default);
+            raw_positional.push(None);
+        }
+
+        // Validators run last, so they see values from every source
+        for definition in flag_definitions {
+            if let Some(value) = named.get(definition.name.as_str()) {
+                validate_named(definition, value, || format!("--{}", definition.name))?;
+            }
         }
 
         Ok(Args {
             binary,
             positional,
             named,
+            trailing_lossy: trailing
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            trailing,
+            rest_start: variadic.then(|| positional_definitions.len() - 1),
+            positional_names: positional_definitions
+                .iter()
+                .map(|definition| definition.name.clone())
+                .collect(),
+            unknown,
+            warnings,
+            sources,
+            positional_sources,
+            events,
+            raw_named,
+            raw_positional,
+            sensitive: flag_definitions
+                .iter()
+                .filter(|definition| definition.sensitive)
+                .map(|definition| Arc::from(definition.name.as_str()))
+                .collect(),
         })
     }
 
+    /// Fills in named arguments from a TOML config file, for the classic order of precedence: defaults, then the config file, then the environment, then the command line.
+    ///
+    /// Keys are flag names or aliases, written as they are or with underscores instead of dashes, like `log-level` or `log_level`.
+    /// Values replace defaults, but not values from the environment or command line.
+    /// Booleans set unit flags, and everything else is parsed like it was given on the command line, so `threads = 4` works for a flag that allows ints or floats.
+    /// Arrays are only allowed for flags that collect their values, see [`RepeatPolicy::Collect`], and a single value for those is a list of one, like on the command line.
+    /// Once the values are filled in, conflicts and requirements between flags are checked again, see [`FlagDefinition::conflicts_with`].
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, RepeatPolicy, ValueSource, Variant, VariantFlag};
+    /// let flags = [
+    ///     FlagDefinition::new("threads", VariantFlag::int()).default(Variant::Int(1)),
+    ///     FlagDefinition::new("log-level", VariantFlag::string()),
+    /// ];
+    /// let mut args = Args::from_slice(&["my-tool", "--log-level", "debug"], &[], &flags).unwrap();
+    /// let table: toml::Table = "threads = 4\nlog_level = \"info\"".parse().unwrap();
+    /// args.overlay_config(&table, &flags).unwrap();
+    /// assert_eq!(args.get_named_int("threads"), Some(4));
+    /// assert_eq!(args.source_of("threads"), Some(ValueSource::ConfigFile));
+    /// assert_eq!(args.get_named_string("log-level"), Some("debug"));
+    /// assert_eq!(args.source_of("log-level"), Some(ValueSource::CommandLine { index: 1 }));
+    ///
+    /// let mut args = Args::from_slice(&["my-tool"], &[], &flags).unwrap();
+    /// let table: toml::Table = "thread = 4\nthreads = \"four\"".parse().unwrap();
+    /// assert_eq!(
+    ///     args.overlay_config(&table, &flags).unwrap_err().to_string(),
+    ///     "Config key thread does not match any known flag name\n\
+    ///      Config key threads: 'four' cannot be parsed as type int"
+    /// );
+    ///
+    /// let flags = [FlagDefinition::new("include", VariantFlag::path())
+    ///     .alias("include-dir")
+    ///     .repeat(RepeatPolicy::Collect)];
+    /// let from_command_line = Args::from_slice(&["my-tool", "--include", "a"], &[], &flags).unwrap();
+    /// let mut args = Args::from_slice(&["my-tool"], &[], &flags).unwrap();
+    /// args.overlay_config(&"include_dir = \"a\"".parse().unwrap(), &flags).unwrap();
+    /// assert_eq!(args.get_named("include"), from_command_line.get_named("include"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Every key that isn't a flag, and every value that isn't valid for its flag, naming the key.
+    /// Values that are valid are still filled in.
+    #[cfg(feature = "toml")]
+    pub fn overlay_config(
+        &mut self,
+        table: &toml::Table,
+        flag_definitions: &[FlagDefinition],
+    ) -> Result<(), ArgumentError> {
+        self.overlay(
+            table
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.into())),
+            flag_definitions,
+        )
+    }
+
+    /// Like [`Args::overlay_config`], but for a JSON object.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, VariantFlag};
+    /// let flags = [FlagDefinition::new("verbose", VariantFlag::new_unit())];
+    /// let mut args = Args::from_slice(&["my-tool"], &[], &flags).unwrap();
+    /// let serde_json::Value::Object(object) = serde_json::json!({ "verbose": true }) else {
+    ///     unreachable!()
+    /// };
+    /// args.overlay_json(&object, &flags).unwrap();
+    /// assert_eq!(args.get_named_bool("verbose"), Some(true));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See [`Args::overlay_config`].
+    #[cfg(feature = "json")]
+    pub fn overlay_json(
+        &mut self,
+        object: &serde_json::Map<String, serde_json::Value>,
+        flag_definitions: &[FlagDefinition],
+    ) -> Result<(), ArgumentError> {
+        self.overlay(
+            object
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.into())),
+            flag_definitions,
+        )
+    }
+
+    #[cfg(any(feature = "toml", feature = "json"))]
+    fn overlay<'a>(
+        &mut self,
+        entries: impl Iterator<Item = (&'a str, ConfigValue)>,
+        flag_definitions: &[FlagDefinition],
+    ) -> Result<(), ArgumentError> {
+        let mut problems = Vec::new();
+        for (key, value) in entries {
+            let Some(definition) = flag_definitions.iter().find(|definition| {
+                names(definition).any(|name| name == key || name == key.replace('_', "-"))
+            }) else {
+                problems.push(format!(
+                    "Config key {key} does not match any known flag name"
+                ));
+                continue;
+            };
+            if self.named.contains_key(definition.name.as_str())
+                && self.sources.get(definition.name.as_str()) != Some(&ValueSource::Default)
+            {
+                continue;
+            }
+            let parsed = config_occurrences(definition, value)
+                .map_err(|reason| {
+                    ArgumentError::new(&if definition.sensitive {
+                        format!(
+                            "Config key {key}: value for --{} (redacted) could not be parsed as type {}",
+                            definition.name, definition.allowed_type
+                        )
+                    } else {
+                        format!("Config key {key}: {reason}")
+                    })
+                })
+                .and_then(|parsed| {
+                    validate_named(definition, &parsed, || format!("Config key {key}"))
+                        .map(|()| parsed)
+                });
+            match parsed {
+                Ok(parsed) => {
+                    let key: Arc<str> = Arc::from(definition.name.as_str());
+                    self.named.insert(key.clone(), parsed);
+                    self.sources.insert(key, ValueSource::ConfigFile);
+                    self.raw_named.remove(definition.name.as_str());
+                }
+                Err(error) => problems.push(error.to_string()),
+            }
+        }
+        if problems.is_empty()
+            && let Err(error) = check_constraints(flag_definitions, &self.sources)
+        {
+            problems.push(error.to_string());
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ArgumentError::new(&problems.join("\n")))
+        }
+    }
+
     /// Get the first argument, which is normally the name of the binary
     pub fn binary(&self) -> &str {
         &self.binary
@@ -109,41 +1545,2208 @@ impl Args {
         self.positional.get(index)
     }
 
-    /// Gets a named argument.
-    pub fn get_named(&self, name: &str) -> Option<&Variant> {
-        self.named.get(name)
+    /// Gets a positional argument by its [`PositionalDefinition::name`], so that indices don't need to be kept in sync across your program.
+    /// For a last argument that takes any number of values, this is the first of them, see [`Args::positional_rest`] for all of them.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Parser, PositionalDefinition, VariantFlag};
+    /// let parser = Parser::new("my-tool")
+    ///     .positional(PositionalDefinition::from(VariantFlag::path()).name("input-path"))
+    ///     .positional(PositionalDefinition::from(VariantFlag::path()).name("output-path"));
+    ///
+    /// let args = parser.parse_from(["my-tool", "in.txt", "out.txt"]).unwrap();
+    /// assert_eq!(args.get_positional_by_name("output-path"), args.get_positional(1));
+    /// assert!(args.get_positional_by_name("output").is_none());
+    /// assert_eq!(parser.usage(), "usage: my-tool <input-path> <output-path>");
+    ///
+    /// let parser = Parser::new("my-tool").positional(PositionalDefinition::from(VariantFlag::int()).name("count"));
+    /// let Err(error) = parser.parse_from(["my-tool", "many"]) else {
+    ///     panic!("many is not an int");
+    /// };
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "<count> at position 1: 'many' cannot be parsed as type int: invalid digit found in string"
+    /// );
+    /// ```
+    pub fn get_positional_by_name(&self, name: &str) -> Option<&Variant> {
+        let index = self
+            .positional_names
+            .iter()
+            .position(|positional_name| positional_name.as_deref() == Some(name))?;
+        self.positional.get(index)
     }
-}
 
-fn match_flag_definition<'a>(
-    flag_definitions: &'a [FlagDefinition],
-    arg: &str,
-) -> Result<Option<&'a FlagDefinition>, ArgumentError> {
-    Ok(if arg.starts_with("--") {
-        let input_name: String = arg.chars().skip(2).collect();
-        Some(
-            flag_definitions
-                .iter()
-                .find(|definition| definition.name == input_name)
-                .ok_or(ArgumentError::new(&format!(
-                    "--{input_name} does not match any known flag name"
-                )))?,
-        )
-    } else if arg.starts_with('-') && arg.chars().count() == 2 {
-        let input_char = arg.chars().last().ok_or(ArgumentError::new("Infallible"))?;
-        Some(
-            flag_definitions
-                .iter()
-                .find(|definition| {
-                    definition
-                        .abbreviation
-                        .is_some_and(|abbreviation| input_char == abbreviation)
-                })
-                .ok_or(ArgumentError::new(&format!(
-                    "-{input_char} does not match any known flag abbreviation"
-                )))?,
-        )
-    } else {
-        None
-    })
+    /// Where the value of the named argument `name` came from, so you can tell your end user why it is what it is.
+    /// Arguments made with [`Args::from_map`] have no source.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag, ValueSource};
+    /// let flags = [
+    ///     FlagDefinition::new("threads", VariantFlag::int()).default(Variant::Int(1)),
+    ///     FlagDefinition::new("level", VariantFlag::int()).default(Variant::Int(3)),
+    ///     FlagDefinition::new("name", VariantFlag::string()),
+    /// ];
+    /// let args = Args::from_slice(&["my-tool", "in.txt", "--name", "x", "--threads", "8"], &[VariantFlag::path()], &flags).unwrap();
+    ///
+    /// assert_eq!(args.source_of("threads"), Some(ValueSource::CommandLine { index: 4 }));
+    /// assert_eq!(args.source_of("level"), Some(ValueSource::Default));
+    /// assert_eq!(args.source_of("missing"), None);
+    /// assert_eq!(args.positional_source_of(0), Some(ValueSource::CommandLine { index: 1 }));
+    /// ```
+    pub fn source_of(&self, name: &str) -> Option<ValueSource> {
+        self.sources.get(name).cloned()
+    }
+
+    /// Where the value of a positional argument came from, see [`Args::source_of`].
+    pub fn positional_source_of(&self, index: usize) -> Option<ValueSource> {
+        self.positional_sources.get(index).cloned()
+    }
+
+    /// Iterates over the named and positional arguments given on the command line, in the order they were given.
+    /// Useful when the order of flags matters relative to positional arguments, like the expressions of `find`.
+    ///
+    /// Each time a flag is given is a separate event, with the value given that time.
+    /// Unit flags have a value of `true`, or `false` when negated, and counted flags have a value of 1.
+    /// Values from the environment, config files, and defaults aren't included, and neither are [`Args::unknown`] or [`Args::trailing`] arguments.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{ArgEvent, Args, FlagDefinition, RepeatPolicy, Variant, VariantFlag};
+    /// let flags = [
+    ///     FlagDefinition::new("name", VariantFlag::string()).repeat(RepeatPolicy::Collect),
+    ///     FlagDefinition::new("print", VariantFlag::new_unit()),
+    /// ];
+    /// let args = Args::from_slice(
+    ///     &["find", "src", "--name", "*.rs", "--print", "docs", "--name", "*.md"],
+    ///     &[VariantFlag::path(), VariantFlag::path()],
+    ///     &flags,
+    /// )
+    /// .unwrap();
+    ///
+    /// let order: Vec<String> = args
+    ///     .events()
+    ///     .map(|event| match event {
+    ///         ArgEvent::Positional { index, value } => format!("{index}: {value}"),
+    ///         ArgEvent::Named { index, name, value, .. } => format!("{index}: --{name} {value}"),
+    ///         _ => unreachable!(),
+    ///     })
+    ///     .collect();
+    /// assert_eq!(order, ["1: src", "2: --name *.rs", "4: --print true", "5: docs", "6: --name *.md"]);
+    /// ```
+    pub fn events(&self) -> impl Iterator<Item = &ArgEvent> {
+        self.events.iter()
+    }
+
+    /// Gets the exact text a named argument's value was parsed from, like `1.50` for a value of 1.5.
+    ///
+    /// Values from the command line and the environment have their text, the last one if the flag was given more than once.
+    /// Unit flags, counted flags, defaults, config files, and [`Args::from_map`] have none.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+    /// let flags = [
+    ///     FlagDefinition::new("ratio", VariantFlag::float()),
+    ///     FlagDefinition::new("level", VariantFlag::int()).default(Variant::Int(3)),
+    ///     FlagDefinition::new("force", VariantFlag::new_unit()),
+    /// ];
+    /// let args = Args::from_slice(&["my-tool", "--ratio=1.50", "0x1F", "--force"], &[VariantFlag::int()], &flags).unwrap();
+    ///
+    /// assert_eq!(args.get_named_float("ratio"), Some(1.5));
+    /// assert_eq!(args.get_named_raw("ratio"), Some("1.50"));
+    /// assert_eq!(args.get_named_raw("level"), None);
+    /// assert_eq!(args.get_named_raw("force"), None);
+    /// assert_eq!(args.get_positional_raw(0), Some("0x1F"));
+    /// ```
+    pub fn get_named_raw(&self, name: &str) -> Option<&str> {
+        self.raw_named.get(name).map(String::as_str)
+    }
+
+    /// Gets the exact text a positional argument was parsed from, see [`Args::get_named_raw`].
+    /// Defaults have none.
+    pub fn get_positional_raw(&self, index: usize) -> Option<&str> {
+        self.raw_positional.get(index)?.as_deref()
+    }
+
+    /// Gets a named argument, or else a positional argument with that [`PositionalDefinition::name`].
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Parser, PositionalDefinition, Variant, VariantFlag, FlagDefinition};
+    /// let parser = Parser::new("my-tool")
+    ///     .positional(PositionalDefinition::from(VariantFlag::path()).name("input"))
+    ///     .flag(FlagDefinition::new("threads", VariantFlag::int()));
+    /// let args = parser.parse_from(["my-tool", "in.txt", "--threads", "4"]).unwrap();
+    ///
+    /// assert_eq!(args.lookup("threads"), Some(&Variant::Int(4)));
+    /// assert_eq!(args.lookup("input"), args.get_positional(0));
+    /// assert_eq!(args.lookup("output"), None);
+    /// ```
+    pub fn lookup(&self, key: &str) -> Option<&Variant> {
+        self.get_named(key)
+            .or_else(|| self.get_positional_by_name(key))
+    }
+
+    /// Takes the binary, positional arguments, and named arguments, for when you'd rather own them.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+    /// let flags = [FlagDefinition::new("threads", VariantFlag::int())];
+    /// let args = Args::from_slice(&["my-tool", "in.txt", "--threads", "4"], &[VariantFlag::string()], &flags).unwrap();
+    ///
+    /// let (binary, positional, named) = args.into_parts();
+    /// assert_eq!(binary, "my-tool");
+    /// assert_eq!(positional, [Variant::String("in.txt".to_string())]);
+    /// assert_eq!(named["threads"], Variant::Int(4));
+    /// ```
+    #[must_use]
+    pub fn into_parts(self) -> (String, Vec<Variant>, HashMap<String, Variant>) {
+        let named = self
+            .named
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        (self.binary, self.positional, named)
+    }
+
+    /// Gets a named argument.
+    ///
+    /// Any `&str` finds it, whether it's a literal, borrowed from a [`String`], or the definition's own name.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+    /// let flags = [FlagDefinition::new("threads", VariantFlag::int()).abbrev('t')];
+    /// let args = Args::from_slice(&["my-tool", "-t", "4"], &[], &flags).unwrap();
+    ///
+    /// let name = String::from("threads");
+    /// assert_eq!(args.get_named("threads"), Some(&Variant::Int(4)));
+    /// assert_eq!(args.get_named(&name), Some(&Variant::Int(4)));
+    /// assert_eq!(args.get_named(&flags[0].name), Some(&Variant::Int(4)));
+    /// assert_eq!(args.get_named("t"), None);
+    /// ```
+    pub fn get_named(&self, name: &str) -> Option<&Variant> {
+        self.named.get(name)
+    }
+
+    /// Gets a named argument as any type that implements [`FromVariant`], like `args.get::<i32>("threads")`.
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get<'a, T: FromVariant<'a>>(&'a self, name: &str) -> Option<T> {
+        self.get_named(name).and_then(T::from_variant)
+    }
+
+    /// Gets a positional argument as any type that implements [`FromVariant`], like `args.get_positional_as::<PathBuf>(0)`.
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_positional_as<'a, T: FromVariant<'a>>(&'a self, index: usize) -> Option<T> {
+        self.get_positional(index).and_then(T::from_variant)
+    }
+
+    /// Gets a named argument if it is a [`bool`].
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_named_bool(&self, name: &str) -> Option<bool> {
+        self.get_named(name).and_then(Variant::as_bool)
+    }
+
+    /// Gets a named argument if it is an [`i32`].
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_named_int(&self, name: &str) -> Option<i32> {
+        self.get_named(name).and_then(Variant::as_int)
+    }
+
+    /// Gets a named argument if it is an [`f32`].
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_named_float(&self, name: &str) -> Option<f32> {
+        self.get_named(name).and_then(Variant::as_float)
+    }
+
+    /// Gets a named argument if it is a [`SocketAddr`].
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_named_socket(&self, name: &str) -> Option<SocketAddr> {
+        self.get_named(name).and_then(Variant::as_socket)
+    }
+
+    /// Gets a named argument if it is a [`PathBuf`].
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_named_path(&self, name: &str) -> Option<&PathBuf> {
+        self.get_named(name).and_then(Variant::as_path)
+    }
+
+    /// Gets a named argument if it is a [`String`].
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_named_string(&self, name: &str) -> Option<&str> {
+        self.get_named(name).and_then(Variant::as_string)
+    }
+
+    /// Gets a positional argument if it is a [`bool`], see [`Args::get_positional`].
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_positional_bool(&self, index: usize) -> Option<bool> {
+        self.get_positional(index).and_then(Variant::as_bool)
+    }
+
+    /// Gets a positional argument if it is an [`i32`], see [`Args::get_positional`].
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_positional_int(&self, index: usize) -> Option<i32> {
+        self.get_positional(index).and_then(Variant::as_int)
+    }
+
+    /// Gets a positional argument if it is an [`f32`], see [`Args::get_positional`].
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_positional_float(&self, index: usize) -> Option<f32> {
+        self.get_positional(index).and_then(Variant::as_float)
+    }
+
+    /// Gets a positional argument if it is a [`SocketAddr`], see [`Args::get_positional`].
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_positional_socket(&self, index: usize) -> Option<SocketAddr> {
+        self.get_positional(index).and_then(Variant::as_socket)
+    }
+
+    /// Gets a positional argument if it is a [`PathBuf`], see [`Args::get_positional`].
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_positional_path(&self, index: usize) -> Option<&PathBuf> {
+        self.get_positional(index).and_then(Variant::as_path)
+    }
+
+    /// Gets a positional argument if it is a [`String`], see [`Args::get_positional`].
+    /// Returns None if it wasn't given, or is a different type.
+    pub fn get_positional_string(&self, index: usize) -> Option<&str> {
+        self.get_positional(index).and_then(Variant::as_string)
+    }
+
+    /// Gets a named argument if it is a [`bool`], or `default` if it wasn't given or is a different type.
+    pub fn get_named_bool_or(&self, name: &str, default: bool) -> bool {
+        self.get_named_bool(name).unwrap_or(default)
+    }
+
+    /// Gets a named argument if it is an [`i32`], or `default` if it wasn't given or is a different type.
+    pub fn get_named_int_or(&self, name: &str, default: i32) -> i32 {
+        self.get_named_int(name).unwrap_or(default)
+    }
+
+    /// Gets a named argument if it is an [`f32`], or `default` if it wasn't given or is a different type.
+    pub fn get_named_float_or(&self, name: &str, default: f32) -> f32 {
+        self.get_named_float(name).unwrap_or(default)
+    }
+
+    /// Gets a named argument if it is a [`SocketAddr`], or `default` if it wasn't given or is a different type.
+    pub fn get_named_socket_or(&self, name: &str, default: SocketAddr) -> SocketAddr {
+        self.get_named_socket(name).unwrap_or(default)
+    }
+
+    /// Gets a named argument if it is a [`PathBuf`], or `default` if it wasn't given or is a different type.
+    pub fn get_named_path_or<'a>(&'a self, name: &str, default: &'a Path) -> &'a Path {
+        self.get_named_path(name)
+            .map(PathBuf::as_path)
+            .unwrap_or(default)
+    }
+
+    /// Gets a named argument if it is a [`String`], or `default` if it wasn't given or is a different type.
+    pub fn get_named_string_or<'a>(&'a self, name: &str, default: &'a str) -> &'a str {
+        self.get_named_string(name).unwrap_or(default)
+    }
+
+    /// Gets a positional argument if it is a [`bool`], or `default` if it wasn't given or is a different type.
+    pub fn get_positional_bool_or(&self, index: usize, default: bool) -> bool {
+        self.get_positional_bool(index).unwrap_or(default)
+    }
+
+    /// Gets a positional argument if it is an [`i32`], or `default` if it wasn't given or is a different type.
+    pub fn get_positional_int_or(&self, index: usize, default: i32) -> i32 {
+        self.get_positional_int(index).unwrap_or(default)
+    }
+
+    /// Gets a positional argument if it is an [`f32`], or `default` if it wasn't given or is a different type.
+    pub fn get_positional_float_or(&self, index: usize, default: f32) -> f32 {
+        self.get_positional_float(index).unwrap_or(default)
+    }
+
+    /// Gets a positional argument if it is a [`SocketAddr`], or `default` if it wasn't given or is a different type.
+    pub fn get_positional_socket_or(&self, index: usize, default: SocketAddr) -> SocketAddr {
+        self.get_positional_socket(index).unwrap_or(default)
+    }
+
+    /// Gets a positional argument if it is a [`PathBuf`], or `default` if it wasn't given or is a different type.
+    pub fn get_positional_path_or<'a>(&'a self, index: usize, default: &'a Path) -> &'a Path {
+        self.get_positional_path(index)
+            .map(PathBuf::as_path)
+            .unwrap_or(default)
+    }
+
+    /// Gets a positional argument if it is a [`String`], or `default` if it wasn't given or is a different type.
+    pub fn get_positional_string_or<'a>(&'a self, index: usize, default: &'a str) -> &'a str {
+        self.get_positional_string(index).unwrap_or(default)
+    }
+
+    /// Describes a named value that isn't the type it was expected to be, leaving out the values of [`FlagDefinition::sensitive`] flags.
+    fn wrong_type(&self, name: &str, value: &Variant, expected: &str) -> ArgumentError {
+        if self.sensitive.contains(name) {
+            return ArgumentError::new(&format!("--{name} (redacted) is not {expected}"));
+        }
+        ArgumentError::new(&format!("--{name} is {value}, which is not {expected}"))
+    }
+
+    /// Gets a named argument if it is a [`bool`], returning None if it wasn't given.
+    ///
+    /// # Errors
+    ///
+    /// The argument was given, but is a different type.
+    pub fn try_get_named_bool(&self, name: &str) -> Result<Option<bool>, ArgumentError> {
+        self.get_named(name)
+            .map(|value| {
+                value
+                    .as_bool()
+                    .ok_or_else(|| self.wrong_type(name, value, "a bool"))
+            })
+            .transpose()
+    }
+
+    /// Gets a named argument if it is an [`i32`], returning None if it wasn't given.
+    ///
+    /// # Errors
+    ///
+    /// The argument was given, but is a different type.
+    pub fn try_get_named_int(&self, name: &str) -> Result<Option<i32>, ArgumentError> {
+        self.get_named(name)
+            .map(|value| {
+                value
+                    .as_int()
+                    .ok_or_else(|| self.wrong_type(name, value, "an int"))
+            })
+            .transpose()
+    }
+
+    /// Gets a named argument if it is an [`f32`], returning None if it wasn't given.
+    ///
+    /// # Errors
+    ///
+    /// The argument was given, but is a different type.
+    pub fn try_get_named_float(&self, name: &str) -> Result<Option<f32>, ArgumentError> {
+        self.get_named(name)
+            .map(|value| {
+                value
+                    .as_float()
+                    .ok_or_else(|| self.wrong_type(name, value, "a float"))
+            })
+            .transpose()
+    }
+
+    /// Gets a named argument if it is a [`SocketAddr`], returning None if it wasn't given.
+    ///
+    /// # Errors
+    ///
+    /// The argument was given, but is a different type.
+    pub fn try_get_named_socket(&self, name: &str) -> Result<Option<SocketAddr>, ArgumentError> {
+        self.get_named(name)
+            .map(|value| {
+                value
+                    .as_socket()
+                    .ok_or_else(|| self.wrong_type(name, value, "a socket"))
+            })
+            .transpose()
+    }
+
+    /// Gets a named argument if it is a [`PathBuf`], returning None if it wasn't given.
+    ///
+    /// # Errors
+    ///
+    /// The argument was given, but is a different type.
+    pub fn try_get_named_path(&self, name: &str) -> Result<Option<&PathBuf>, ArgumentError> {
+        self.get_named(name)
+            .map(|value| {
+                value
+                    .as_path()
+                    .ok_or_else(|| self.wrong_type(name, value, "a path"))
+            })
+            .transpose()
+    }
+
+    /// Gets a named argument if it is a [`String`], returning None if it wasn't given.
+    ///
+    /// # Errors
+    ///
+    /// The argument was given, but is a different type.
+    pub fn try_get_named_string(&self, name: &str) -> Result<Option<&str>, ArgumentError> {
+        self.get_named(name)
+            .map(|value| {
+                value
+                    .as_string()
+                    .ok_or_else(|| self.wrong_type(name, value, "a string"))
+            })
+            .transpose()
+    }
+
+    /// Gets the values of the last positional argument, when it takes any number of them, see [`PositionalDefinition::zero_or_more`].
+    /// They can also be gotten one at a time, by their index among every positional argument.
+    /// Without such an argument, this is always empty.
+    pub fn positional_rest(&self) -> &[Variant] {
+        match self.rest_start {
+            Some(start) => self.positional.get(start..).unwrap_or_default(),
+            None => &[],
+        }
+    }
+
+    /// Iterates over the named arguments that have a value, from any source, in no particular order.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+    /// let flags = [
+    ///     FlagDefinition::new("threads", VariantFlag::int()).default(Variant::Int(4)),
+    ///     FlagDefinition::new("verbose", VariantFlag::new_unit()),
+    ///     FlagDefinition::new("output", VariantFlag::path()),
+    /// ];
+    /// let args = Args::from_slice(&["my-tool", "in.txt", "--verbose"], &[VariantFlag::path()], &flags).unwrap();
+    ///
+    /// let mut named: Vec<(&str, &Variant)> = args.named_iter().collect();
+    /// named.sort_by_key(|(name, _)| *name);
+    /// assert_eq!(named, [("threads", &Variant::Int(4)), ("verbose", &Variant::Bool(true))]);
+    /// assert_eq!(args.named_len(), 2);
+    /// assert!(args.contains("threads"));
+    /// assert!(!args.contains("output"));
+    /// assert_eq!(args.positional_iter().count(), args.positional_len());
+    /// assert_eq!(
+    ///     format!("{args:?}"),
+    ///     r#"Args { binary: "my-tool", positional: [Path("in.txt")], named: {"threads": Int(4), "verbose": Bool(true)}, trailing: [], unknown: [], positional_sources: [CommandLine { index: 1 }], sources: {"threads": Default, "verbose": CommandLine { index: 2 }} }"#
+    /// );
+    /// ```
+    pub fn named_iter(&self) -> impl Iterator<Item = (&str, &Variant)> {
+        self.named.iter().map(|(name, value)| (&**name, value))
+    }
+
+    /// Iterates over the positional arguments in order, including any defaults that filled in for missing ones.
+    pub fn positional_iter(&self) -> impl Iterator<Item = &Variant> {
+        self.positional.iter()
+    }
+
+    /// How many named arguments have a value, see [`Args::named_iter`].
+    pub fn named_len(&self) -> usize {
+        self.named.len()
+    }
+
+    /// How many positional arguments have a value, see [`Args::positional_iter`].
+    pub fn positional_len(&self) -> usize {
+        self.positional.len()
+    }
+
+    /// Whether the named argument `name` has a value, from any source.
+    pub fn contains(&self, name: &str) -> bool {
+        self.named.contains_key(name)
+    }
+
+    /// Turns these arguments back into a command line, starting with the binary, that parses into the same arguments with the same definitions.
+    /// Useful for starting another copy of your program, with a few arguments changed through [`Args::from_map`] for example.
+    ///
+    /// Named arguments come first, in the order of `flag_definitions`, followed by [`Args::unknown`] arguments, then positional ones and [`Args::trailing`] arguments.
+    /// Unit flags are given as just `--name` when they are true, counted flags are repeated, and collected flags are given once for each value.
+    /// Values that start with `-` are attached with `=`, and a `--` is added before positional arguments that start with `-`.
+    /// Values from the environment or defaults are given explicitly too.
+    ///
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use flagged_cl_args::{Args, FlagDefinition, RepeatPolicy, Variant, VariantFlag};
+    /// let flags = [
+    ///     FlagDefinition::new("verbose", VariantFlag::count()).abbrev('v'),
+    ///     FlagDefinition::new("force", VariantFlag::new_unit()),
+    ///     FlagDefinition::new("offset", VariantFlag::int()),
+    ///     FlagDefinition::new("ratio", VariantFlag::double()),
+    ///     FlagDefinition::new("timeout", VariantFlag::duration()),
+    ///     FlagDefinition::new("size", VariantFlag::byte_size()),
+    ///     FlagDefinition::new("since", VariantFlag::datetime()),
+    ///     FlagDefinition::new("listen", VariantFlag::socket()),
+    ///     FlagDefinition::new("upstream", VariantFlag::host_port()),
+    ///     FlagDefinition::new("tags", VariantFlag::list_of(VariantFlag::string()).delimiter(';')),
+    ///     FlagDefinition::new("define", VariantFlag::key_value(VariantFlag::int()))
+    ///         .abbrev('D')
+    ///         .repeat(RepeatPolicy::Collect),
+    ///     FlagDefinition::new("title", VariantFlag::string()),
+    /// ];
+    /// let positionals = [VariantFlag::path(), VariantFlag::long(), VariantFlag::port()];
+    /// let args = Args::from_slice(
+    ///     &[
+    ///         "my-tool", "-vv", "in file.txt", "--force", "--offset", "-3", "--ratio=0.5", "--timeout", "1m30s",
+    ///         "--size", "4KiB", "--since", "2024-01-02T03:04:05Z", "--listen", "127.0.0.1:80",
+    ///         "--upstream", "[::1]:8080", "--tags", "a,b;c", "-D", "x=1", "--define", "y=-2", "--title", "",
+    ///         "--", "-7", "443", "extra",
+    ///     ],
+    ///     &positionals,
+    ///     &flags,
+    /// )
+    /// .unwrap();
+    ///
+    /// let argv = args.to_argv(&flags);
+    /// assert_eq!(argv[..4], ["my-tool", "--verbose", "--verbose", "--force"]);
+    /// assert_eq!(argv[argv.len() - 5..], ["--", "in file.txt", "-7", "443", "extra"]);
+    /// let argv: Vec<&str> = argv.iter().map(String::as_str).collect();
+    /// let reparsed = Args::from_slice(&argv, &positionals, &flags).unwrap();
+    /// let named: BTreeMap<&str, &Variant> = args.named_iter().collect();
+    /// assert_eq!(reparsed.named_iter().collect::<BTreeMap<_, _>>(), named);
+    /// assert!(reparsed.positional_iter().eq(args.positional_iter()));
+    /// assert_eq!(reparsed.trailing_os(), args.trailing_os());
+    /// ```
+    #[must_use]
+    pub fn to_argv(&self, flag_definitions: &[FlagDefinition]) -> Vec<String> {
+        self.argv(flag_definitions, false)
+    }
+
+    /// Like [`Args::to_argv`], but with `***` in place of the values of [`FlagDefinition::sensitive`] flags, for showing or logging.
+    #[must_use]
+    pub fn to_argv_redacted(&self, flag_definitions: &[FlagDefinition]) -> Vec<String> {
+        self.argv(flag_definitions, true)
+    }
+
+    /// See [`Args::to_argv`] and [`Args::to_argv_redacted`].
+    fn argv(&self, flag_definitions: &[FlagDefinition], redact: bool) -> Vec<String> {
+        let mut argv = vec![self.binary.clone()];
+        let push_value = |argv: &mut Vec<String>, spelling: &str, value: String| {
+            if value.is_empty() || value.starts_with('-') {
+                argv.push(format!("{spelling}={value}"));
+            } else {
+                argv.push(spelling.to_string());
+                argv.push(value);
+            }
+        };
+        // Flags taking several values are always followed by them, even ones starting with `-`
+        let push_values = |argv: &mut Vec<String>,
+                           spelling: &str,
+                           allowed_type: &VariantFlag,
+                           value: &Variant| {
+            argv.push(spelling.to_string());
+            match value {
+                Variant::List(values) => {
+                    argv.extend(values.iter().map(|value| allowed_type.unparse(value)));
+                }
+                value => argv.push(allowed_type.unparse(value)),
+            }
+        };
+        for definition in flag_definitions {
+            let Some(value) = self.named.get(definition.name.as_str()) else {
+                continue;
+            };
+            let spelling = match definition.abbreviation {
+                Some(abbreviation) if definition.name.is_empty() => format!("-{abbreviation}"),
+                _ => format!("--{}", definition.name),
+            };
+            let allowed_type = &definition.allowed_type;
+            let redacted;
+            let value = if redact
+                && definition.sensitive
+                && !allowed_type.is_unit()
+                && !allowed_type.is_count()
+            {
+                redacted = redact_value(value);
+                &redacted
+            } else {
+                value
+            };
+            match value {
+                Variant::Bool(true) if allowed_type.is_unit() => argv.push(spelling),
+                Variant::Bool(false) if allowed_type.is_unit() => {
+                    argv.push(format!("{spelling}=false"));
+                }
+                Variant::Int(count) if allowed_type.is_count() => {
+                    for _ in 0..*count {
+                        argv.push(spelling.clone());
+                    }
+                }
+                Variant::List(values)
+                    if definition.repeat == RepeatPolicy::Collect && definition.num_values > 1 =>
+                {
+                    for value in values {
+                        push_values(&mut argv, &spelling, allowed_type, value);
+                    }
+                }
+                value if definition.num_values > 1 => {
+                    push_values(&mut argv, &spelling, allowed_type, value);
+                }
+                // Optional values are only read when they are attached
+                Variant::List(values)
+                    if definition.repeat == RepeatPolicy::Collect
+                        && definition.optional_value.is_some() =>
+                {
+                    for value in values {
+                        argv.push(format!("{spelling}={}", allowed_type.unparse(value)));
+                    }
+                }
+                Variant::List(values) if definition.repeat == RepeatPolicy::Collect => {
+                    for value in values {
+                        push_value(&mut argv, &spelling, allowed_type.unparse(value));
+                    }
+                }
+                value if definition.optional_value.is_some() => {
+                    argv.push(format!("{spelling}={}", allowed_type.unparse(value)));
+                }
+                value => push_value(&mut argv, &spelling, allowed_type.unparse(value)),
+            }
+        }
+        // Arguments without a definition, like --help, or ones added through Args::from_map
+        let mut undefined: Vec<(&str, &Variant)> = self
+            .named_iter()
+            .filter(|(name, _)| {
+                !flag_definitions
+                    .iter()
+                    .any(|definition| definition.name == *name)
+            })
+            .collect();
+        undefined.sort_by_key(|(name, _)| *name);
+        for (name, value) in undefined {
+            match value {
+                Variant::Bool(true) if name == "help" => argv.push("--help".to_string()),
+                Variant::String(_) if name == "version" => argv.push("--version".to_string()),
+                value => push_value(&mut argv, &format!("--{name}"), value.to_string()),
+            }
+        }
+        argv.extend(self.unknown.iter().cloned());
+        let positional: Vec<String> = self.positional.iter().map(ToString::to_string).collect();
+        if !self.trailing.is_empty()
+            || positional
+                .iter()
+                .any(|value| value.starts_with('-') && value != "-")
+        {
+            argv.push("--".to_string());
+        }
+        argv.extend(positional);
+        argv.extend(
+            self.trailing
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned()),
+        );
+        argv
+    }
+
+    /// Like [`Args::to_argv_redacted`], but joined into one line with arguments quoted where needed, for showing your end user or writing to a log.
+    /// [`split_command_line`](crate::split_command_line) splits it back into the same arguments, with `***` for the values of [`FlagDefinition::sensitive`] flags.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, VariantFlag, split_command_line};
+    /// let flags = [FlagDefinition::new("title", VariantFlag::string())];
+    /// let args = Args::from_slice(&["my-tool", "--title", "it's here", "in.txt"], &[VariantFlag::path()], &flags).unwrap();
+    /// assert_eq!(args.to_shell_string(&flags), r"my-tool --title 'it'\''s here' in.txt");
+    /// assert_eq!(split_command_line(&args.to_shell_string(&flags)).unwrap(), args.to_argv(&flags));
+    /// ```
+    #[must_use]
+    pub fn to_shell_string(&self, flag_definitions: &[FlagDefinition]) -> String {
+        self.to_argv_redacted(flag_definitions)
+            .iter()
+            .map(|arg| quote(arg))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Gets the arguments after a bare `--` that weren't needed to fill the positional arguments.
+    ///
+    /// Everything after the first `--` is treated as positional, even if it looks like a flag (including another `--`).
+    /// Those arguments fill any remaining positional arguments first, and the rest end up here, unparsed.
+    /// This is useful for passing arguments along to another program, like `binary build -- --flag-for-child`.
+    ///
+    /// Anything that isn't valid UTF-8 is replaced with `U+FFFD`, see [`Args::trailing_os`] to get them exactly as they were given.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, VariantFlag};
+    /// let args = Args::from_slice(&["my-tool", "build", "--", "--release", "--", "x"], &[VariantFlag::string()], &[]).unwrap();
+    /// assert_eq!(args.get_positional_string(0), Some("build"));
+    /// assert_eq!(args.trailing(), ["--release", "--", "x"]);
+    /// ```
+    pub fn trailing(&self) -> &[String] {
+        &self.trailing_lossy
+    }
+
+    /// Like [`Args::trailing`], but kept exactly as they were given, even if they aren't valid UTF-8.
+    pub fn trailing_os(&self) -> &[OsString] {
+        &self.trailing
+    }
+
+    /// Gets the arguments that looked like flags but didn't match any definition, when [`ParserConfig::allow_unknown_flags`] is set.
+    ///
+    /// They are kept whole and in order, so `--color=always` stays one argument, ready to pass along to another program.
+    pub fn unknown(&self) -> &[String] {
+        &self.unknown
+    }
+
+    /// Gets the warnings produced while parsing, like uses of deprecated flags.
+    ///
+    /// Nothing is printed while parsing, so it's up to you to show these to your end user, on stderr or in a log for example.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Whether the end user asked for help with `--help` or `-h`, see [`ParserConfig::auto_help`].
+    /// When they did, nothing else was parsed, so show them [`Args::help_text`] and exit.
+    /// A flag of your own named `help` is never a request for help, since its value has a source, see [`Args::source_of`].
+    pub fn help_requested(&self) -> bool {
+        self.named.get("help") == Some(&Variant::Bool(true)) && !self.sources.contains_key("help")
+    }
+
+    /// The version to show your end user, if they asked for it with `--version` or `-V`, see [`ParserConfig::version`].
+    /// When they did, nothing else was parsed, so print it and exit.
+    pub fn version_requested(&self) -> Option<&str> {
+        match self.named.get("version") {
+            Some(Variant::String(version)) => Some(version),
+            _ => None,
+        }
+    }
+
+    /// Deserializes named arguments into `T`, usually a struct with a field for each flag.
+    ///
+    /// A field named `output_dir` is filled by the flag `output_dir`, or `output-dir` if there's no such flag.
+    /// Use `#[serde(rename = "...")]` for anything else.
+    /// Flags that weren't given, and have no environment variable or default, are missing, which is an error unless the field is an [`Option`] or has `#[serde(default)]`.
+    /// Flags with choices can be deserialized into enums with unit variants.
+    ///
+    /// Positional arguments fill a field renamed to `_positional`, like a [`Vec`] or tuple.
+    /// Tuples and tuple structs are filled from positional arguments alone.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+    /// #[derive(serde::Deserialize, PartialEq, Debug)]
+    /// #[serde(rename_all = "lowercase")]
+    /// enum Format {
+    ///     Json,
+    ///     Plain,
+    /// }
+    ///
+    /// #[derive(serde::Deserialize, PartialEq, Debug)]
+    /// struct Config {
+    ///     verbose: Option<bool>,
+    ///     threads: i32,
+    ///     output_dir: PathBuf,
+    ///     format: Format,
+    /// }
+    ///
+    /// let flags = [
+    ///     FlagDefinition::new("verbose", VariantFlag::new_unit()),
+    ///     FlagDefinition::new("threads", VariantFlag::int()).default(Variant::Int(4)),
+    ///     FlagDefinition::new("output-dir", VariantFlag::path()).default(Variant::Path("out".into())),
+    ///     FlagDefinition::new("format", VariantFlag::choice(&["json", "plain"]))
+    ///         .default(Variant::String("plain".to_string())),
+    /// ];
+    /// let args = Args::new(&[], &flags).unwrap();
+    /// assert_eq!(
+    ///     args.deserialize::<Config>().unwrap(),
+    ///     Config {
+    ///         verbose: None,
+    ///         threads: 4,
+    ///         output_dir: PathBuf::from("out"),
+    ///         format: Format::Plain,
+    ///     }
+    /// );
+    ///
+    /// #[derive(serde::Deserialize, Debug)]
+    /// struct Wrong {
+    ///     threads: String,
+    /// }
+    /// assert_eq!(
+    ///     args.deserialize::<Wrong>().unwrap_err().to_string(),
+    ///     "--threads: invalid type: integer `4`, expected a string"
+    /// );
+    ///
+    /// #[derive(serde::Deserialize, Debug)]
+    /// struct Missing {
+    ///     verbose: bool,
+    /// }
+    /// assert_eq!(
+    ///     args.deserialize::<Missing>().unwrap_err().to_string(),
+    ///     "--verbose is required"
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the flag when a value is missing or can't be deserialized into its field.
+    #[cfg(feature = "serde")]
+    pub fn deserialize<'de, T: serde::Deserialize<'de>>(&'de self) -> Result<T, ArgumentError> {
+        T::deserialize(crate::serialization::ArgsDeserializer::new(
+            &self.positional,
+            &self.named,
+        ))
+    }
+}
+
+/// Serializes the binary, positional arguments, and named arguments.
+/// Named arguments are sorted by name, so the output is stable.
+///
+/// ```
+/// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+/// let flags = [FlagDefinition::new("threads", VariantFlag::int()).default(Variant::Int(4))];
+/// let args = Args::new(&[], &flags).unwrap();
+/// let json = serde_json::to_value(&args).unwrap();
+/// assert_eq!(json["binary"], args.binary());
+/// assert_eq!(json["positional"], serde_json::json!([]));
+/// assert_eq!(json["named"], serde_json::json!({ "threads": 4 }));
+/// ```
+#[cfg(feature = "serde")]
+impl serde::Serialize for Args {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let named: std::collections::BTreeMap<&str, &Variant> = self.named_iter().collect();
+        let mut state = serializer.serialize_struct("Args", 3)?;
+        state.serialize_field("binary", &self.binary)?;
+        state.serialize_field("positional", &self.positional)?;
+        state.serialize_field("named", &named)?;
+        state.end()
+    }
+}
+
+/// Named arguments are sorted by name, so the output is the same every time.
+impl std::fmt::Debug for Args {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let named: std::collections::BTreeMap<&str, DebugValue> = self
+            .named_iter()
+            .map(|(name, value)| (name, DebugValue(value, self.sensitive.contains(name))))
+            .collect();
+        let sources: std::collections::BTreeMap<&Arc<str>, &ValueSource> =
+            self.sources.iter().collect();
+        f.debug_struct("Args")
+            .field("binary", &self.binary)
+            .field("positional", &self.positional)
+            .field("named", &named)
+            .field("trailing", &self.trailing)
+            .field("unknown", &self.unknown)
+            .field("positional_sources", &self.positional_sources)
+            .field("sources", &sources)
+            .finish()
+    }
+}
+
+/// A named value in [`Args`]' Debug output, which is `***` for [`FlagDefinition::sensitive`] flags.
+struct DebugValue<'a>(&'a Variant, bool);
+
+impl std::fmt::Debug for DebugValue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.1 {
+            write!(f, "***")
+        } else {
+            self.0.fmt(f)
+        }
+    }
+}
+
+/// Arguments are equal when their binary, values, [`Args::trailing`] arguments, and [`Args::unknown`] arguments are.
+/// Where values came from, the text they were parsed from, their order, and warnings aren't compared, so parsed arguments can be checked against ones made with [`Args::from_map`].
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+/// let flags = [FlagDefinition::new("threads", VariantFlag::int())];
+/// let args = Args::from_slice(&["my-tool", "--threads", "4", "in.txt"], &[VariantFlag::string()], &flags).unwrap();
+/// let expected = Args::from_map(
+///     "my-tool",
+///     vec![Variant::String("in.txt".to_string())],
+///     HashMap::from([("threads".to_string(), Variant::Int(4))]),
+/// );
+/// assert_eq!(args, expected);
+/// ```
+impl PartialEq for Args {
+    fn eq(&self, other: &Self) -> bool {
+        self.binary == other.binary
+            && self.positional == other.positional
+            && self.named == other.named
+            && self.trailing == other.trailing
+            && self.unknown == other.unknown
+    }
+}
+
+/// Gets a named argument, like `args["threads"]`.
+///
+/// ```
+/// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+/// let flags = [FlagDefinition::new("threads", VariantFlag::int()).default(Variant::Int(1))];
+/// let args = Args::from_slice(&["my-tool"], &[], &flags).unwrap();
+/// assert_eq!(args["threads"], Variant::Int(1));
+/// ```
+///
+/// # Panics
+///
+/// The argument has no value, with a message like `--output has no value, it wasn't given and has no default`.
+/// Use [`Args::get_named`] when it might not have one.
+///
+/// ```
+/// # use flagged_cl_args::{Args, FlagDefinition, VariantFlag};
+/// let flags = [FlagDefinition::new("output", VariantFlag::path())];
+/// let args = Args::from_slice(&["my-tool"], &[], &flags).unwrap();
+/// let panic = std::panic::catch_unwind(|| args["output"].clone()).unwrap_err();
+/// assert_eq!(
+///     panic.downcast_ref::<String>().unwrap(),
+///     "--output has no value, it wasn't given and has no default"
+/// );
+/// ```
+impl std::ops::Index<&str> for Args {
+    type Output = Variant;
+
+    fn index(&self, name: &str) -> &Variant {
+        self.get_named(name)
+            .unwrap_or_else(|| panic!("--{name} has no value, it wasn't given and has no default"))
+    }
+}
+
+/// Gets a positional argument, like `args[0]`.
+///
+/// ```
+/// # use flagged_cl_args::{Args, Variant, VariantFlag};
+/// let args = Args::from_slice(&["my-tool", "5"], &[VariantFlag::int()], &[]).unwrap();
+/// assert_eq!(args[0], Variant::Int(5));
+/// ```
+///
+/// # Panics
+///
+/// There is no positional argument at `index`, with a message like `There is no positional argument 2, there are only 1`.
+/// Use [`Args::get_positional`] when there might not be one.
+///
+/// ```
+/// # use flagged_cl_args::{Args, VariantFlag};
+/// let args = Args::from_slice(&["my-tool", "5"], &[VariantFlag::int()], &[]).unwrap();
+/// let panic = std::panic::catch_unwind(|| args[2].clone()).unwrap_err();
+/// assert_eq!(
+///     panic.downcast_ref::<String>().unwrap(),
+///     "There is no positional argument 2, there are only 1"
+/// );
+/// ```
+impl std::ops::Index<usize> for Args {
+    type Output = Variant;
+
+    fn index(&self, index: usize) -> &Variant {
+        self.get_positional(index).unwrap_or_else(|| {
+            panic!(
+                "There is no positional argument {index}, there are only {}",
+                self.positional.len()
+            )
+        })
+    }
+}
+
+/// Stops parsing at `error`, or records it and lets parsing continue when [`ParserConfig::collect_errors`] is set.
+fn recover(
+    config: &ParserConfig,
+    errors: &mut Vec<ArgumentError>,
+    error: impl Into<ArgumentError>,
+) -> Result<(), ArgumentError> {
+    let error = error.into();
+    if config.collect_errors {
+        errors.push(error);
+        Ok(())
+    } else {
+        Err(error)
+    }
+}
+
+/// Like [`config_variant`], but a single occurrence of a [`RepeatPolicy::Collect`] flag is a list of one, like on the command line.
+#[cfg(any(feature = "toml", feature = "json"))]
+fn config_occurrences(definition: &FlagDefinition, value: ConfigValue) -> Result<Variant, String> {
+    let single = match &value {
+        ConfigValue::Array(values) => {
+            definition.num_values > 1
+                && !values
+                    .iter()
+                    .any(|value| matches!(value, ConfigValue::Array(_)))
+        }
+        _ => true,
+    };
+    let parsed = config_variant(definition, value)?;
+    if definition.repeat == RepeatPolicy::Collect && single {
+        Ok(Variant::List(vec![parsed]))
+    } else {
+        Ok(parsed)
+    }
+}
+
+/// Turns a config file value into a value for `definition`, see [`Args::overlay_config`].
+#[cfg(any(feature = "toml", feature = "json"))]
+pub(crate) fn config_variant(
+    definition: &FlagDefinition,
+    value: ConfigValue,
+) -> Result<Variant, String> {
+    match value {
+        // A single occurrence of a flag taking several values, as opposed to an array of them
+        ConfigValue::Array(values)
+            if definition.num_values > 1
+                && !values
+                    .iter()
+                    .any(|value| matches!(value, ConfigValue::Array(_))) =>
+        {
+            if values.len() != definition.num_values {
+                return Err(format!(
+                    "expected {} values for --{}, got {}",
+                    definition.num_values,
+                    definition.name,
+                    values.len()
+                ));
+            }
+            values
+                .into_iter()
+                .map(|value| match value {
+                    ConfigValue::Bool(b) => parse_config_text(definition, &b.to_string()),
+                    ConfigValue::Text(text) => parse_config_text(definition, &text),
+                    ConfigValue::Float(f) => config_float(definition, f),
+                    value => Err(format!(
+                        "{} isn't allowed, --{} is {}",
+                        value.description(),
+                        definition.name,
+                        definition.allowed_type
+                    )),
+                })
+                .collect::<Result<_, _>>()
+                .map(Variant::List)
+        }
+        _ if definition.num_values > 1 && !matches!(value, ConfigValue::Array(_)) => Err(format!(
+            "expected an array of {} values, since --{} takes {}",
+            definition.num_values, definition.name, definition.num_values
+        )),
+        ConfigValue::Array(values) if definition.repeat == RepeatPolicy::Collect => values
+            .into_iter()
+            .map(|value| config_variant(definition, value))
+            .collect::<Result<_, _>>()
+            .map(Variant::List),
+        ConfigValue::Array(_) => Err(format!(
+            "an array isn't allowed, since --{} can only be given once",
+            definition.name
+        )),
+        ConfigValue::Other(description) | ConfigValue::Table(description, _) => Err(format!(
+            "{description} isn't allowed, --{} is {}",
+            definition.name, definition.allowed_type
+        )),
+        ConfigValue::Bool(b) if definition.allowed_type.is_unit() => Ok(Variant::Bool(b)),
+        ConfigValue::Text(text) if definition.allowed_type.is_unit() => Err(format!(
+            "'{text}' isn't allowed, --{} can only be true or false",
+            definition.name
+        )),
+        ConfigValue::Text(text) if definition.allowed_type.is_count() => text
+            .parse()
+            .map(Variant::Int)
+            .map_err(|_| format!("'{text}' isn't a count")),
+        ConfigValue::Bool(b) if definition.allowed_type.is_count() => {
+            Err(format!("{b} isn't a count"))
+        }
+        ConfigValue::Float(f) if definition.allowed_type.is_unit() => Err(format!(
+            "{f:?} isn't allowed, --{} can only be true or false",
+            definition.name
+        )),
+        ConfigValue::Float(f) if definition.allowed_type.is_count() => {
+            Err(format!("{f:?} isn't a count"))
+        }
+        ConfigValue::Float(f) => config_float(definition, f),
+        ConfigValue::Bool(b) => parse_config_text(definition, &b.to_string()),
+        ConfigValue::Text(text) => parse_config_text(definition, &text),
+    }
+}
+
+/// Turns a floating point number from a config file into a float or double, when the flag allows one.
+/// Other types see it written out, like `1.0`, so it never becomes an int.
+#[cfg(any(feature = "toml", feature = "json"))]
+fn config_float(definition: &FlagDefinition, f: f64) -> Result<Variant, String> {
+    let allowed_type = &definition.allowed_type;
+    let float = Variant::Float(f as f32);
+    // Too large for a float, which would make it infinite
+    if allowed_type.allows(VariantKind::Float)
+        && f.is_finite() == (f as f32).is_finite()
+        && allowed_type.allows_value(&float)
+    {
+        return Ok(float);
+    }
+    let double = Variant::Double(f);
+    if allowed_type.allows(VariantKind::Double) && allowed_type.allows_value(&double) {
+        return Ok(double);
+    }
+    parse_config_text(definition, &format!("{f:?}"))
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+fn parse_config_text(definition: &FlagDefinition, text: &str) -> Result<Variant, String> {
+    let allowed_type = &definition.allowed_type;
+    allowed_type.parse_or_reason(text).map_err(|reason| {
+        reason.unwrap_or_else(|| format!("'{text}' cannot be parsed as type {allowed_type}"))
+    })
+}
+
+/// Inserts a named value, following the definition's [`RepeatPolicy`] if it was already given.
+fn insert_named(
+    named: &mut HashMap<Arc<str>, Variant>,
+    key: &Arc<str>,
+    definition: &FlagDefinition,
+    value: Variant,
+) -> Result<(), ArgumentError> {
+    match (named.get_mut(key), definition.repeat) {
+        (None, RepeatPolicy::Collect) => {
+            named.insert(key.clone(), Variant::List(vec![value]));
+        }
+        (None, _) | (Some(_), RepeatPolicy::LastWins) => {
+            named.insert(key.clone(), value);
+        }
+        (Some(Variant::List(values)), RepeatPolicy::Collect) => values.push(value),
+        (Some(_), _) => {
+            return Err(ArgumentError::new(&format!(
+                "--{} was given more than once",
+                definition.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Derives an environment variable name from a flag's name, like `MYTOOL_LOG_LEVEL` for `log-level`.
+pub(crate) fn derive_env_var(env_prefix: &str, name: &str) -> String {
+    format!(
+        "{env_prefix}{}",
+        name.to_ascii_uppercase().replace('-', "_")
+    )
+}
+
+/// Checks every definition's conflicts and requirements against the named arguments that were given, reporting all violations at once.
+/// Values from anywhere but a default count as given, since a default would otherwise always be in conflict.
+fn check_constraints(
+    flag_definitions: &[FlagDefinition],
+    sources: &HashMap<Arc<str>, ValueSource>,
+) -> Result<(), ArgumentError> {
+    let given = |name: &str| {
+        sources
+            .get(name)
+            .is_some_and(|source| *source != ValueSource::Default)
+    };
+    // Constraints can name a flag by one of its aliases
+    let canonical = |name: &'_ str| {
+        flag_definitions
+            .iter()
+            .find(|definition| names(definition).any(|other| other == name))
+            .map_or(name.to_string(), |definition| definition.name.clone())
+    };
+    let mut violations = Vec::new();
+    for definition in flag_definitions {
+        if !given(&definition.name) {
+            continue;
+        }
+        for conflict in definition.conflicts_with.iter().map(|name| canonical(name)) {
+            // Conflicts declared on both flags are only reported once
+            let reversed = format!("--{conflict} cannot be used with --{}", definition.name);
+            if given(&conflict) && !violations.contains(&reversed) {
+                violations.push(format!(
+                    "--{} cannot be used with --{conflict}",
+                    definition.name
+                ));
+            }
+        }
+        for requirement in definition.requires.iter().map(|name| canonical(name)) {
+            if !given(&requirement) {
+                violations.push(format!("--{} requires --{requirement}", definition.name));
+            }
+        }
+    }
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ArgumentError::new(&violations.join("\n")))
+    }
+}
+
+/// The behavior of the functions that take no [`ParserConfig`], which don't recognize `--help` on their own, see [`ParserConfig::auto_help`].
+fn legacy_config() -> ParserConfig {
+    ParserConfig {
+        auto_help: false,
+        ..Default::default()
+    }
+}
+
+/// Describes positional arguments that only have a type.
+fn positional_definitions(positional_types: &[VariantFlag]) -> Vec<PositionalDefinition> {
+    positional_types
+        .iter()
+        .cloned()
+        .map(PositionalDefinition::from)
+        .collect()
+}
+
+/// Runs a validator, if there is one, describing the rejected value with `describe`.
+/// The value is left out when it is `sensitive`, see [`FlagDefinition::sensitive`].
+fn validate(
+    validator: Option<&Validator>,
+    value: &Variant,
+    sensitive: bool,
+    describe: impl Fn() -> String,
+) -> Result<(), ArgumentError> {
+    match validator.map(|validator| validator(value)) {
+        Some(Err(reason)) if sensitive => Err(ArgumentError::new(&format!(
+            "{} value (redacted): {reason}",
+            describe()
+        ))),
+        Some(Err(reason)) => Err(ArgumentError::new(&format!(
+            "{} value {value}: {reason}",
+            describe()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Runs a flag's validator on its value, once for each value of flags that [`RepeatPolicy::Collect`].
+fn validate_named(
+    definition: &FlagDefinition,
+    value: &Variant,
+    describe: impl Fn() -> String,
+) -> Result<(), ArgumentError> {
+    let values = match (value, definition.repeat) {
+        (Variant::List(values), RepeatPolicy::Collect) => values.iter().collect(),
+        (value, _) => vec![value],
+    };
+    for value in values {
+        validate(
+            definition.validator.as_deref(),
+            value,
+            definition.sensitive,
+            &describe,
+        )?;
+    }
+    Ok(())
+}
+
+/// Parses a flag's value from its environment variable.
+/// Unit flags accept `true` or `false`, and counted flags accept a count.
+fn parse_env_value(definition: &FlagDefinition, value: &OsStr) -> Result<Variant, Option<String>> {
+    let allowed_type = &definition.allowed_type;
+    if allowed_type.is_count() {
+        return value
+            .to_str()
+            .and_then(|value| value.parse::<i32>().ok())
+            .filter(|count| *count >= 0)
+            .map(Variant::Int)
+            .ok_or(Some("expected a count, like 2".to_string()));
+    }
+    if allowed_type.is_unit() {
+        return value
+            .to_str()
+            .and_then(|value| allowed_type.parse_unit_value(value))
+            .map(Variant::Bool)
+            .ok_or(Some("expected true or false".to_string()));
+    }
+    if definition.num_values > 1 {
+        let values: Vec<&str> = value
+            .to_str()
+            .ok_or_else(|| format!("'{}' is not valid UTF-8", value.to_string_lossy()))?
+            .split_ascii_whitespace()
+            .collect();
+        if values.len() != definition.num_values {
+            return Err(Some(format!(
+                "expected {} values separated by whitespace, got {}",
+                definition.num_values,
+                values.len()
+            )));
+        }
+        return values
+            .into_iter()
+            .map(|value| allowed_type.parse_os_or_reason(OsStr::new(value)))
+            .collect::<Result<_, _>>()
+            .map(Variant::List);
+    }
+    allowed_type.parse_os_or_reason(value)
+}
+
+/// Whether `value` could be a single occurrence of `definition`, which is a list when it takes several values, see [`FlagDefinition::num_values`].
+fn allows_occurrence(definition: &FlagDefinition, value: &Variant) -> bool {
+    match value {
+        Variant::List(values) if definition.num_values > 1 => {
+            values.len() == definition.num_values
+                && values
+                    .iter()
+                    .all(|value| definition.allowed_type.allows_value(value))
+        }
+        _ if definition.num_values > 1 => false,
+        _ => definition.allowed_type.allows_value(value),
+    }
+}
+
+/// The value `definition` takes when it isn't given, a list for [`RepeatPolicy::Collect`], or `None` when its default isn't a valid type for it.
+fn flag_default(definition: &FlagDefinition) -> Option<Variant> {
+    let default = definition.default.as_ref()?;
+    match (definition.repeat, default) {
+        (RepeatPolicy::Collect, Variant::List(values))
+            if values
+                .iter()
+                .all(|value| allows_occurrence(definition, value)) =>
+        {
+            Some(default.clone())
+        }
+        (RepeatPolicy::Collect, _) => {
+            allows_occurrence(definition, default).then(|| Variant::List(vec![default.clone()]))
+        }
+        _ => allows_occurrence(definition, default).then(|| default.clone()),
+    }
+}
+
+/// Takes the values of a flag with several, see [`FlagDefinition::num_values`], starting with the value attached to it if there is one.
+/// Like any value, the following arguments are taken even if they look like flags.
+fn take_values(
+    args: &mut impl Iterator<Item = (usize, OsString)>,
+    definition: &FlagDefinition,
+    index: usize,
+    attached_value: Option<&str>,
+) -> Result<Vec<(usize, OsString)>, ArgumentError> {
+    let mut values: Vec<(usize, OsString)> = attached_value
+        .map(|value| (index, OsString::from(value)))
+        .into_iter()
+        .collect();
+    values.extend(args.take(definition.num_values - values.len()));
+    if values.len() < definition.num_values {
+        return Err(ArgumentError::new(&format!(
+            "expected {} values for --{}, got {}",
+            definition.num_values,
+            definition.name,
+            values.len()
+        )));
+    }
+    Ok(values)
+}
+
+/// Describes a value that none of its allowed types accept.
+/// When there isn't a more specific reason, the source is the error from the type's parser, like a [`std::num::ParseIntError`].
+/// With several types, it's every type that was tried and why each didn't accept the value.
+fn parse_failed(
+    flag_or_position: String,
+    position: usize,
+    value: &OsStr,
+    allowed: &VariantFlag,
+    failure: &ParseFailure,
+) -> ArgumentError {
+    let error: ArgumentError = ArgumentErrorKind::ParseFailed {
+        flag_or_position,
+        position,
+        value: value.to_string_lossy().into_owned(),
+        allowed: allowed.clone(),
+        reason: failure.reason().map(str::to_string),
+    }
+    .into();
+    if failure.reason().is_some() {
+        return error;
+    }
+    match failure.shared_cause() {
+        Some(cause) => error.with_source(cause.clone()),
+        None if failure.attempts().next().is_some() => error.with_source(Arc::new(failure.clone())),
+        None => error,
+    }
+}
+
+/// Replaces a value like `@token.txt` with the trimmed contents of that file, and `@@text` with `@text`, see [`FlagDefinition::value_from_file`].
+fn value_from_file<'a>(
+    definition: &FlagDefinition,
+    index: usize,
+    value: &'a OsStr,
+) -> Result<Cow<'a, OsStr>, ArgumentError> {
+    let Some(text) = value.to_str().filter(|_| definition.value_from_file) else {
+        return Ok(Cow::Borrowed(value));
+    };
+    if text.starts_with("@@") {
+        return Ok(Cow::Owned(OsString::from(&text[1..])));
+    }
+    let Some(path) = text.strip_prefix('@').filter(|path| !path.is_empty()) else {
+        return Ok(Cow::Borrowed(value));
+    };
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Cow::Owned(OsString::from(contents.trim()))),
+        Err(error) => Err(ArgumentError::new(&format!(
+            "--{} at position {index}: could not read {path}: {error}",
+            definition.name
+        ))
+        .with_source(Arc::new(error))),
+    }
+}
+
+/// Replaces a value with `***`, keeping the shape of lists so each of their values is replaced, see [`Args::to_argv_redacted`].
+fn redact_value(value: &Variant) -> Variant {
+    match value {
+        Variant::List(values) => Variant::List(values.iter().map(redact_value).collect()),
+        _ => Variant::String("***".to_string()),
+    }
+}
+
+/// Like [`parse_failed`] for a flag's value, but leaves the value out for [`FlagDefinition::sensitive`] flags.
+fn flag_parse_failed(
+    definition: &FlagDefinition,
+    position: usize,
+    value: &OsStr,
+    failure: &ParseFailure,
+) -> ArgumentError {
+    if definition.sensitive {
+        // The reason and cause could repeat the value too
+        return ArgumentError::new(&format!(
+            "value for --{} at position {position} (redacted) could not be parsed as type {}",
+            definition.name, definition.allowed_type
+        ));
+    }
+    parse_failed(
+        format!("--{}", definition.name),
+        position,
+        value,
+        &definition.allowed_type,
+        failure,
+    )
+}
+
+/// A token that matched a flag definition.
+struct FlagMatch<'a, 'b> {
+    definition: &'a FlagDefinition,
+    /// A value given in the same token, like `--name=value`
+    value: Option<&'b str>,
+    /// Whether the flag was given as `--no-<name>`
+    negated: bool,
+    /// How the flag was written, like `--name` or `-a`, borrowed from the argument unless it was combined with others
+    spelling: Cow<'b, str>,
+}
+
+/// Whether the text after a dash looks like the rest of a negative number, like `5`, `5.0`, or `.5`.
+fn looks_like_number(input: &str) -> bool {
+    let digits = input.strip_prefix('.').unwrap_or(input);
+    digits.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// All of the names a definition can be given by, starting with its canonical name.
+pub(crate) fn names(definition: &FlagDefinition) -> impl Iterator<Item = &str> {
+    std::iter::once(definition.name.as_str()).chain(definition.aliases.iter().map(String::as_str))
+}
+
+/// All of the abbreviations a definition can be given by.
+pub(crate) fn abbreviations(definition: &FlagDefinition) -> impl Iterator<Item = char> {
+    definition
+        .abbreviation
+        .into_iter()
+        .chain(definition.extra_abbreviations.iter().copied())
+}
+
+/// Where to find each definition by name or abbreviation, without looking through all of them.
+/// It's built once for each parse, or once for a [`crate::Parser`], which keeps it for every parse after.
+/// The first definition with a name or abbreviation wins, like a search through them in order would.
+#[derive(Clone)]
+pub(crate) struct FlagIndex {
+    /// Names and aliases, lowercase while [`ParserConfig::case_insensitive_names`] is set
+    names: HashMap<String, usize>,
+    abbreviations: HashMap<char, usize>,
+    case_insensitive: bool,
+    /// Each definition's name, shared by every value parsed for it, see [`FlagLookup::key`]
+    keys: HashSet<Arc<str>>,
+}
+
+impl FlagIndex {
+    pub(crate) fn new(config: &ParserConfig, definitions: &[FlagDefinition]) -> FlagIndex {
+        let mut index = FlagIndex {
+            names: HashMap::new(),
+            abbreviations: HashMap::new(),
+            case_insensitive: config.case_insensitive_names,
+            keys: HashSet::new(),
+        };
+        for (position, definition) in definitions.iter().enumerate() {
+            if !index.keys.contains(definition.name.as_str()) {
+                index.keys.insert(Arc::from(definition.name.as_str()));
+            }
+            for name in names(definition) {
+                let key = if index.case_insensitive {
+                    name.to_lowercase()
+                } else {
+                    name.to_string()
+                };
+                index.names.entry(key).or_insert(position);
+            }
+            for abbreviation in abbreviations(definition) {
+                index.abbreviations.entry(abbreviation).or_insert(position);
+            }
+        }
+        index
+    }
+}
+
+/// The definitions being parsed, along with their [`FlagIndex`].
+struct FlagLookup<'a> {
+    definitions: &'a [FlagDefinition],
+    index: Cow<'a, FlagIndex>,
+}
+
+impl<'a> FlagLookup<'a> {
+    fn new(config: &ParserConfig, definitions: &'a [FlagDefinition]) -> FlagLookup<'a> {
+        FlagLookup {
+            definitions,
+            index: Cow::Owned(FlagIndex::new(config, definitions)),
+        }
+    }
+
+    fn find_name(&self, input_name: &str) -> Option<&'a FlagDefinition> {
+        let index = if self.index.case_insensitive {
+            self.index.names.get(input_name.to_lowercase().as_str())
+        } else {
+            self.index.names.get(input_name)
+        };
+        index.map(|index| &self.definitions[*index])
+    }
+
+    /// The key a definition's values are stored under, made once per parse instead of once per value.
+    fn key(&self, definition: &FlagDefinition) -> Arc<str> {
+        match self.index.keys.get(definition.name.as_str()) {
+            Some(key) => key.clone(),
+            None => Arc::from(definition.name.as_str()),
+        }
+    }
+
+    fn find_abbreviation(&self, input_char: char) -> Option<&'a FlagDefinition> {
+        self.index
+            .abbreviations
+            .get(&input_char)
+            .map(|index| &self.definitions[*index])
+    }
+}
+
+/// Matches `/name`, `/name:value`, `/a`, and `/a:value` tokens, see [`ParserConfig::slash_flags`].
+/// Unlike flags starting with `-`, anything that doesn't exactly match a definition is not a flag, since it is likely a path.
+fn match_slash_flag<'a, 'b>(
+    lookup: &FlagLookup<'a>,
+    arg: &'b str,
+    input: &'b str,
+) -> Option<FlagMatch<'a, 'b>> {
+    let (input_name, value) = match input.split_once(':') {
+        Some((input_name, value)) => (input_name, Some(value)),
+        None => (input, None),
+    };
+    let mut chars = input_name.chars();
+    let definition =
+        lookup
+            .find_name(input_name)
+            .or_else(|| match (chars.next(), chars.next()) {
+                (Some(abbreviation), None) => lookup.find_abbreviation(abbreviation),
+                _ => None,
+            })?;
+    Some(FlagMatch {
+        definition,
+        value,
+        negated: false,
+        spelling: Cow::Borrowed(&arg[..1 + input_name.len()]),
+    })
+}
+
+/// Whether a name was given as `input_name`, ignoring case with [`ParserConfig::case_insensitive_names`].
+fn same_name(config: &ParserConfig, name: &str, input_name: &str) -> bool {
+    if config.case_insensitive_names {
+        name.to_lowercase() == input_name.to_lowercase()
+    } else {
+        name == input_name
+    }
+}
+
+/// The rest of `input_name` after `prefix`, ignoring case with [`ParserConfig::case_insensitive_names`].
+fn strip_name_prefix<'a>(
+    config: &ParserConfig,
+    input_name: &'a str,
+    prefix: &str,
+) -> Option<&'a str> {
+    let start = input_name.get(..prefix.len())?;
+    same_name(config, prefix, start).then(|| &input_name[prefix.len()..])
+}
+
+/// The closest name, alias, or `no-` spelling to a name that isn't defined, like `--verbose` for `verbos`.
+fn suggest_name(flag_definitions: &[FlagDefinition], input_name: &str) -> Option<String> {
+    let negated: Vec<String> = flag_definitions
+        .iter()
+        .filter(|definition| definition.negatable)
+        .flat_map(names)
+        .map(|name| format!("no-{name}"))
+        .collect();
+    let spellings = flag_definitions
+        .iter()
+        .flat_map(names)
+        .chain(negated.iter().map(String::as_str));
+    closest(input_name, spellings).map(|name| format!("--{name}"))
+}
+
+/// An abbreviation that only differs in case from one that isn't defined, like `-v` for `-V`.
+/// Any other single character is too far from the input to be a helpful guess.
+fn suggest_abbreviation(flag_definitions: &[FlagDefinition], input_char: char) -> Option<String> {
+    flag_definitions
+        .iter()
+        .flat_map(abbreviations)
+        .find(|abbreviation| {
+            *abbreviation != input_char && abbreviation.to_lowercase().eq(input_char.to_lowercase())
+        })
+        .map(|abbreviation| format!("-{abbreviation}"))
+}
+
+/// The candidate closest to `input`, if one is close enough to be what was meant.
+///
+/// Candidates within an edit distance of a third of the input's length are close enough, with at least one edit allowed for inputs longer than a single character.
+/// Candidates that start with an input of three or more characters are close enough too, so `verb` finds `verbose`.
+pub(crate) fn closest<'a>(
+    input: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let length = input.chars().count();
+    let threshold = (length / 3).max(1);
+    candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(input, candidate);
+            let close = distance <= threshold && distance < length;
+            let prefix = length >= 3 && candidate.starts_with(input);
+            (close || prefix).then_some((distance, candidate))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The number of single character insertions, deletions, or substitutions needed to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// How a positional argument is described in errors, like `<output>`, or `Positional argument 2` when it has no name.
+fn positional_label(index: usize, definition: &PositionalDefinition) -> String {
+    match &definition.name {
+        Some(name) => format!("<{name}>"),
+        None => format!("Positional argument {index}"),
+    }
+}
+
+/// Checks the definitions before parsing, see [`crate::validate_definitions`], along with the parts that depend on `config`.
+pub(crate) fn check_definitions(
+    config: &ParserConfig,
+    positional_definitions: &[PositionalDefinition],
+    flag_definitions: &[FlagDefinition],
+) -> Result<(), ArgumentError> {
+    let mut problems = definition_problems(flag_definitions);
+    for (index, pair) in positional_definitions.windows(2).enumerate() {
+        let index = index + 1;
+        if pair[0].is_optional() && !pair[1].is_optional() {
+            problems.push(format!(
+                "{} is required, so it can't come after an optional one",
+                positional_label(index, &pair[1])
+            ));
+        } else if pair[0].default.is_none() && pair[1].default.is_some() && pair[0].is_optional() {
+            problems.push(format!(
+                "{} has a default, so the optional one before it needs a default too",
+                positional_label(index, &pair[1])
+            ));
+        }
+    }
+    for (index, definition) in positional_definitions.iter().enumerate() {
+        if let Some(name) = &definition.name
+            && positional_definitions[..index]
+                .iter()
+                .any(|other| other.name.as_ref() == Some(name))
+        {
+            problems.push(format!(
+                "Positional argument {index} is named {name}, which is already used by an earlier one"
+            ));
+        }
+        if definition.required && definition.default.is_some() {
+            problems.push(format!(
+                "{} is required, so its default would never be used",
+                positional_label(index, definition)
+            ));
+        }
+        if definition.arity != PositionalArity::One && index + 1 != positional_definitions.len() {
+            problems.push(format!(
+                "{} takes any number of values, so it must be the last one",
+                positional_label(index, definition)
+            ));
+        }
+        if definition.allowed_type.is_unit() {
+            problems.push(format!(
+                "{} can't be a unit flag, because positional arguments always have a value",
+                positional_label(index, definition)
+            ));
+        }
+        if let Some(default) = &definition.default
+            && !definition.allowed_type.allows_value(default)
+        {
+            problems.push(format!(
+                "{}: the default value {default} is not a valid type for it, which is {}",
+                positional_label(index, definition),
+                definition.allowed_type
+            ));
+        }
+        problems.extend(unreachable_problems(
+            &positional_label(index, definition),
+            &definition.allowed_type,
+        ));
+    }
+    if config.case_insensitive_names {
+        for (index, definition) in flag_definitions.iter().enumerate() {
+            for other in &flag_definitions[..index] {
+                // Names that are exactly the same are already reported
+                if let Some(name) = names(definition).find(|name| {
+                    names(other).any(|other| other != *name && same_name(config, other, name))
+                }) {
+                    let spelling = if name == definition.name {
+                        format!("--{name}")
+                    } else {
+                        format!("The alias --{name} of --{}", definition.name)
+                    };
+                    problems.push(format!(
+                        "{spelling} only differs by case from --{}, so they can't be told apart while names are case insensitive",
+                        other.name
+                    ));
+                }
+            }
+        }
+    }
+    if config.reserved_names == ReservedNames::Reject {
+        problems.extend(
+            reserved_name_uses(config, flag_definitions)
+                .into_iter()
+                .map(|(definition, spelling, purpose)| {
+                    format!(
+                        "--{} can't use {spelling}, because it is used for {purpose}",
+                        definition.name
+                    )
+                }),
+        );
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(ArgumentError::new(&problems.join("\n")))
+    }
+}
+
+/// The flags that use the spelling of an automatic flag, along with the spelling and what it is used for, see [`ParserConfig::reserved_names`].
+pub(crate) fn reserved_name_uses<'a>(
+    config: &ParserConfig,
+    flag_definitions: &'a [FlagDefinition],
+) -> Vec<(&'a FlagDefinition, String, String)> {
+    let mut uses = Vec::new();
+    let mut reserved = Vec::new();
+    if config.auto_help {
+        reserved.push(("help", 'h', "automatic help"));
+    }
+    if config.version.is_some() {
+        reserved.push(("version", 'V', "printing the version"));
+    }
+    for definition in flag_definitions {
+        for &(name, abbreviation, purpose) in &reserved {
+            if names(definition).any(|other| other == name) {
+                uses.push((definition, format!("--{name}"), purpose.to_string()));
+            }
+            if abbreviations(definition).any(|other| other == abbreviation) {
+                uses.push((definition, format!("-{abbreviation}"), purpose.to_string()));
+            }
+        }
+        for name in names(definition) {
+            let negated = flag_definitions.iter().find(|other| {
+                other.negatable
+                    && strip_name_prefix(config, name, "no-").is_some_and(|name| {
+                        names(other).any(|other| same_name(config, other, name))
+                    })
+            });
+            if let Some(negated) = negated {
+                uses.push((
+                    definition,
+                    format!("--{name}"),
+                    format!("negating --{}", negated.name),
+                ));
+            }
+        }
+    }
+    uses
+}
+
+/// Everything wrong with a set of flag definitions, see [`crate::validate_definitions`].
+pub(crate) fn definition_problems(flag_definitions: &[FlagDefinition]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (index, definition) in flag_definitions.iter().enumerate() {
+        if definition.name.is_empty() {
+            problems.push(match definition.abbreviation {
+                Some(abbreviation) => {
+                    format!("The flag abbreviated -{abbreviation} has an empty name")
+                }
+                None => "A flag has an empty name".to_string(),
+            });
+        }
+        for name in names(definition).filter(|name| !name.is_empty()) {
+            if name.starts_with('-') || name.contains(|c: char| c.is_whitespace() || c == '=') {
+                problems.push(format!(
+                    "{name:?} can't be a flag name, because it starts with - or contains whitespace or ="
+                ));
+            }
+        }
+        problems.extend(unreachable_problems(
+            &format!("--{}", definition.name),
+            &definition.allowed_type,
+        ));
+        if definition.value_from_file
+            && (definition.allowed_type.is_unit() || definition.allowed_type.is_count())
+        {
+            problems.push(format!(
+                "--{} can't read its value from a file, because it doesn't take one",
+                definition.name
+            ));
+        }
+        if definition.num_values == 0 {
+            problems.push(format!(
+                "--{} must take at least 1 value, not 0",
+                definition.name
+            ));
+        } else if definition.num_values > 1
+            && (definition.allowed_type.is_unit()
+                || definition.allowed_type.is_count()
+                || definition.optional_value.is_some())
+        {
+            problems.push(format!(
+                "--{} takes {} values, which only flags with a value that isn't optional can",
+                definition.name, definition.num_values
+            ));
+        }
+        match &definition.optional_value {
+            Some(_) if definition.allowed_type.is_unit() || definition.allowed_type.is_count() => {
+                problems.push(format!(
+                    "--{} doesn't take a value, so it can't have an optional one",
+                    definition.name
+                ));
+            }
+            Some(value) if !definition.allowed_type.allows_value(value) => {
+                problems.push(format!(
+                    "The optional value {value} for --{} is not a valid type for it, which is {}",
+                    definition.name, definition.allowed_type
+                ));
+            }
+            _ => {}
+        }
+        if let Some(default) = &definition.default
+            && flag_default(definition).is_none()
+        {
+            problems.push(format!(
+                "The default value {default} for --{} is not a valid type for it, which is {}",
+                definition.name, definition.allowed_type
+            ));
+        }
+        for abbreviation in abbreviations(definition) {
+            if !abbreviation.is_alphanumeric() {
+                problems.push(format!(
+                    "The abbreviation {abbreviation:?} of --{} isn't a letter or digit",
+                    definition.name
+                ));
+            }
+        }
+        for unknown in definition
+            .conflicts_with
+            .iter()
+            .chain(&definition.requires)
+            .filter(|name| {
+                !flag_definitions
+                    .iter()
+                    .any(|other| names(other).any(|other| other == name.as_str()))
+            })
+        {
+            problems.push(format!(
+                "--{} has a constraint on --{unknown}, which does not match any known flag name",
+                definition.name
+            ));
+        }
+        // Only earlier flags are compared, so that each clash is reported once
+        for other in &flag_definitions[..index] {
+            if let Some(name) =
+                names(definition).find(|name| names(other).any(|other| other == *name))
+            {
+                problems.push(if name != definition.name {
+                    format!(
+                        "The alias --{name} of --{} is already used by --{}",
+                        definition.name, other.name
+                    )
+                } else if name == other.name {
+                    format!("--{name} is defined more than once")
+                } else {
+                    format!("--{name} is already used as an alias of --{}", other.name)
+                });
+            }
+            if let Some(abbreviation) = abbreviations(definition)
+                .find(|abbreviation| abbreviations(other).any(|other| other == *abbreviation))
+            {
+                problems.push(format!(
+                    "The abbreviation -{abbreviation} of --{} is already used by --{}",
+                    definition.name, other.name
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// Describes the kinds `allowed_type` can never parse, see [`VariantFlag::unreachable_kinds`].
+fn unreachable_problems<'a>(
+    argument: &'a str,
+    allowed_type: &'a VariantFlag,
+) -> impl Iterator<Item = String> + 'a {
+    allowed_type.unreachable_kinds().map(move |kind| {
+        format!(
+            "{argument} allows {kind}, but it is never parsed because {} accepts the same values first",
+            allowed_type.shadowed_by(kind).expect("unreachable kinds are shadowed")
+        )
+    })
+}
+
+/// Finds the argument that selects a subcommand, skipping global flags and their values.
+/// None when there is no subcommand, or an argument isn't a global flag, in which case parsing every argument as global reports the problem.
+fn find_subcommand(
+    args: &[OsString],
+    config: &ParserConfig,
+    flag_definitions: &[FlagDefinition],
+) -> Option<usize> {
+    let lookup = FlagLookup::new(config, flag_definitions);
+    let mut index = 1;
+    while let Some(arg) = args.get(index) {
+        let Some(arg) = arg.to_str() else {
+            // Flags are always valid UTF-8
+            return Some(index);
+        };
+        if arg == "--" {
+            return None;
+        }
+        let flag_matches = match_flag_definition(config, &lookup, arg, index).ok()?;
+        if flag_matches.is_empty() {
+            return Some(index);
+        }
+        index += 1 + values_after(&flag_matches);
+    }
+    None
+}
+
+/// The arguments (without the binary) that are in the place of a flag, up to a bare `--`.
+/// The values of flags are skipped, since they are never read as flags, so `--message --help` doesn't ask for help.
+pub(crate) fn flag_positions<'a>(
+    args: &'a [OsString],
+    config: &ParserConfig,
+    flag_definitions: &[FlagDefinition],
+) -> Vec<&'a OsString> {
+    let lookup = FlagLookup::new(config, flag_definitions);
+    let mut positions = Vec::new();
+    let mut index = 0;
+    while let Some(arg) = args.get(index) {
+        if arg == "--" {
+            break;
+        }
+        positions.push(arg);
+        index += 1 + arg
+            .to_str()
+            .and_then(|arg| match_flag_definition(config, &lookup, arg, index + 1).ok())
+            .map_or(0, |flag_matches| values_after(&flag_matches));
+    }
+    positions
+}
+
+/// How many of the arguments after `arg` are values of [`FlagDefinition::value_from_file`] flags, which response files leave for the flag to read.
+fn file_values_after(config: &ParserConfig, lookup: &FlagLookup, arg: &str) -> usize {
+    match_flag_definition(config, lookup, arg, 0).map_or(0, |flag_matches| {
+        let flag_matches: Vec<FlagMatch> = flag_matches
+            .into_iter()
+            .filter(|flag_match| flag_match.definition.value_from_file)
+            .collect();
+        values_after(&flag_matches)
+    })
+}
+
+/// How many of the following arguments are values of the matched flags, which is none when they take no value or it is attached.
+fn values_after(flag_matches: &[FlagMatch]) -> usize {
+    flag_matches
+        .iter()
+        .map(|flag_match| {
+            let definition = flag_match.definition;
+            let allowed_type = &definition.allowed_type;
+            if flag_match.negated
+                || allowed_type.is_unit()
+                || allowed_type.is_count()
+                || (flag_match.value.is_none() && definition.optional_value.is_some())
+            {
+                0
+            } else {
+                definition.num_values - usize::from(flag_match.value.is_some())
+            }
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Matches `--name`, `--name=value`, `-a`, and `-a=value` tokens, and `--no-name` for negatable flags.
+/// With [`ParserConfig::prefix_matching`], `--na` matches `--name` too, when no other flag starts that way.
+///
+/// A single dash followed by several characters is read one abbreviation at a time:
+/// - Unit and counted flags are matched and the next character is read, so `-rfv` means `-r -f -v`
+/// - A flag that takes a value uses the rest of the token as its value, so `-n5` means `-n 5`, and `-rn5` means `-r -n 5`
+/// - If the value-taking flag is the last character, like `-rn`, its value is the next argument as usual
+///
+/// Negative numbers like `-5` and `-.5` are not flags either, unless a flag's abbreviation is the digit after the dash.
+/// With [`ParserConfig::single_dash_long`], long names are tried before any of this, and with [`ParserConfig::slash_flags`], `/name` is matched too.
+/// Anything else is not a flag, and results in no matches.
+fn match_flag_definition<'a, 'b>(
+    config: &ParserConfig,
+    lookup: &FlagLookup<'a>,
+    arg: &'b str,
+    position: usize,
+) -> Result<Vec<FlagMatch<'a, 'b>>, ArgumentError> {
+    let flag_definitions = lookup.definitions;
+    if config.slash_flags
+        && let Some(input) = arg.strip_prefix('/')
+    {
+        return Ok(match_slash_flag(lookup, arg, input).into_iter().collect());
+    }
+    if !arg.starts_with('-') || arg.len() < 2 {
+        return Ok(Vec::new());
+    }
+    if let Some(input) = arg.strip_prefix("--") {
+        let (input_name, value) = match input.split_once('=') {
+            Some((input_name, value)) => (input_name, Some(value)),
+            None => (input, None),
+        };
+        let spelling = &arg[..2 + input_name.len()];
+        if let Some(definition) = lookup.find_name(input_name) {
+            return Ok(vec![FlagMatch {
+                definition,
+                value,
+                negated: false,
+                spelling: Cow::Borrowed(spelling),
+            }]);
+        }
+        // `--no-<name>` is only considered when nothing is literally named that way
+        if let Some(definition) = strip_name_prefix(config, input_name, "no-")
+            .and_then(|input_name| lookup.find_name(input_name))
+            .filter(|definition| {
+                definition.negatable
+                    && (definition.allowed_type.is_unit()
+                        || definition.allowed_type.allows(VariantKind::Bool))
+            })
+        {
+            return Ok(vec![FlagMatch {
+                definition,
+                value,
+                negated: true,
+                spelling: Cow::Borrowed(spelling),
+            }]);
+        }
+        // Prefixes are only considered when nothing is spelled exactly that way
+        if config.prefix_matching && !input_name.is_empty() {
+            let candidates: Vec<&FlagDefinition> = flag_definitions
+                .iter()
+                .filter(|definition| {
+                    names(definition)
+                        .any(|name| strip_name_prefix(config, name, input_name).is_some())
+                })
+                .collect();
+            match candidates[..] {
+                [] => {}
+                [definition] => {
+                    return Ok(vec![FlagMatch {
+                        definition,
+                        value,
+                        negated: false,
+                        spelling: Cow::Borrowed(spelling),
+                    }]);
+                }
+                _ => {
+                    return Err(ArgumentErrorKind::AmbiguousFlag {
+                        name: format!("--{input_name}"),
+                        position,
+                        candidates: candidates
+                            .iter()
+                            .map(|definition| format!("--{}", definition.name))
+                            .collect(),
+                    }
+                    .into());
+                }
+            }
+        }
+        return Err(ArgumentErrorKind::UnknownFlag {
+            name: format!("--{input_name}"),
+            position,
+            suggestion: suggest_name(flag_definitions, input_name),
+        }
+        .into());
+    }
+
+    let input = &arg[1..];
+    let (input_name, value) = match input.split_once('=') {
+        Some((input_name, value)) => (input_name, Some(value)),
+        None => (input, None),
+    };
+    if !config.single_dash_long || input_name.chars().nth(1).is_none() {
+        return match_abbreviations(lookup, arg, position);
+    }
+    // Long names come first, see `ParserConfig::single_dash_long`
+    if let Some(definition) = lookup.find_name(input_name) {
+        return Ok(vec![FlagMatch {
+            definition,
+            value,
+            negated: false,
+            spelling: Cow::Borrowed(&arg[..1 + input_name.len()]),
+        }]);
+    }
+    match_abbreviations(lookup, arg, position).map_err(|error| match error.kind() {
+        ArgumentErrorKind::UnknownFlag { suggestion, .. } => ArgumentErrorKind::UnknownFlag {
+            name: format!("-{input_name}"),
+            position,
+            suggestion: suggest_name(flag_definitions, input_name).or_else(|| suggestion.clone()),
+        }
+        .into(),
+        _ => error,
+    })
+}
+
+/// Matches the abbreviations in a token starting with a single dash, see [`match_flag_definition`].
+fn match_abbreviations<'a, 'b>(
+    lookup: &FlagLookup<'a>,
+    arg: &'b str,
+    position: usize,
+) -> Result<Vec<FlagMatch<'a, 'b>>, ArgumentError> {
+    let flag_definitions = lookup.definitions;
+    let input = &arg[1..];
+    let mut chars = input.chars();
+    let first = chars.next().ok_or(ArgumentError::new("Infallible"))?;
+    if looks_like_number(input) && lookup.find_abbreviation(first).is_none() {
+        // Negative numbers like `-5` or `-.5` are values, unless a flag is actually abbreviated that way
+        return Ok(Vec::new());
+    }
+    let rest = chars.as_str();
+    if rest.is_empty() || rest.starts_with('=') {
+        let definition =
+            lookup
+                .find_abbreviation(first)
+                .ok_or_else(|| ArgumentErrorKind::UnknownFlag {
+                    name: format!("-{first}"),
+                    position,
+                    suggestion: suggest_abbreviation(flag_definitions, first),
+                })?;
+        return Ok(vec![FlagMatch {
+            definition,
+            value: rest.strip_prefix('='),
+            negated: false,
+            spelling: Cow::Borrowed(&arg[..1 + first.len_utf8()]),
+        }]);
+    }
+
+    // Combined unit flags like `-rfv`, possibly ending in a flag with an attached value like `-rn5`
+    let mut matches = Vec::new();
+    for (offset, input_char) in input.char_indices() {
+        let definition =
+            lookup
+                .find_abbreviation(input_char)
+                .ok_or_else(|| ArgumentErrorKind::UnknownFlag {
+                    name: format!("-{input_char}"),
+                    position,
+                    suggestion: suggest_abbreviation(flag_definitions, input_char),
+                })?;
+        if definition.allowed_type.is_unit() || definition.allowed_type.is_count() {
+            matches.push(FlagMatch {
+                definition,
+                value: None,
+                negated: false,
+                spelling: Cow::Owned(format!("-{input_char}")),
+            });
+        } else {
+            // The rest of the token is this flag's value
+            let rest = &input[offset + input_char.len_utf8()..];
+            let value = match rest.strip_prefix('=') {
+                Some(value) => Some(value),
+                None => Some(rest).filter(|rest| !rest.is_empty()),
+            };
+            matches.push(FlagMatch {
+                definition,
+                value,
+                negated: false,
+                spelling: Cow::Owned(format!("-{input_char}")),
+            });
+            break;
+        }
+    }
+    Ok(matches)
 }
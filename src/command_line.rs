@@ -0,0 +1,191 @@
+use std::ffi::OsString;
+
+use crate::{ArgumentError, ResponseFiles};
+
+/// How deep response files can include each other, which also stops a file that includes itself.
+const MAX_RESPONSE_FILE_DEPTH: usize = 10;
+
+/// Splits a command line into arguments the way a POSIX shell does, without expanding anything.
+///
+/// - Whitespace separates arguments, unless it is quoted or escaped
+/// - A backslash outside of quotes keeps the next character as it is, so `my\ file` is one argument
+/// - Everything between single quotes is kept as it is
+/// - Between double quotes, a backslash only escapes `"`, `\`, `$`, and `` ` ``, so Windows paths like `"C:\Users"` keep their backslashes
+/// - Quoted parts next to each other or to other text are joined into one argument, and `''` is an empty argument
+///
+/// ```
+/// # use flagged_cl_args::split_command_line;
+/// assert_eq!(
+///     split_command_line(r#"my-tool --name "it's here" 'say "hi"' my\ file"#).unwrap(),
+///     ["my-tool", "--name", "it's here", r#"say "hi""#, "my file"]
+/// );
+/// assert_eq!(split_command_line(r#"a "" '' b"#).unwrap(), ["a", "", "", "b"]);
+/// assert_eq!(
+///     split_command_line(r#"copy "C:\Program Files\tool" "say \"hi\"""#).unwrap(),
+///     ["copy", r"C:\Program Files\tool", r#"say "hi""#]
+/// );
+/// assert_eq!(
+///     split_command_line("echo 'oops").unwrap_err().to_string(),
+///     "Unterminated single quote starting at byte 5"
+/// );
+/// ```
+///
+/// # Errors
+///
+/// A quote isn't closed, or the line ends with a backslash that has nothing to escape.
+pub fn split_command_line(line: &str) -> Result<Vec<String>, ArgumentError> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    // Whether there is an argument in progress, which an empty pair of quotes starts
+    let mut in_arg = false;
+    let mut chars = line.char_indices();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some((_, '\'')) => break,
+                        Some((_, c)) => current.push(c),
+                        None => {
+                            return Err(ArgumentError::new(&format!(
+                                "Unterminated single quote starting at byte {offset}"
+                            )));
+                        }
+                    }
+                }
+            }
+            '"' => {
+                in_arg = true;
+                let unterminated = || {
+                    ArgumentError::new(&format!(
+                        "Unterminated double quote starting at byte {offset}"
+                    ))
+                };
+                loop {
+                    match chars.next().ok_or_else(unterminated)? {
+                        (_, '"') => break,
+                        (_, '\\') => match chars.next().ok_or_else(unterminated)? {
+                            (_, c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            (_, c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                        },
+                        (_, c) => current.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_arg = true;
+                let (_, escaped) = chars.next().ok_or_else(|| {
+                    ArgumentError::new(&format!(
+                        "The backslash at byte {offset} has nothing to escape"
+                    ))
+                })?;
+                current.push(escaped);
+            }
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Quotes an argument so that [`split_command_line`] gives it back as it is, leaving it alone when it doesn't need quotes.
+pub(crate) fn quote(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./:=,+%@".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Replaces `@file` arguments after the binary with the arguments in the file, see [`crate::ParserConfig::response_files`].
+/// `file_values` gives how many arguments after one are values of [`crate::FlagDefinition::value_from_file`] flags, which are left for the flag to read.
+pub(crate) fn expand_response_files(
+    args: Vec<OsString>,
+    format: ResponseFiles,
+    file_values: &dyn Fn(&str) -> usize,
+) -> Result<Vec<OsString>, ArgumentError> {
+    let mut args = args.into_iter();
+    let mut expanded: Vec<OsString> = args.next().into_iter().collect();
+    expand_into(&mut expanded, args, format, file_values, 0)?;
+    Ok(expanded)
+}
+
+fn expand_into(
+    expanded: &mut Vec<OsString>,
+    args: impl Iterator<Item = OsString>,
+    format: ResponseFiles,
+    file_values: &dyn Fn(&str) -> usize,
+    depth: usize,
+) -> Result<(), ArgumentError> {
+    let mut after_separator = false;
+    let mut left_alone = 0;
+    for arg in args {
+        if left_alone > 0 {
+            left_alone -= 1;
+            expanded.push(arg);
+            continue;
+        }
+        let path = match arg.to_str() {
+            _ if after_separator => None,
+            Some("--") => {
+                after_separator = true;
+                None
+            }
+            Some(arg) if arg.starts_with("@@") => {
+                expanded.push(OsString::from(&arg[1..]));
+                continue;
+            }
+            Some(arg) => arg.strip_prefix('@').filter(|path| !path.is_empty()),
+            None => None,
+        };
+        let Some(path) = path else {
+            if !after_separator {
+                left_alone = arg.to_str().map_or(0, file_values);
+            }
+            expanded.push(arg);
+            continue;
+        };
+        if depth == MAX_RESPONSE_FILE_DEPTH {
+            return Err(ArgumentError::new(&format!(
+                "Response file {path} is included more than {MAX_RESPONSE_FILE_DEPTH} deep, it may be including itself"
+            )));
+        }
+        let contents = std::fs::read_to_string(path).map_err(|error| {
+            ArgumentError::new(&format!("Response file {path} could not be read: {error}"))
+        })?;
+        let file_args: Vec<String> = match format {
+            ResponseFiles::Lines => contents
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(ToString::to_string)
+                .collect(),
+            ResponseFiles::ShellSplit => split_command_line(&contents).map_err(|error| {
+                ArgumentError::new(&format!("In response file {path}: {error}"))
+            })?,
+        };
+        expand_into(
+            expanded,
+            file_args.into_iter().map(OsString::from),
+            format,
+            file_values,
+            depth + 1,
+        )?;
+    }
+    Ok(())
+}
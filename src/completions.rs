@@ -0,0 +1,283 @@
+//! Generates shell completion scripts from your flag definitions.
+//!
+//! Write the output to your shell's completion directory while packaging your program, or print it from a flag like `--generate-completions bash`.
+
+use crate::{
+    FlagDefinition, VariantKind,
+    args::{abbreviations, names},
+};
+
+/// Generates a bash completion script for `binary`.
+///
+/// Words starting with `-` complete to flag names, negations, and abbreviations.
+/// After a flag that takes a path, filenames are completed, and after a flag with choices, its choices are completed.
+/// Anything else completes to filenames, since positional arguments are often paths.
+///
+/// ```
+/// # use flagged_cl_args::{FlagDefinition, VariantFlag, completions::generate_bash};
+/// let flags = [
+///     FlagDefinition::new("output", VariantFlag::path()).abbrev('o'),
+///     FlagDefinition::new("format", VariantFlag::choice(&["json", "plain"])),
+///     FlagDefinition::new("threads", VariantFlag::int()),
+///     FlagDefinition::new("cache", VariantFlag::new_unit()).negatable(),
+/// ];
+/// assert_eq!(
+///     generate_bash("my-tool", &flags),
+///     r#"_my_tool() {
+///     local cur="${COMP_WORDS[COMP_CWORD]}"
+///     local prev="${COMP_WORDS[COMP_CWORD-1]}"
+///     case "$prev" in
+///         --output|-o)
+///             COMPREPLY=($(compgen -f -- "$cur"))
+///             return 0
+///             ;;
+///         --format)
+///             COMPREPLY=($(compgen -W "json plain" -- "$cur"))
+///             return 0
+///             ;;
+///         --threads)
+///             COMPREPLY=()
+///             return 0
+///             ;;
+///     esac
+///     if [[ "$cur" == -* ]]; then
+///         COMPREPLY=($(compgen -W "--output -o --format --threads --cache --no-cache" -- "$cur"))
+///         return 0
+///     fi
+///     COMPREPLY=($(compgen -f -- "$cur"))
+/// }
+/// complete -F _my_tool my-tool
+/// "#
+/// );
+/// ```
+#[must_use]
+pub fn generate_bash(binary: &str, flag_definitions: &[FlagDefinition]) -> String {
+    let function = format!("_{}", shell_identifier(binary));
+    let mut script = format!(
+        "{function}() {{\n    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n    local prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"\n    case \"$prev\" in\n"
+    );
+    for definition in flag_definitions {
+        let allowed_type = &definition.allowed_type;
+        // Optional values are attached with `=`, so the next word is never one
+        if allowed_type.is_unit() || allowed_type.is_count() || definition.optional_value.is_some()
+        {
+            continue;
+        }
+        let reply = if allowed_type.allows(VariantKind::Path) {
+            "($(compgen -f -- \"$cur\"))".to_string()
+        } else if allowed_type.choice_allowed() {
+            format!(
+                "($(compgen -W \"{}\" -- \"$cur\"))",
+                allowed_type.choices().join(" ")
+            )
+        } else {
+            // Nothing sensible to suggest, but don't suggest flags either
+            "()".to_string()
+        };
+        script.push_str(&format!(
+            "        {})\n            COMPREPLY={reply}\n            return 0\n            ;;\n",
+            spellings(definition, false).join("|")
+        ));
+    }
+    let words: Vec<String> = flag_definitions
+        .iter()
+        .flat_map(|definition| spellings(definition, true))
+        .collect();
+    script.push_str(&format!(
+        "    esac\n    if [[ \"$cur\" == -* ]]; then\n        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n        return 0\n    fi\n    COMPREPLY=($(compgen -f -- \"$cur\"))\n}}\ncomplete -F {function} {binary}\n",
+        words.join(" ")
+    ));
+    script
+}
+
+/// Generates a zsh completion script for `binary`, using help as descriptions.
+///
+/// Flags that take a path complete filenames, and flags with choices complete their choices.
+/// Positional arguments complete filenames.
+///
+/// ```
+/// # use flagged_cl_args::{FlagDefinition, VariantFlag, completions::generate_zsh};
+/// let flags = [
+///     FlagDefinition::new("output", VariantFlag::path())
+///         .abbrev('o')
+///         .help("Where to write [the] \"result\", if it's wanted"),
+///     FlagDefinition::new("format", VariantFlag::choice(&["json", "plain"])),
+///     FlagDefinition::new("verbose", VariantFlag::new_unit())
+///         .abbrev('v')
+///         .help("Print more"),
+/// ];
+/// assert_eq!(
+///     generate_zsh("my-tool", &flags),
+///     r#"#compdef my-tool
+///
+/// _arguments \
+///     '--output[Where to write \[the\] "result", if it'\''s wanted]:output:_files' \
+///     '-o[Where to write \[the\] "result", if it'\''s wanted]:output:_files' \
+///     '--format:format:(json plain)' \
+///     '--verbose[Print more]' \
+///     '-v[Print more]' \
+///     '*:file:_files'
+/// "#
+/// );
+/// ```
+#[must_use]
+pub fn generate_zsh(binary: &str, flag_definitions: &[FlagDefinition]) -> String {
+    let mut specs = Vec::new();
+    for definition in flag_definitions {
+        let allowed_type = &definition.allowed_type;
+        let description = match &definition.help {
+            Some(help) => format!("[{}]", escape_zsh_description(help)),
+            None => String::new(),
+        };
+        let action = if allowed_type.is_unit()
+            || allowed_type.is_count()
+            || definition.optional_value.is_some()
+        {
+            String::new()
+        } else if allowed_type.allows(VariantKind::Path) {
+            format!(":{}:_files", definition.name)
+        } else if allowed_type.choice_allowed() {
+            format!(
+                ":{}:({})",
+                definition.name,
+                allowed_type.choices().join(" ")
+            )
+        } else {
+            format!(":{}: ", definition.name)
+        };
+        // One action for each value
+        let action = action.repeat(definition.num_values);
+        for spelling in spellings(definition, true) {
+            specs.push(quote_zsh(&format!("{spelling}{description}{action}")));
+        }
+    }
+    specs.push("'*:file:_files'".to_string());
+    format!(
+        "#compdef {binary}\n\n_arguments \\\n    {}\n",
+        specs.join(" \\\n    ")
+    )
+}
+
+/// Generates a fish completion script for `binary`, using help as descriptions.
+///
+/// Unit and counted flags don't take an argument.
+/// Flags that take a path complete filenames, and flags with choices complete their choices.
+///
+/// ```
+/// # use flagged_cl_args::{FlagDefinition, VariantFlag, completions::generate_fish};
+/// let flags = [
+///     FlagDefinition::new("output", VariantFlag::path())
+///         .abbrev('o')
+///         .help("Where to write [the] \"result\", if it's wanted"),
+///     FlagDefinition::new("format", VariantFlag::choice(&["json", "plain"])),
+///     FlagDefinition::new("cache", VariantFlag::new_unit())
+///         .negatable()
+///         .help("Cache results"),
+/// ];
+/// assert_eq!(
+///     generate_fish("my-tool", &flags),
+///     r#"complete -c my-tool -l output -s o -d 'Where to write [the] "result", if it\'s wanted' -r -F
+/// complete -c my-tool -l format -x -a 'json plain'
+/// complete -c my-tool -l cache -d 'Cache results'
+/// complete -c my-tool -l no-cache -d 'Cache results'
+/// "#
+/// );
+/// ```
+#[must_use]
+pub fn generate_fish(binary: &str, flag_definitions: &[FlagDefinition]) -> String {
+    let mut script = String::new();
+    for definition in flag_definitions {
+        let allowed_type = &definition.allowed_type;
+        let mut options = String::new();
+        for name in names(definition) {
+            options.push_str(&format!(" -l {name}"));
+        }
+        for abbreviation in abbreviations(definition) {
+            options.push_str(&format!(" -s {abbreviation}"));
+        }
+        if let Some(help) = &definition.help {
+            options.push_str(&format!(" -d {}", quote_fish(help)));
+        }
+        if allowed_type.is_unit() || allowed_type.is_count() || definition.optional_value.is_some()
+        {
+            // Takes no argument, or an optional one that has to be attached
+        } else if allowed_type.allows(VariantKind::Path) {
+            options.push_str(" -r -F");
+        } else if allowed_type.choice_allowed() {
+            options.push_str(&format!(
+                " -x -a {}",
+                quote_fish(&allowed_type.choices().join(" "))
+            ));
+        } else {
+            options.push_str(" -x");
+        }
+        script.push_str(&format!("complete -c {binary}{options}\n"));
+        if definition.negatable {
+            let mut options = String::new();
+            for name in names(definition) {
+                options.push_str(&format!(" -l no-{name}"));
+            }
+            if let Some(help) = &definition.help {
+                options.push_str(&format!(" -d {}", quote_fish(help)));
+            }
+            script.push_str(&format!("complete -c {binary}{options}\n"));
+        }
+    }
+    script
+}
+
+/// Escapes the characters zsh treats specially inside an `_arguments` description.
+fn escape_zsh_description(help: &str) -> String {
+    let mut escaped = String::new();
+    for c in help.chars() {
+        match c {
+            '\\' | '[' | ']' | ':' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Single quotes a word for zsh.
+fn quote_zsh(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/// Single quotes a word for fish, which allows escaping quotes and backslashes inside single quotes.
+fn quote_fish(word: &str) -> String {
+    format!(
+        "'{}'",
+        word.replace('\\', "\\\\")
+            .replace('\'', "\\'")
+            .replace('\n', " ")
+    )
+}
+
+/// Every way a flag can be written, like `--name`, `--alias`, and `-a`, optionally including `--no-name` for negatable flags.
+pub(crate) fn spellings(definition: &FlagDefinition, negations: bool) -> Vec<String> {
+    let mut spellings = Vec::new();
+    for name in names(definition) {
+        spellings.push(format!("--{name}"));
+    }
+    if negations && definition.negatable {
+        for name in names(definition) {
+            spellings.push(format!("--no-{name}"));
+        }
+    }
+    for abbreviation in abbreviations(definition) {
+        spellings.push(format!("-{abbreviation}"));
+    }
+    spellings
+}
+
+/// Replaces anything that can't be part of a shell function name, like the dash in `my-tool`.
+fn shell_identifier(binary: &str) -> String {
+    binary
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
@@ -0,0 +1,304 @@
+use crate::{ArgumentError, FlagDefinition, VariantFlag, args::config_variant};
+
+/// A value from a config file, in the shapes that matter for named arguments, see [`crate::Args::overlay_config`].
+pub(crate) enum ConfigValue {
+    Bool(bool),
+    /// Integers, strings, and dates, which are parsed like they were given on the command line
+    Text(String),
+    /// Floating point numbers, which stay floats instead of being written out and parsed again
+    Float(f64),
+    Array(Vec<ConfigValue>),
+    /// A table or object, described like "a table", with its entries in order
+    Table(&'static str, Vec<(String, ConfigValue)>),
+    /// Anything that can't be a named argument, described like "null"
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    Other(&'static str),
+}
+
+#[cfg(feature = "toml")]
+impl From<&toml::Value> for ConfigValue {
+    fn from(value: &toml::Value) -> Self {
+        match value {
+            toml::Value::Boolean(b) => ConfigValue::Bool(*b),
+            toml::Value::Integer(i) => ConfigValue::Text(i.to_string()),
+            toml::Value::Float(f) => ConfigValue::Float(*f),
+            toml::Value::String(s) => ConfigValue::Text(s.clone()),
+            toml::Value::Datetime(datetime) => ConfigValue::Text(datetime.to_string()),
+            toml::Value::Array(values) => {
+                ConfigValue::Array(values.iter().map(ConfigValue::from).collect())
+            }
+            toml::Value::Table(table) => ConfigValue::Table(
+                "a table",
+                table
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<&serde_json::Value> for ConfigValue {
+    fn from(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Bool(b) => ConfigValue::Bool(*b),
+            serde_json::Value::Number(n) => match n.as_f64() {
+                Some(f) if n.is_f64() => ConfigValue::Float(f),
+                _ => ConfigValue::Text(n.to_string()),
+            },
+            serde_json::Value::String(s) => ConfigValue::Text(s.clone()),
+            serde_json::Value::Array(values) => {
+                ConfigValue::Array(values.iter().map(ConfigValue::from).collect())
+            }
+            serde_json::Value::Object(object) => ConfigValue::Table(
+                "an object",
+                object
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.into()))
+                    .collect(),
+            ),
+            serde_json::Value::Null => ConfigValue::Other("null"),
+        }
+    }
+}
+
+impl ConfigValue {
+    /// Describes the value for error messages, like "a table" or "an array".
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            ConfigValue::Bool(_) => "a boolean",
+            ConfigValue::Text(_) => "a string or number",
+            ConfigValue::Float(_) => "a number",
+            ConfigValue::Array(_) => "an array",
+            ConfigValue::Table(description, _) | ConfigValue::Other(description) => description,
+        }
+    }
+}
+
+/// Reads positional types and flag definitions from a TOML file, so several programs can share them.
+///
+/// `positional` is an array of types, and each `[[flags]]` table has a `name` and `type`, and optionally an `abbreviation`, `aliases`, `help`, and a `default`.
+/// Types are written like [`VariantFlag`]'s Display implementation, see its [`FromStr`](std::str::FromStr) implementation.
+/// A missing type is [`VariantFlag::new_unit`].
+/// Defaults are read like [`crate::Args::overlay_config`] reads values.
+///
+/// ```
+/// # use flagged_cl_args::{Args, Variant, definitions_from_toml};
+/// let (positional, flags) = definitions_from_toml(r#"
+///     positional = ["path"]
+///
+///     [[flags]]
+///     name = "threads"
+///     abbreviation = "t"
+///     type = "int"
+///     help = "How many threads to use"
+///     default = 4
+///
+///     [[flags]]
+///     name = "verbose"
+///     abbreviation = "v"
+///     type = "count"
+/// "#).unwrap();
+/// let args = Args::from_slice(&["my-tool", "in.txt", "-vv"], &positional, &flags).unwrap();
+/// assert_eq!(args.get_named_int("threads"), Some(4));
+/// assert_eq!(args.get_named_int("verbose"), Some(2));
+///
+/// let error = definitions_from_toml(r#"
+///     [[flags]]
+///     name = "threads"
+///     type = "integer"
+///     abbrev = "t"
+/// "#).unwrap_err();
+/// assert_eq!(
+///     error.to_string().lines().map(|line| line.split(',').next().unwrap()).collect::<Vec<_>>(),
+///     [
+///         "Definition key flags[0].abbrev isn't a known field",
+///         "Definition key flags[0].type: Unknown type 'integer'",
+///     ]
+/// );
+/// ```
+///
+/// # Errors
+///
+/// If `src` isn't valid TOML, and otherwise every key that isn't known and every value that isn't valid, naming the key.
+/// The definitions must also pass [`crate::validate_definitions`].
+#[cfg(feature = "toml")]
+pub fn definitions_from_toml(
+    src: &str,
+) -> Result<(Vec<VariantFlag>, Vec<FlagDefinition>), ArgumentError> {
+    let table: toml::Table = src.parse().map_err(|error| {
+        ArgumentError::new("Definitions aren't valid TOML").with_source(std::sync::Arc::new(error))
+    })?;
+    definitions(ConfigValue::from(&toml::Value::Table(table)))
+}
+
+/// Like [`definitions_from_toml`], but for a JSON object.
+///
+/// ```
+/// # use flagged_cl_args::definitions_from_json;
+/// let (positional, flags) = definitions_from_json(r#"{
+///     "positional": ["int|float"],
+///     "flags": [{ "name": "force", "abbreviation": "f", "aliases": ["yes"] }]
+/// }"#).unwrap();
+/// assert_eq!(positional.len(), 1);
+/// assert_eq!(flags[0].aliases, ["yes"]);
+///
+/// let error = definitions_from_json(r#"{ "flags": [{ "name": "force", "abbreviation": "ff" }] }"#).unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "Definition key flags[0].abbreviation: 'ff' isn't a single character"
+/// );
+/// ```
+///
+/// # Errors
+///
+/// See [`definitions_from_toml`].
+#[cfg(feature = "json")]
+pub fn definitions_from_json(
+    src: &str,
+) -> Result<(Vec<VariantFlag>, Vec<FlagDefinition>), ArgumentError> {
+    let value: serde_json::Value = serde_json::from_str(src).map_err(|error| {
+        ArgumentError::new("Definitions aren't valid JSON").with_source(std::sync::Arc::new(error))
+    })?;
+    definitions(ConfigValue::from(&value))
+}
+
+/// Reads definitions from a parsed file, see [`definitions_from_toml`].
+fn definitions(
+    value: ConfigValue,
+) -> Result<(Vec<VariantFlag>, Vec<FlagDefinition>), ArgumentError> {
+    let mut problems = Vec::new();
+    let mut positional = Vec::new();
+    let mut flags = Vec::new();
+    match value {
+        ConfigValue::Table(_, entries) => {
+            for (key, value) in entries {
+                match (key.as_str(), value) {
+                    ("positional", ConfigValue::Array(values)) => {
+                        for (index, value) in values.into_iter().enumerate() {
+                            match variant_flag(value) {
+                                Ok(flag) => positional.push(flag),
+                                Err(reason) => problems
+                                    .push(format!("Definition key positional[{index}]: {reason}")),
+                            }
+                        }
+                    }
+                    ("flags", ConfigValue::Array(values)) => {
+                        for (index, value) in values.into_iter().enumerate() {
+                            let key = format!("flags[{index}]");
+                            if let Some(flag) = flag_definition(&key, value, &mut problems) {
+                                flags.push(flag);
+                            }
+                        }
+                    }
+                    ("positional" | "flags", value) => problems.push(format!(
+                        "Definition key {key}: expected an array, found {}",
+                        value.description()
+                    )),
+                    _ => problems.push(format!(
+                        "Definition key {key} isn't a known field, expected positional or flags"
+                    )),
+                }
+            }
+        }
+        value => problems.push(format!(
+            "Definitions must be a table, found {}",
+            value.description()
+        )),
+    }
+    if !problems.is_empty() {
+        return Err(ArgumentError::new(&problems.join("\n")));
+    }
+    crate::validate_definitions(&flags)?;
+    Ok((positional, flags))
+}
+
+/// Reads a single flag definition, adding any problems with it to `problems`.
+fn flag_definition(
+    key: &str,
+    value: ConfigValue,
+    problems: &mut Vec<String>,
+) -> Option<FlagDefinition> {
+    let ConfigValue::Table(_, entries) = value else {
+        problems.push(format!(
+            "Definition key {key}: expected a table, found {}",
+            value.description()
+        ));
+        return None;
+    };
+    let problem_count = problems.len();
+    let mut definition = FlagDefinition::new("", VariantFlag::new_unit());
+    let mut name = None;
+    let mut default = None;
+    for (field, value) in entries {
+        let result = match field.as_str() {
+            "name" => text(value).map(|text| name = Some(text)),
+            "type" => variant_flag(value).map(|flag| definition.allowed_type = flag),
+            "abbreviation" => text(value).and_then(|text| {
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(abbreviation), None) => {
+                        definition.abbreviation = Some(abbreviation);
+                        Ok(())
+                    }
+                    _ => Err(format!("'{text}' isn't a single character")),
+                }
+            }),
+            "aliases" => match value {
+                ConfigValue::Array(values) => values
+                    .into_iter()
+                    .map(text)
+                    .collect::<Result<_, _>>()
+                    .map(|aliases| definition.aliases = aliases),
+                value => Err(format!("expected an array, found {}", value.description())),
+            },
+            "help" => text(value).map(|text| definition.help = Some(text)),
+            // Read once the type is known
+            "default" => {
+                default = Some(value);
+                Ok(())
+            }
+            _ => {
+                problems.push(format!(
+                    "Definition key {key}.{field} isn't a known field, expected name, type, abbreviation, aliases, help, or default"
+                ));
+                continue;
+            }
+        };
+        if let Err(reason) = result {
+            problems.push(format!("Definition key {key}.{field}: {reason}"));
+        }
+    }
+    match name {
+        Some(name) => definition.name = name,
+        None => problems.push(format!("Definition key {key} is missing a name")),
+    }
+    if problems.len() > problem_count {
+        return None;
+    }
+    if let Some(default) = default {
+        match config_variant(&definition, default) {
+            Ok(default) => definition.default = Some(default),
+            Err(reason) => {
+                problems.push(format!("Definition key {key}.default: {reason}"));
+                return None;
+            }
+        }
+    }
+    Some(definition)
+}
+
+/// Reads a type written like [`VariantFlag`]'s Display implementation.
+fn variant_flag(value: ConfigValue) -> Result<VariantFlag, String> {
+    text(value)?
+        .parse()
+        .map_err(|error: ArgumentError| error.to_string())
+}
+
+fn text(value: ConfigValue) -> Result<String, String> {
+    match value {
+        ConfigValue::Text(text) => Ok(text),
+        value => Err(format!("expected a string, found {}", value.description())),
+    }
+}
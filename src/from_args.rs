@@ -0,0 +1,229 @@
+use std::ffi::OsString;
+
+use crate::{
+    Args, ArgumentError, FlagDefinition, FromVariant, NoPrompt, ParserConfig, PositionalDefinition,
+    PromptSource, TerminalPrompt, Variant,
+};
+
+/// A type that can be built from command line arguments, usually by deriving it with the `derive` feature.
+///
+/// Deriving it for a struct turns each field into a named argument.
+/// The flag's name is the field's name with underscores replaced by dashes, so `output_dir` is set by `--output-dir`.
+///
+/// | Field type  | Argument                                                                |
+/// |-------------|-------------------------------------------------------------------------|
+/// | `bool`      | A unit flag, false unless given                                         |
+/// | `T`         | Required, an error if it isn't given                                    |
+/// | `Option<T>` | Optional                                                                |
+/// | `Vec<T>`    | Can be given any number of times, see [`crate::RepeatPolicy::Collect`] |
+///
+/// `T` can be [`bool`], [`i32`], [`f32`], [`std::net::SocketAddr`], [`std::path::PathBuf`], or [`String`].
+///
+/// Fields can be annotated with `#[flag(abbrev = 'v', help = "...", name = "...")]`.
+/// Fields marked with `#[positional]`, or `#[positional(help = "...")]`, are positional arguments instead, in the order they are declared.
+/// A positional `Option<T>` can be left off the end, and a positional `Vec<T>` takes every remaining value, so it must be the last one.
+///
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// # use std::path::PathBuf;
+/// use flagged_cl_args::FromArgs;
+///
+/// #[derive(FromArgs)]
+/// struct Config {
+///     #[positional(help = "The file to read")]
+///     input: PathBuf,
+///     #[flag(abbrev = 'v', help = "Print more")]
+///     verbose: bool,
+///     threads: Option<i32>,
+///     exclude: Vec<String>,
+/// }
+///
+/// let config = Config::parse_from(
+///     ["my-tool", "in.txt", "-v", "--exclude", "a", "--exclude", "b"].map(String::from),
+/// )
+/// .unwrap();
+/// assert_eq!(config.input, PathBuf::from("in.txt"));
+/// assert!(config.verbose);
+/// assert_eq!(config.threads, None);
+/// assert_eq!(config.exclude, ["a", "b"]);
+/// # }
+/// ```
+///
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// # use std::path::PathBuf;
+/// use flagged_cl_args::FromArgs;
+///
+/// #[derive(FromArgs)]
+/// struct Copy {
+///     #[positional]
+///     mode: Option<String>,
+///     #[positional]
+///     paths: Vec<PathBuf>,
+/// }
+///
+/// let copy = Copy::parse_from(["my-tool", "fast", "a.txt", "b.txt"].map(String::from)).unwrap();
+/// assert_eq!(copy.mode.as_deref(), Some("fast"));
+/// assert_eq!(copy.paths, [PathBuf::from("a.txt"), PathBuf::from("b.txt")]);
+///
+/// let copy = Copy::parse_from(["my-tool"].map(String::from)).unwrap();
+/// assert_eq!(copy.mode, None);
+/// assert!(copy.paths.is_empty());
+/// # }
+/// ```
+///
+/// Other field types are rejected when deriving, and so are positional arguments after a `Vec`, or required ones after an `Option`.
+///
+#[cfg_attr(feature = "derive", doc = "```compile_fail")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// #[derive(flagged_cl_args::FromArgs)]
+/// struct Config {
+///     threads: u8,
+/// }
+/// ```
+///
+#[cfg_attr(feature = "derive", doc = "```compile_fail")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// #[derive(flagged_cl_args::FromArgs)]
+/// struct Config {
+///     #[positional]
+///     inputs: Vec<String>,
+///     #[positional]
+///     output: String,
+/// }
+/// ```
+///
+#[cfg_attr(feature = "derive", doc = "```compile_fail")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// #[derive(flagged_cl_args::FromArgs)]
+/// struct Config {
+///     #[positional]
+///     input: Option<String>,
+///     #[positional]
+///     output: String,
+/// }
+/// ```
+pub trait FromArgs: Sized {
+    /// The positional arguments, in order.
+    fn positional_definitions() -> Vec<PositionalDefinition>;
+
+    /// The named arguments.
+    fn flag_definitions() -> Vec<FlagDefinition>;
+
+    /// Builds the value from arguments parsed with [`FromArgs::positional_definitions`] and [`FromArgs::flag_definitions`].
+    ///
+    /// # Errors
+    ///
+    /// A required argument is missing, or an argument is a different type than its field.
+    fn from_parsed(args: &Args) -> Result<Self, ArgumentError>;
+
+    /// Parses the arguments your program was run with, which don't need to be valid UTF-8, see [`Args::new_os`].
+    ///
+    /// # Errors
+    ///
+    /// See [`FromArgs::parse_from`].
+    fn parse() -> Result<Self, ArgumentError> {
+        parse_with_prompt(std::env::args_os(), &mut TerminalPrompt)
+    }
+
+    /// Parses `args`, which starts with the name of the binary.
+    ///
+    /// # Errors
+    ///
+    /// See [`Args::new`] and [`FromArgs::from_parsed`].
+    /// When the end user asks for help, the error is the help text, so showing them the error does the right thing.
+    fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self, ArgumentError> {
+        Self::parse_from_os(args.into_iter().map(OsString::from))
+    }
+
+    /// Like [`FromArgs::parse_from`], but arguments don't need to be valid UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// See [`FromArgs::parse_from`].
+    fn parse_from_os(args: impl IntoIterator<Item = OsString>) -> Result<Self, ArgumentError> {
+        parse_with_prompt(args, &mut NoPrompt)
+    }
+}
+
+/// Parses `args` into `T`, asking `prompt` for the values of [`FlagDefinition::prompt`] flags.
+fn parse_with_prompt<T: FromArgs>(
+    args: impl IntoIterator<Item = OsString>,
+    prompt: &mut dyn PromptSource,
+) -> Result<T, ArgumentError> {
+    let positional_definitions = T::positional_definitions();
+    let flag_definitions = T::flag_definitions();
+    let config = ParserConfig::default();
+    let args = Args::from_iter_os_with_env(
+        args.into_iter(),
+        &config,
+        &positional_definitions,
+        &flag_definitions,
+        |name| std::env::var_os(name),
+        prompt,
+    )?;
+    if args.help_requested() {
+        return Err(ArgumentError::new(&Args::help_text(
+            args.binary(),
+            &config,
+            &positional_definitions,
+            &flag_definitions,
+        )));
+    }
+    T::from_parsed(&args)
+}
+
+/// Used by the code `#[derive(FromArgs)]` generates, not public API.
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    pub fn missing_flag(name: &str) -> ArgumentError {
+        ArgumentError::new(&format!("--{name} is required"))
+    }
+
+    pub fn missing_positional(index: usize) -> ArgumentError {
+        ArgumentError::new(&format!("positional argument {} is missing", index + 1))
+    }
+
+    /// Gets every value of a flag collected with [`crate::RepeatPolicy::Collect`].
+    pub fn get_all<T: for<'a> FromVariant<'a>>(
+        args: &Args,
+        name: &str,
+    ) -> Result<Vec<T>, ArgumentError> {
+        let values = match args.get_named(name) {
+            None => return Ok(Vec::new()),
+            Some(Variant::List(values)) => values.as_slice(),
+            Some(value) => std::slice::from_ref(value),
+        };
+        values
+            .iter()
+            .map(|value| {
+                T::from_variant(value).ok_or_else(|| {
+                    ArgumentError::new(&format!(
+                        "--{name} is {value}, which is not the expected type"
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Gets the values of a last positional argument that takes any number of them, which starts at `index`.
+    pub fn get_rest<T: for<'a> FromVariant<'a>>(
+        args: &Args,
+        index: usize,
+    ) -> Result<Vec<T>, ArgumentError> {
+        args.positional_rest()
+            .iter()
+            .enumerate()
+            .map(|(offset, value)| {
+                T::from_variant(value).ok_or_else(|| {
+                    ArgumentError::new(&format!(
+                        "positional argument {} is {value}, which is not the expected type",
+                        index + offset + 1
+                    ))
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,313 @@
+use std::ffi::OsString;
+
+use crate::{
+    FlagDefinition, ParserConfig, PositionalArity, PositionalDefinition, RepeatPolicy,
+    args::{abbreviations, derive_env_var, flag_positions, names},
+};
+
+/// The text shown for the automatic help flag.
+const HELP_HELP: &str = "Print this help";
+
+/// The text shown for the automatic version flag.
+const VERSION_HELP: &str = "Print the version";
+
+/// Whether `--help` and `-h` are free to be used for automatic help, because none of the flag definitions use them.
+fn automatic_help(flag_definitions: &[FlagDefinition]) -> (bool, bool) {
+    unused_spellings(flag_definitions, "help", 'h')
+}
+
+/// Whether `--version` and `-V` are free to be used for printing the version, like [`automatic_help`].
+fn automatic_version(flag_definitions: &[FlagDefinition]) -> (bool, bool) {
+    unused_spellings(flag_definitions, "version", 'V')
+}
+
+fn unused_spellings(
+    flag_definitions: &[FlagDefinition],
+    name: &str,
+    abbreviation: char,
+) -> (bool, bool) {
+    let long = !flag_definitions
+        .iter()
+        .any(|definition| names(definition).any(|other| other == name));
+    let short = !flag_definitions
+        .iter()
+        .any(|definition| abbreviations(definition).any(|other| other == abbreviation));
+    (long, short)
+}
+
+/// Whether the arguments (without the binary) ask for automatic help.
+/// Arguments after a bare `--`, or that are the value of a flag, never do.
+pub(crate) fn help_requested(
+    args: &[OsString],
+    config: &ParserConfig,
+    flag_definitions: &[FlagDefinition],
+) -> bool {
+    let (long, short) = automatic_help(flag_definitions);
+    flag_positions(args, config, flag_definitions)
+        .into_iter()
+        .any(|arg| (long && arg == "--help") || (short && arg == "-h"))
+}
+
+/// Whether the arguments (without the binary) ask for the version with `--version` or `-V`.
+/// Arguments after a bare `--`, or that are the value of a flag, never do.
+pub(crate) fn version_requested(
+    args: &[OsString],
+    config: &ParserConfig,
+    flag_definitions: &[FlagDefinition],
+) -> bool {
+    let (long, short) = automatic_version(flag_definitions);
+    flag_positions(args, config, flag_definitions)
+        .into_iter()
+        .any(|arg| (long && arg == "--version") || (short && arg == "-V"))
+}
+
+/// See [`crate::Args::usage`].
+pub(crate) fn usage(
+    binary: &str,
+    positional_definitions: &[PositionalDefinition],
+    flag_definitions: &[FlagDefinition],
+) -> String {
+    let mut usage = format!("usage: {binary}");
+    for item in usage_items(positional_definitions, flag_definitions) {
+        usage.push(' ');
+        usage.push_str(&item);
+    }
+    usage
+}
+
+/// The pieces of the usage line after the binary, one for each positional argument and flag, like `<path>` or `[--threads <int>]`.
+fn usage_items(
+    positional_definitions: &[PositionalDefinition],
+    flag_definitions: &[FlagDefinition],
+) -> Vec<String> {
+    let mut items: Vec<String> = positional_definitions
+        .iter()
+        .map(|definition| decorated(definition, shown(definition)))
+        .collect();
+    for definition in flag_definitions {
+        let takes_value = !definition.allowed_type.is_unit() && !definition.allowed_type.is_count();
+        let spelling = match definition.abbreviation {
+            Some(abbreviation) if !takes_value || definition.name.is_empty() => {
+                format!("-{abbreviation}")
+            }
+            _ => format!("--{}", definition.name),
+        };
+        let mut item = format!("[{spelling}");
+        if takes_value && definition.optional_value.is_some() {
+            item.push_str(&format!("[=<{}>]", definition.allowed_type));
+        } else if takes_value {
+            item.push_str(&format!(" <{}>", definition.allowed_type).repeat(definition.num_values));
+        }
+        item.push(']');
+        if definition.allowed_type.is_count() || definition.repeat != RepeatPolicy::Reject {
+            item.push_str("...");
+        }
+        items.push(item);
+    }
+    items
+}
+
+/// See [`crate::Args::help_text`].
+pub(crate) fn help_text(
+    binary: &str,
+    config: &ParserConfig,
+    positional_definitions: &[PositionalDefinition],
+    flag_definitions: &[FlagDefinition],
+) -> String {
+    let positional_rows: Vec<(String, String)> = positional_definitions
+        .iter()
+        .enumerate()
+        .map(|(index, definition)| {
+            (
+                decorated(definition, positional_column(index, definition)),
+                positional_help(definition),
+            )
+        })
+        .collect();
+    let mut flag_rows: Vec<(String, String)> = flag_definitions
+        .iter()
+        .map(|definition| (flag_column(definition), flag_help(config, definition)))
+        .collect();
+    match automatic_help(flag_definitions) {
+        _ if !config.auto_help => {}
+        (true, true) => flag_rows.push(("-h, --help".to_string(), HELP_HELP.to_string())),
+        (true, false) => flag_rows.push(("    --help".to_string(), HELP_HELP.to_string())),
+        (false, true) => flag_rows.push(("-h".to_string(), HELP_HELP.to_string())),
+        (false, false) => {}
+    }
+    match automatic_version(flag_definitions) {
+        _ if config.version.is_none() => {}
+        (true, true) => flag_rows.push(("-V, --version".to_string(), VERSION_HELP.to_string())),
+        (true, false) => flag_rows.push(("    --version".to_string(), VERSION_HELP.to_string())),
+        (false, true) => flag_rows.push(("-V".to_string(), VERSION_HELP.to_string())),
+        (false, false) => {}
+    }
+
+    // The same line as `usage`, wrapped between items and lined up after the binary when it is too long
+    let prefix = format!("usage: {binary}");
+    let indent = prefix.chars().count() + 1;
+    let mut text = prefix;
+    let mut line_width = indent - 1;
+    for item in usage_items(positional_definitions, flag_definitions) {
+        let item_width = item.chars().count();
+        if line_width > indent && line_width + 1 + item_width > config.help_width {
+            text.push_str(&format!("\n{:indent$}{item}", ""));
+            line_width = indent + item_width;
+        } else {
+            text.push(' ');
+            text.push_str(&item);
+            line_width += 1 + item_width;
+        }
+    }
+    text.push('\n');
+
+    // Both tables share a column width, so help lines up across them
+    let width = positional_rows
+        .iter()
+        .chain(&flag_rows)
+        .map(|(column, _)| column.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (title, rows) in [("Arguments", &positional_rows), ("Flags", &flag_rows)] {
+        if rows.is_empty() {
+            continue;
+        }
+        text.push_str(&format!("\n{title}:\n"));
+        for (column, help) in rows {
+            push_row(&mut text, config.help_width, width, column, help);
+        }
+    }
+    text
+}
+
+/// How a positional argument is shown, like `<path>`, marked when it is optional or takes any number of values, like `[<path>]` or `<path>...`.
+pub(crate) fn decorated(definition: &PositionalDefinition, shown: String) -> String {
+    let shown = match definition.arity {
+        PositionalArity::One => shown,
+        PositionalArity::OneOrMore | PositionalArity::ZeroOrMore => format!("{shown}..."),
+    };
+    if definition.is_optional() {
+        format!("[{shown}]")
+    } else {
+        shown
+    }
+}
+
+/// How a positional argument is shown in usage, like `<output>`, or `<path>` when it has no name.
+pub(crate) fn shown(definition: &PositionalDefinition) -> String {
+    match &definition.name {
+        Some(name) => format!("<{name}>"),
+        None => format!("<{}>", definition.allowed_type),
+    }
+}
+
+/// The left column for a positional argument, like `<output>`, or `<POS2:path>` when it has no name.
+pub(crate) fn positional_column(index: usize, definition: &PositionalDefinition) -> String {
+    match &definition.name {
+        Some(name) => format!("<{name}>"),
+        None => format!("<POS{}:{}>", index + 1, definition.allowed_type),
+    }
+}
+
+/// The help for a positional argument, along with its default value if it has one.
+pub(crate) fn positional_help(definition: &PositionalDefinition) -> String {
+    let mut parts: Vec<String> = definition.help.iter().cloned().collect();
+    if let Some(default) = &definition.default {
+        parts.push(format!("[default: {default}]"));
+    }
+    parts.join(" ")
+}
+
+/// The left column for a flag, like `-t, --threads <int>`.
+fn flag_column(definition: &FlagDefinition) -> String {
+    let mut spellings: Vec<String> = abbreviations(definition)
+        .map(|abbreviation| format!("-{abbreviation}"))
+        .collect();
+    let abbreviated = !spellings.is_empty();
+    for (index, name) in names(definition).enumerate() {
+        if index == 0 && definition.negatable {
+            spellings.push(format!("--[no-]{name}"));
+        } else {
+            spellings.push(format!("--{name}"));
+        }
+    }
+    let mut column = spellings.join(", ");
+    if !abbreviated {
+        // Line long names up with the long names of abbreviated flags
+        column.insert_str(0, "    ");
+    }
+    if definition.allowed_type.is_unit() || definition.allowed_type.is_count() {
+        return column;
+    }
+    if definition.optional_value.is_some() {
+        column.push_str(&format!("[=<{}>]", definition.allowed_type));
+    } else {
+        column.push_str(&format!(" <{}>", definition.allowed_type).repeat(definition.num_values));
+    }
+    column
+}
+
+/// The help for a flag, followed by where else its value can come from.
+pub(crate) fn flag_help(config: &ParserConfig, definition: &FlagDefinition) -> String {
+    let mut parts: Vec<String> = definition.help.iter().cloned().collect();
+    if let Some(message) = &definition.deprecated {
+        parts.push(format!("[deprecated: {message}]"));
+    }
+    // Sensitive values are secret even when they are the default
+    if let Some(default) = &definition.default {
+        if definition.sensitive {
+            parts.push("[default: ***]".to_string());
+        } else {
+            parts.push(format!("[default: {default}]"));
+        }
+    }
+    if let Some(value) = &definition.optional_value {
+        if definition.sensitive {
+            parts.push("[when bare: ***]".to_string());
+        } else {
+            parts.push(format!("[when bare: {value}]"));
+        }
+    }
+    let env_var = match (&definition.env_var, &config.env_prefix) {
+        _ if definition.skip_env => None,
+        (Some(env_var), _) => Some(env_var.clone()),
+        (None, Some(env_prefix)) => Some(derive_env_var(env_prefix, &definition.name)),
+        (None, None) => None,
+    };
+    if let Some(env_var) = env_var {
+        parts.push(format!("[env: {env_var}]"));
+    }
+    parts.join(" ")
+}
+
+/// Writes a row of a table, wrapping the help so that it stays within `total_width` where possible.
+fn push_row(text: &mut String, total_width: usize, width: usize, column: &str, help: &str) {
+    let indent = 2 + width + 2;
+    let lines = wrap(help, total_width.saturating_sub(indent).max(20));
+    let padding = width - column.chars().count();
+    text.push_str(&format!("  {column}"));
+    for (index, line) in lines.iter().enumerate() {
+        if index == 0 {
+            text.push_str(&format!("{:padding$}  {line}", ""));
+        } else {
+            text.push_str(&format!("\n{:indent$}{line}", ""));
+        }
+    }
+    text.push('\n');
+}
+
+/// Splits text into lines of at most `width` characters, breaking between words.
+/// Words longer than `width` get a line of their own.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
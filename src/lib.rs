@@ -1,53 +1,1610 @@
 //! A tool for sorting and parsing command line arguments!
 //!
-//! [`gather_command_line_flags`] is your entry point. See also [`FlagDefinition`], [`Variant`], and [`Args`].
+//! [`Parser`] is your entry point, or [`gather_command_line_flags`] for a single call. See also [`FlagDefinition`], [`PositionalDefinition`], [`Variant`], and [`Args`].
 
 mod args;
+mod command_line;
+pub mod completions;
+#[cfg(any(feature = "toml", feature = "json"))]
+mod config_file;
+mod from_args;
+mod help;
+pub mod man;
+mod parser;
+mod prompt;
+#[cfg(feature = "serde")]
+mod serialization;
 mod variant;
 
 pub use crate::args::Args;
+pub use crate::command_line::split_command_line;
+#[cfg(feature = "json")]
+pub use crate::config_file::definitions_from_json;
+#[cfg(feature = "toml")]
+pub use crate::config_file::definitions_from_toml;
+#[doc(hidden)]
+pub use crate::from_args::__private;
+pub use crate::from_args::FromArgs;
+pub use crate::parser::Parser;
+pub use crate::prompt::NoPrompt;
+pub use crate::prompt::PromptSource;
+pub use crate::prompt::TerminalPrompt;
+#[cfg(feature = "serde")]
+pub use crate::serialization::tagged;
+pub use crate::variant::FromVariant;
+pub use crate::variant::ParseFailure;
+#[cfg(feature = "regex")]
+pub use crate::variant::Regex;
 pub use crate::variant::Variant;
 pub use crate::variant::VariantFlag;
-use std::{error::Error, fmt::Display};
+pub use crate::variant::VariantKind;
+pub use crate::variant::VariantTypeError;
+#[cfg(feature = "derive")]
+pub use flagged_cl_args_derive::FromArgs;
+use std::{error::Error, fmt::Display, sync::Arc};
 
 /// Defines a named argument that your program is expecting.
 ///
 /// ```
-/// FlagDefinition {
-///     name: "example".to_string(),
-///     abbreviation: Some('e'),
-///     allowed_type: VariantFlag::new_bool(),
-/// }
+/// # use flagged_cl_args::{FlagDefinition, VariantFlag};
+/// FlagDefinition::new("example", VariantFlag::bool()).abbrev('e');
 /// ```
 /// This value will be set by `binary_name --example true` or `binary_name -e false`
+///
+/// The argument after a flag that needs a value is always its value, even if it looks like a flag.
+/// A value can also be attached with `=`, like `--message=--help`.
+///
+/// ```
+/// # use flagged_cl_args::{FlagDefinition, Parser, VariantFlag};
+/// let parser = Parser::new("my-tool")
+///     .version("1.0.0")
+///     .flag(FlagDefinition::new("message", VariantFlag::string()).abbrev('m'))
+///     .flag(FlagDefinition::new("offset", VariantFlag::int()))
+///     .flag(FlagDefinition::new("point", VariantFlag::int()).num_values(2))
+///     .flag(FlagDefinition::new("verbose", VariantFlag::count()).abbrev('v'));
+/// for message in ["-v", "--help", "-h", "--version", "--", "--offset"] {
+///     for args in [
+///         &["my-tool", "--message", message, "--offset", "-5"][..],
+///         &["my-tool", &format!("-m={message}"), "--offset=-5"],
+///     ] {
+///         let args = parser.parse_from(args).unwrap();
+///         assert_eq!(args.get_named_string("message"), Some(message));
+///         assert_eq!(args.get_named_int("offset"), Some(-5));
+///         assert!(!args.help_requested() && args.version_requested().is_none());
+///         assert_eq!(args.get_named("verbose"), None);
+///     }
+/// }
+///
+/// let args = parser.parse_from(["my-tool", "--point", "-5", "-7", "-v"]).unwrap();
+/// assert_eq!(args.get_named("point").unwrap().to_string(), "-5,-7");
+/// assert_eq!(args.get_named_int("verbose"), Some(1));
+/// ```
+///
+/// Definitions are built with [`FlagDefinition::new`] and the methods that follow it, so that adding fields isn't a breaking change.
+/// Each field can also be set directly.
+///
+/// Definitions can be cloned and shared between threads, validators included.
+///
+/// ```
+/// # use flagged_cl_args::{FlagDefinition, PositionalDefinition, Subcommand, VariantFlag};
+/// fn assert_shareable<T: Clone + Send + Sync + std::fmt::Debug>() {}
+/// assert_shareable::<FlagDefinition>();
+/// assert_shareable::<PositionalDefinition>();
+/// assert_shareable::<Subcommand>();
+///
+/// let threads = FlagDefinition::new("threads", VariantFlag::int()).validator(|_| Ok(()));
+/// let copy = threads.clone();
+/// std::thread::spawn(move || assert_eq!(copy.name, "threads")).join().unwrap();
+/// assert!(format!("{threads:?}").contains("validator: Some(<function>)"));
+/// ```
+#[derive(Clone)]
+#[non_exhaustive]
 pub struct FlagDefinition {
     /// The name of the flagged argument.
-    /// Your end users can set this argument by passing `--name <value>`.
+    /// Your end users can set this argument by passing `--name <value>` or `--name=<value>`.
+    /// `--name=` sets the value to an empty string, which only succeeds if the allowed type accepts one.
+    /// Unit flags can be explicitly set with `--name=true` or `--name=false`, or other spellings like `--name=off` with [`VariantFlag::lenient_bools`].
     /// This will also be the key in the HashMap<String, Variant> produced by [`gather_command_line_flags`].
     pub name: String,
-    /// An optional abbreviation that can be set with `-a <value>`.
+    /// An optional abbreviation that can be set with `-a <value>` or `-a=<value>`.
+    /// Abbreviations of unit and counted flags can be combined, so `-rf` is the same as `-r -f`.
+    /// Values can be attached to abbreviations, so `-n5` is the same as `-n 5`.
     /// The named argument's key is still the argument's name, even if it was passed using its abbreviation.
     pub abbreviation: Option<char>,
+    /// Other names that can be used instead of `name`, like `colour` for a flag named `color`.
+    /// The key in the HashMap is always `name`, no matter which alias was given.
+    pub aliases: Vec<String>,
+    /// Other abbreviations that can be used instead of `abbreviation`, like `O` alongside `o`.
+    pub extra_abbreviations: Vec<char>,
     /// The type(s) that [`gather_command_line_flags`] will attempt to parse the given value into.
     pub allowed_type: VariantFlag,
+    /// How many values each occurrence of this argument takes, like 2 for `--resolution 1920 1080`, which is 1 by default.
+    /// With more than 1, the values are stored in a [`Variant::List`], and each is parsed with `allowed_type`.
+    ///
+    /// The values are the arguments after the flag, or a value attached to it followed by the rest, like `--resolution=1920 1080`.
+    /// Like any value, they are taken even if they look like flags, so `--resolution 1920 --force` is an error saying `--force` isn't an int.
+    /// From an environment variable the values are separated by whitespace, and from a config file they are an array.
+    pub num_values: usize,
+    /// Read a value given like `@token.txt` from that file, so secrets and large values don't need to be on the command line, where other users can see them.
+    /// The file's contents are trimmed, and `@@` starts a value that really begins with `@`, like `@@home` for `@home`.
+    /// [`Args::get_named_raw`] and parse errors show the value as it was given, not the file's contents.
+    ///
+    /// This only applies to values on the command line.
+    /// With [`ParserConfig::response_files`], this flag's values are read by the flag rather than as response files, so `@@` still means a literal `@`.
+    pub value_from_file: bool,
+    /// Keep this flag's value, like a password, out of errors, help, and the Debug output of [`Args`], which shows `***` instead.
+    /// [`Args::to_argv`] still includes it, to pass along to another program, and [`Args::to_argv_redacted`] doesn't.
+    pub sensitive: bool,
+    /// Ask for this flag's value with this prompt, like `Username: `, when it isn't given on the command line or by its environment variable.
+    /// The answer is read like the value of an environment variable, and an empty answer leaves the flag to its default if it has one.
+    /// [`FlagDefinition::sensitive`] flags hide the answer while it is typed.
+    ///
+    /// When parsing the arguments your program was run with, prompts are written to stderr and answered on stdin, see [`TerminalPrompt`].
+    /// When stdin isn't a terminal, the flag takes its default, or parsing fails saying it is missing.
+    /// Parsing arguments you pass in never asks, see [`NoPrompt`], unless it is given a [`PromptSource`] like [`Parser::parse_from_with_prompt`].
+    pub prompt: Option<String>,
+    /// What happens when this argument is given more than once.
+    pub repeat: RepeatPolicy,
+    /// Whether `--no-<name>` is also recognized, setting this argument to `false`.
+    /// Only unit flags and flags that allow [`VariantFlag::bool`] can be negated.
+    /// A flag that is literally named `no-<name>` always takes precedence over the negated spelling.
+    /// Giving both `--<name>` and `--no-<name>` is an error.
+    pub negatable: bool,
+    /// The value used when this argument isn't given, so [`crate::Args::get_named`] always finds it.
+    /// It must be a type that `allowed_type` permits, like [`Variant::Bool`] for unit flags or [`Variant::Int`] for counted flags.
+    /// Defaults for arguments that [`RepeatPolicy::Collect`] are put in a [`Variant::List`] if they aren't one already.
+    pub default: Option<Variant>,
+    /// The value used when this argument is given without one, like `auto` for a bare `--color`, which makes its value optional.
+    /// A value must then be attached, like `--color=always` or `-calways`, since the argument after a bare `--color` is never taken as its value.
+    /// That keeps `--color --force` and `--color dark` unambiguous, `dark` is always a positional argument.
+    /// It must be a type that `allowed_type` permits, and only flags that take a value can have one.
+    pub optional_value: Option<Variant>,
+    /// An environment variable to read this argument's value from when it isn't given on the command line, like `DATABASE_URL`.
+    /// The command line takes precedence over the environment, which takes precedence over `default`.
+    /// Setting this overrides the name derived from [`ParserConfig::env_prefix`].
+    /// Unit flags read `true` or `false` from the variable, and counted flags read a count.
+    pub env_var: Option<String>,
+    /// Never read this argument from the environment, even when [`ParserConfig::env_prefix`] would derive a variable for it.
+    /// Useful for secrets that shouldn't be picked up from the environment by accident.
+    pub skip_env: bool,
+    /// Marks this argument as deprecated, with a message like `use --color instead`.
+    /// Giving it, by any name or abbreviation, still works, but adds a warning to [`crate::Args::warnings`].
+    pub deprecated: Option<String>,
+    /// The names or aliases of other arguments that can't be given alongside this one.
+    /// Values from the command line, environment variables, prompts, and [`crate::Args::overlay_config`] count as given, defaults don't.
+    pub conflicts_with: Vec<String>,
+    /// The names or aliases of other arguments that must also be given whenever this one is, counted like [`FlagDefinition::conflicts_with`].
+    /// Requirements are checked when parsing, so a config file overlaid afterwards can't meet one.
+    pub requires: Vec<String>,
+    /// Checks a value after it has been parsed, returning a reason when the value isn't acceptable, like a number being out of range.
+    /// It runs for values from the command line, environment variables, and defaults alike.
+    /// For arguments that [`RepeatPolicy::Collect`], it runs once for each value.
+    pub validator: Option<Arc<Validator>>,
+    /// A description of this argument, shown to your end user by [`crate::Args::help_text`].
+    pub help: Option<String>,
+}
+
+impl Default for FlagDefinition {
+    fn default() -> Self {
+        FlagDefinition {
+            name: String::new(),
+            abbreviation: None,
+            aliases: Vec::new(),
+            extra_abbreviations: Vec::new(),
+            allowed_type: VariantFlag::default(),
+            num_values: 1,
+            value_from_file: false,
+            sensitive: false,
+            prompt: None,
+            repeat: RepeatPolicy::default(),
+            negatable: false,
+            default: None,
+            optional_value: None,
+            env_var: None,
+            skip_env: false,
+            deprecated: None,
+            conflicts_with: Vec::new(),
+            requires: Vec::new(),
+            validator: None,
+            help: None,
+        }
+    }
+}
+
+/// Validators can't be printed, so they are shown as `<function>`.
+impl std::fmt::Debug for FlagDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FlagDefinition")
+            .field("name", &self.name)
+            .field("abbreviation", &self.abbreviation)
+            .field("aliases", &self.aliases)
+            .field("extra_abbreviations", &self.extra_abbreviations)
+            .field("allowed_type", &self.allowed_type)
+            .field("num_values", &self.num_values)
+            .field("value_from_file", &self.value_from_file)
+            .field("sensitive", &self.sensitive)
+            .field("prompt", &self.prompt)
+            .field("repeat", &self.repeat)
+            .field("negatable", &self.negatable)
+            .field("default", &self.default)
+            .field("optional_value", &self.optional_value)
+            .field("env_var", &self.env_var)
+            .field("skip_env", &self.skip_env)
+            .field("deprecated", &self.deprecated)
+            .field("conflicts_with", &self.conflicts_with)
+            .field("requires", &self.requires)
+            .field("validator", &self.validator.as_ref().map(|_| Function))
+            .field("help", &self.help)
+            .finish()
+    }
+}
+
+/// Stands in for a validator when printing definitions.
+struct Function;
+
+impl std::fmt::Debug for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<function>")
+    }
+}
+
+impl FlagDefinition {
+    /// Creates a definition for the argument `--name`, which is parsed into one of the allowed types.
+    /// Use [`VariantFlag::new_unit`] for flags that don't take a value.
+    #[must_use]
+    pub fn new(name: impl Into<String>, allowed_type: VariantFlag) -> FlagDefinition {
+        FlagDefinition {
+            name: name.into(),
+            allowed_type,
+            ..Default::default()
+        }
+    }
+
+    /// Sets [`FlagDefinition::abbreviation`].
+    #[must_use]
+    pub fn abbrev(mut self, abbreviation: char) -> FlagDefinition {
+        self.abbreviation = Some(abbreviation);
+        self
+    }
+
+    /// Adds one of [`FlagDefinition::aliases`].
+    #[must_use]
+    pub fn alias(mut self, alias: impl Into<String>) -> FlagDefinition {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    /// Adds one of [`FlagDefinition::extra_abbreviations`].
+    #[must_use]
+    pub fn extra_abbrev(mut self, abbreviation: char) -> FlagDefinition {
+        self.extra_abbreviations.push(abbreviation);
+        self
+    }
+
+    /// Sets [`FlagDefinition::num_values`].
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+    /// let flags = [
+    ///     FlagDefinition::new("resolution", VariantFlag::int()).num_values(2),
+    ///     FlagDefinition::new("vertex", VariantFlag::float()).num_values(3).abbrev('v'),
+    ///     FlagDefinition::new("force", VariantFlag::new_unit()),
+    /// ];
+    /// let args = Args::from_slice(&["my-tool", "--resolution", "1920", "1080", "-v", "0", "-1.5", "2"], &[], &flags).unwrap();
+    /// assert_eq!(args.get_named("resolution"), Some(&Variant::List(vec![Variant::Int(1920), Variant::Int(1080)])));
+    /// assert_eq!(args.get_named("vertex").unwrap().to_string(), "0,-1.5,2");
+    /// assert_eq!(args.to_argv(&flags)[1..], ["--resolution", "1920", "1080", "--vertex", "0", "-1.5", "2"]);
+    ///
+    /// // An attached value is the first one
+    /// let args = Args::from_slice(&["my-tool", "--resolution=1920", "1080"], &[], &flags).unwrap();
+    /// assert_eq!(args.get_named("resolution").unwrap().to_string(), "1920,1080");
+    ///
+    /// let Err(error) = Args::from_slice(&["my-tool", "--resolution", "1920"], &[], &flags) else {
+    ///     panic!("a value is missing");
+    /// };
+    /// assert_eq!(error.to_string(), "expected 2 values for --resolution, got 1");
+    ///
+    /// let Err(error) = Args::from_slice(&["my-tool", "--resolution", "1920", "--force"], &[], &flags) else {
+    ///     panic!("--force isn't an int");
+    /// };
+    /// assert!(error.to_string().starts_with("--resolution at position 3: '--force' cannot be parsed as type int"));
+    ///
+    /// assert_eq!(
+    ///     Args::usage("my-tool", &[], &flags),
+    ///     "usage: my-tool [--resolution <int> <int>] [--vertex <float> <float> <float>] [--force]"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn num_values(mut self, num_values: usize) -> FlagDefinition {
+        self.num_values = num_values;
+        self
+    }
+
+    /// Sets [`FlagDefinition::value_from_file`].
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, VariantFlag};
+    /// let path = std::env::temp_dir().join("flagged_cl_args_value_from_file_example.txt");
+    /// std::fs::write(&path, "hunter2\n").unwrap();
+    /// let token = format!("@{}", path.display());
+    /// let flags = [
+    ///     FlagDefinition::new("token", VariantFlag::string()).value_from_file(),
+    ///     FlagDefinition::new("retries", VariantFlag::int()).value_from_file(),
+    ///     FlagDefinition::new("name", VariantFlag::string()),
+    /// ];
+    ///
+    /// let args = Args::from_slice(&["my-tool", "--token", &token, "--name", "@me"], &[], &flags).unwrap();
+    /// assert_eq!(args.get_named_string("token"), Some("hunter2"));
+    /// assert_eq!(args.get_named_raw("token"), Some(token.as_str()));
+    /// assert_eq!(args.get_named_string("name"), Some("@me"));
+    ///
+    /// // @@ is a literal @
+    /// let args = Args::from_slice(&["my-tool", "--token=@@home"], &[], &flags).unwrap();
+    /// assert_eq!(args.get_named_string("token"), Some("@home"));
+    ///
+    /// // Errors show where the value came from, rather than what's in the file
+    /// let Err(error) = Args::from_slice(&["my-tool", "--retries", &token], &[], &flags) else {
+    ///     panic!("hunter2 isn't an int");
+    /// };
+    /// assert!(error.to_string().starts_with(&format!("--retries at position 2: '{token}' cannot be parsed as type int")));
+    ///
+    /// std::fs::remove_file(&path).unwrap();
+    /// let Err(error) = Args::from_slice(&["my-tool", "--token", &token], &[], &flags) else {
+    ///     panic!("the file is gone");
+    /// };
+    /// assert!(error.to_string().starts_with(&format!("--token at position 2: could not read {}: ", path.display())));
+    /// assert!(std::error::Error::source(&error).is_some());
+    /// ```
+    #[must_use]
+    pub fn value_from_file(mut self) -> FlagDefinition {
+        self.value_from_file = true;
+        self
+    }
+
+    /// Sets [`FlagDefinition::sensitive`].
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, VariantFlag};
+    /// let flags = [
+    ///     FlagDefinition::new("password", VariantFlag::string()).sensitive(),
+    ///     FlagDefinition::new("pin", VariantFlag::int()).sensitive(),
+    ///     FlagDefinition::new("user", VariantFlag::string()),
+    /// ];
+    /// let args = Args::from_slice(&["my-tool", "--password", "hunter2", "--user", "me"], &[], &flags).unwrap();
+    /// assert_eq!(args.get_named_string("password"), Some("hunter2"));
+    /// let debug = format!("{args:?}");
+    /// assert!(!debug.contains("hunter2"));
+    /// assert!(debug.contains(r#""password": ***"#) && debug.contains(r#""user": String("me")"#));
+    ///
+    /// assert_eq!(args.to_argv(&flags)[1..], ["--password", "hunter2", "--user", "me"]);
+    /// assert_eq!(args.to_argv_redacted(&flags)[1..], ["--password", "***", "--user", "me"]);
+    ///
+    /// let Err(error) = Args::from_slice(&["my-tool", "--pin", "hunter2"], &[], &flags) else {
+    ///     panic!("hunter2 isn't an int");
+    /// };
+    /// assert_eq!(error.to_string(), "value for --pin at position 2 (redacted) could not be parsed as type int");
+    /// assert!(!format!("{error:?}").contains("hunter2"));
+    ///
+    /// // Nor is it in anything else that is shown or logged
+    /// assert_eq!(args.to_shell_string(&flags), "my-tool --password '***' --user me");
+    /// assert!(!format!("{:?}", args.events().collect::<Vec<_>>()).contains("hunter2"));
+    /// let error = args.try_get_named_int("password").unwrap_err();
+    /// assert_eq!(error.to_string(), "--password (redacted) is not an int");
+    ///
+    /// let flags = [FlagDefinition::new("password", VariantFlag::string())
+    ///     .sensitive()
+    ///     .validator(|value| match value.as_string() {
+    ///         Some(password) if password.len() < 8 => Err("too short".to_string()),
+    ///         _ => Ok(()),
+    ///     })];
+    /// let Err(error) = Args::from_slice(&["my-tool", "--password", "hunter2"], &[], &flags) else {
+    ///     panic!("hunter2 is too short");
+    /// };
+    /// assert_eq!(error.to_string(), "--password value (redacted): too short");
+    /// ```
+    #[must_use]
+    pub fn sensitive(mut self) -> FlagDefinition {
+        self.sensitive = true;
+        self
+    }
+
+    /// Sets [`FlagDefinition::prompt`].
+    ///
+    /// ```
+    /// # use flagged_cl_args::{FlagDefinition, Parser, PromptSource, ValueSource, Variant, VariantFlag};
+    /// // Answers prompts in order, recording what was asked
+    /// struct Answers(bool, Vec<&'static str>, Vec<(String, bool)>);
+    /// impl PromptSource for Answers {
+    ///     fn is_interactive(&self) -> bool {
+    ///         self.0
+    ///     }
+    ///     fn read_line(&mut self, prompt: &str, hidden: bool) -> std::io::Result<String> {
+    ///         self.2.push((prompt.to_string(), hidden));
+    ///         Ok(self.1.remove(0).to_string())
+    ///     }
+    /// }
+    ///
+    /// let parser = Parser::new("my-tool")
+    ///     .flag(FlagDefinition::new("user", VariantFlag::string()).prompt("Username: "))
+    ///     .flag(FlagDefinition::new("password", VariantFlag::string()).sensitive().prompt("Password: "))
+    ///     .flag(FlagDefinition::new("port", VariantFlag::int())
+    ///         .default(Variant::Int(22))
+    ///         .prompt("Port [22]: "));
+    /// let mut answers = Answers(true, vec!["hunter2", ""], Vec::new());
+    /// let args = parser.parse_from_with_prompt(["my-tool", "--user", "me"], &mut answers).unwrap();
+    /// assert_eq!(args.get_named_string("password"), Some("hunter2"));
+    /// assert_eq!(args.source_of("password"), Some(ValueSource::Prompt));
+    /// assert_eq!(args.get_named_int("port"), Some(22));
+    /// assert_eq!(
+    ///     answers.2,
+    ///     [("Password: ".to_string(), true), ("Port [22]: ".to_string(), false)]
+    /// );
+    ///
+    /// let mut answers = Answers(true, vec!["me", "hunter2", "twenty"], Vec::new());
+    /// let Err(error) = parser.parse_from_with_prompt(["my-tool"], &mut answers) else {
+    ///     panic!("twenty isn't an int");
+    /// };
+    /// assert_eq!(error.to_string(), "The answer 'twenty' is not a valid type for --port");
+    ///
+    /// // Without a terminal, there is nobody to ask
+    /// let mut answers = Answers(false, Vec::new(), Vec::new());
+    /// let Err(error) = parser.parse_from_with_prompt(["my-tool", "--user", "me"], &mut answers) else {
+    ///     panic!("--password is missing");
+    /// };
+    /// assert_eq!(error.to_string(), "--password was not given, and there is no terminal to ask for it");
+    /// ```
+    #[must_use]
+    pub fn prompt(mut self, prompt: &str) -> FlagDefinition {
+        self.prompt = Some(prompt.to_string());
+        self
+    }
+
+    /// Sets [`FlagDefinition::repeat`].
+    #[must_use]
+    pub fn repeat(mut self, repeat: RepeatPolicy) -> FlagDefinition {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Sets [`FlagDefinition::negatable`].
+    #[must_use]
+    pub fn negatable(mut self) -> FlagDefinition {
+        self.negatable = true;
+        self
+    }
+
+    /// Sets [`FlagDefinition::default`].
+    #[must_use]
+    pub fn default(mut self, default: Variant) -> FlagDefinition {
+        self.default = Some(default);
+        self
+    }
+
+    /// Sets [`FlagDefinition::optional_value`].
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+    /// let flags = [
+    ///     FlagDefinition::new("color", VariantFlag::choice(&["auto", "always", "never"]))
+    ///         .abbrev('c')
+    ///         .optional_value(Variant::String("auto".to_string())),
+    ///     FlagDefinition::new("force", VariantFlag::new_unit()),
+    /// ];
+    /// let args = Args::from_slice(&["my-tool", "--color", "--force"], &[], &flags).unwrap();
+    /// assert_eq!(args.get_named_string("color"), Some("auto"));
+    /// assert_eq!(args.get_named_bool("force"), Some(true));
+    ///
+    /// let args = Args::from_slice(&["my-tool", "--color=always"], &[], &flags).unwrap();
+    /// assert_eq!(args.get_named_string("color"), Some("always"));
+    /// let args = Args::from_slice(&["my-tool", "-cnever"], &[], &flags).unwrap();
+    /// assert_eq!(args.get_named_string("color"), Some("never"));
+    /// assert_eq!(args.to_argv(&flags), ["my-tool", "--color=never"]);
+    ///
+    /// // The next argument is never the value
+    /// let args = Args::from_slice(&["my-tool", "--color", "dark"], &[VariantFlag::string()], &flags).unwrap();
+    /// assert_eq!(args.get_named_string("color"), Some("auto"));
+    /// assert_eq!(args.get_positional_string(0), Some("dark"));
+    ///
+    /// assert_eq!(
+    ///     Args::usage("my-tool", &[], &flags),
+    ///     "usage: my-tool [--color[=<auto|always|never>]] [--force]"
+    /// );
+    /// assert!(Args::help_text("my-tool", &Default::default(), &[], &flags)
+    ///     .contains("  -c, --color[=<auto|always|never>]  [when bare: auto]\n"));
+    /// ```
+    #[must_use]
+    pub fn optional_value(mut self, value: Variant) -> FlagDefinition {
+        self.optional_value = Some(value);
+        self
+    }
+
+    /// Sets [`FlagDefinition::env_var`].
+    #[must_use]
+    pub fn env_var(mut self, env_var: impl Into<String>) -> FlagDefinition {
+        self.env_var = Some(env_var.into());
+        self
+    }
+
+    /// Sets [`FlagDefinition::skip_env`].
+    #[must_use]
+    pub fn skip_env(mut self) -> FlagDefinition {
+        self.skip_env = true;
+        self
+    }
+
+    /// Sets [`FlagDefinition::deprecated`].
+    #[must_use]
+    pub fn deprecated(mut self, message: impl Into<String>) -> FlagDefinition {
+        self.deprecated = Some(message.into());
+        self
+    }
+
+    /// Adds one of [`FlagDefinition::conflicts_with`].
+    #[must_use]
+    pub fn conflicts_with(mut self, name: impl Into<String>) -> FlagDefinition {
+        self.conflicts_with.push(name.into());
+        self
+    }
+
+    /// Adds one of [`FlagDefinition::requires`].
+    #[must_use]
+    pub fn requires(mut self, name: impl Into<String>) -> FlagDefinition {
+        self.requires.push(name.into());
+        self
+    }
+
+    /// Sets [`FlagDefinition::validator`].
+    #[must_use]
+    pub fn validator(
+        mut self,
+        validator: impl Fn(&Variant) -> Result<(), String> + Send + Sync + 'static,
+    ) -> FlagDefinition {
+        self.validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Sets [`FlagDefinition::help`].
+    #[must_use]
+    pub fn help(mut self, help: impl Into<String>) -> FlagDefinition {
+        self.help = Some(help.into());
+        self
+    }
+}
+
+/// Checks a parsed value, see [`FlagDefinition::validator`].
+pub type Validator = dyn Fn(&Variant) -> Result<(), String> + Send + Sync;
+
+/// Defines a positional argument that your program is expecting, for when a type alone isn't enough.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use flagged_cl_args::{PositionalDefinition, Variant, VariantFlag};
+/// PositionalDefinition {
+///     allowed_type: VariantFlag::int(),
+///     validator: Some(Arc::new(|value| match value {
+///         Variant::Int(1..=256) => Ok(()),
+///         _ => Err("must be between 1 and 256".to_string()),
+///     })),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct PositionalDefinition {
+    /// The type(s) that the argument will be parsed into.
+    pub allowed_type: VariantFlag,
+    /// Whether this argument must be given, which is the default.
+    /// Optional arguments can only be left off the end, so none of them can come before a required one.
+    pub required: bool,
+    /// The value used when this optional argument isn't given, so [`crate::Args::get_positional`] always finds it.
+    /// It must be a type that `allowed_type` permits, and every optional argument before this one needs a default too.
+    pub default: Option<Variant>,
+    /// What this argument is called, like `output`, which names it in help and in errors about missing arguments.
+    pub name: Option<String>,
+    /// Checks a value after it has been parsed, see [`FlagDefinition::validator`].
+    pub validator: Option<Arc<Validator>>,
+    /// A description of this argument, shown to your end user by [`crate::Args::help_text`].
+    pub help: Option<String>,
+    /// How many values this argument takes. Only the last positional argument can take more than one.
+    pub arity: PositionalArity,
+}
+
+/// Validators can't be printed, so they are shown as `<function>`.
+impl std::fmt::Debug for PositionalDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PositionalDefinition")
+            .field("allowed_type", &self.allowed_type)
+            .field("required", &self.required)
+            .field("default", &self.default)
+            .field("name", &self.name)
+            .field("validator", &self.validator.as_ref().map(|_| Function))
+            .field("help", &self.help)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+impl Default for PositionalDefinition {
+    fn default() -> Self {
+        PositionalDefinition {
+            allowed_type: VariantFlag::default(),
+            required: true,
+            default: None,
+            name: None,
+            validator: None,
+            help: None,
+            arity: PositionalArity::default(),
+        }
+    }
+}
+
+impl PositionalDefinition {
+    /// Makes this argument optional, see [`PositionalDefinition::required`].
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Parser, PositionalDefinition, Variant, VariantFlag};
+    /// let parser = Parser::new("my-tool")
+    ///     .positional(PositionalDefinition::from(VariantFlag::path()).name("input").default(Variant::Path("-".into())))
+    ///     .positional(PositionalDefinition::from(VariantFlag::path()).name("output").optional());
+    ///
+    /// let args = parser.parse_from(["my-tool"]).unwrap();
+    /// assert_eq!(args.get_positional_path(0).unwrap().to_str(), Some("-"));
+    /// assert!(args.get_positional(1).is_none());
+    /// assert!(parser.parse_from(["my-tool", "in.txt", "out.txt"]).unwrap().get_positional(1).is_some());
+    ///
+    /// let copy = Parser::new("copy")
+    ///     .positional(PositionalDefinition::from(VariantFlag::path()).name("from"))
+    ///     .positional(PositionalDefinition::from(VariantFlag::path()).name("to"));
+    /// let Err(error) = copy.parse_from(["copy", "a.txt"]) else {
+    ///     panic!("the destination is required");
+    /// };
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Not enough positional arguments were supplied, expected 2 but got 1, missing <to>"
+    /// );
+    /// assert_eq!(parser.usage(), "usage: my-tool [<input>] [<output>]");
+    /// ```
+    #[must_use]
+    pub fn optional(mut self) -> PositionalDefinition {
+        self.required = false;
+        self
+    }
+
+    /// Sets [`PositionalDefinition::default`], which also makes this argument optional.
+    #[must_use]
+    pub fn default(mut self, default: Variant) -> PositionalDefinition {
+        self.default = Some(default);
+        self.required = false;
+        self
+    }
+
+    /// Sets [`PositionalDefinition::name`].
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> PositionalDefinition {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Whether this argument can be left off, because it isn't required or takes zero or more values.
+    pub(crate) fn is_optional(&self) -> bool {
+        !self.required || self.arity == PositionalArity::ZeroOrMore
+    }
+
+    /// A last positional argument that takes every remaining value, if there are any, see [`crate::Args::positional_rest`].
+    ///
+    /// ```
+    /// # use flagged_cl_args::{FlagDefinition, Parser, PositionalDefinition, VariantFlag};
+    /// let parser = Parser::new("my-tool")
+    ///     .positional(VariantFlag::string())
+    ///     .positional(PositionalDefinition::zero_or_more(VariantFlag::path()))
+    ///     .flag(FlagDefinition::new("output", VariantFlag::path()));
+    ///
+    /// let args = parser
+    ///     .parse_from(["my-tool", "merge", "a.txt", "b.txt", "--output", "out.txt", "c.txt"])
+    ///     .unwrap();
+    /// assert_eq!(args.positional_rest().len(), 3);
+    /// assert!(args.get_positional_path(3).is_some());
+    /// assert!(parser.parse_from(["my-tool", "merge"]).unwrap().positional_rest().is_empty());
+    /// ```
+    #[must_use]
+    pub fn zero_or_more(allowed_type: VariantFlag) -> PositionalDefinition {
+        PositionalDefinition {
+            allowed_type,
+            arity: PositionalArity::ZeroOrMore,
+            ..Default::default()
+        }
+    }
+
+    /// A last positional argument that takes every remaining value, and at least one.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Parser, PositionalDefinition, VariantFlag};
+    /// let parser = Parser::new("my-tool").positional(PositionalDefinition::one_or_more(VariantFlag::path()));
+    /// assert_eq!(parser.parse_from(["my-tool", "a.txt", "b.txt"]).unwrap().positional_rest().len(), 2);
+    /// assert!(parser.parse_from(["my-tool"]).is_err());
+    /// assert_eq!(parser.usage(), "usage: my-tool <path>...");
+    /// ```
+    #[must_use]
+    pub fn one_or_more(allowed_type: VariantFlag) -> PositionalDefinition {
+        PositionalDefinition {
+            allowed_type,
+            arity: PositionalArity::OneOrMore,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<VariantFlag> for PositionalDefinition {
+    fn from(allowed_type: VariantFlag) -> Self {
+        PositionalDefinition {
+            allowed_type,
+            ..Default::default()
+        }
+    }
+}
+
+/// How many values a positional argument takes, see [`PositionalDefinition::arity`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum PositionalArity {
+    /// Exactly one value.
+    #[default]
+    One,
+    /// Every remaining value, if there are any.
+    ZeroOrMore,
+    /// Every remaining value, and at least one.
+    OneOrMore,
+}
+
+/// Defines a subcommand, like `fetch` in `binary fetch <url>`, with arguments of its own.
+/// See [`Args::new_with_subcommands`].
+///
+/// ```
+/// # use flagged_cl_args::{FlagDefinition, Subcommand, VariantFlag};
+/// Subcommand {
+///     name: "push".to_string(),
+///     positional_definitions: vec![VariantFlag::path().into()],
+///     flag_definitions: vec![FlagDefinition::new("force", VariantFlag::new_unit())],
+///     ..Default::default()
+/// };
+/// ```
+/// This subcommand is selected by `binary push <path> --force`.
+#[derive(Clone, Debug, Default)]
+pub struct Subcommand {
+    /// The name your end users give to select this subcommand.
+    /// This is the name returned alongside the subcommand's arguments, even if an alias was given.
+    pub name: String,
+    /// Other names that select this subcommand, like `rm` for `remove`.
+    pub aliases: Vec<String>,
+    /// The positional arguments that follow this subcommand, see [`PositionalDefinition`].
+    pub positional_definitions: Vec<PositionalDefinition>,
+    /// The named arguments that can follow this subcommand.
+    /// Global flags are only recognized before the subcommand.
+    pub flag_definitions: Vec<FlagDefinition>,
+}
+
+/// Determines what happens when a named argument is given more than once.
+/// Counted flags (see [`VariantFlag::count`]) always add up their occurrences instead.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum RepeatPolicy {
+    /// Giving the argument more than once is an error.
+    #[default]
+    Reject,
+    /// Each occurrence replaces the value of the previous one.
+    LastWins,
+    /// Every occurrence is kept, in order of appearance, in a [`Variant::List`].
+    Collect,
+}
+
+/// Where a value came from, see [`Args::source_of`].
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum ValueSource {
+    /// Given on the command line, the last time if it was given more than once.
+    CommandLine {
+        /// The position of the flag or positional argument, where the binary is 0.
+        index: usize,
+    },
+    /// Read from an environment variable.
+    Environment {
+        /// The name of the variable
+        var: String,
+    },
+    /// Read from a config file, by `Args::overlay_config` or `Args::overlay_json`.
+    ConfigFile,
+    /// Answered at a prompt, see [`FlagDefinition::prompt`].
+    Prompt,
+    /// The default, see [`FlagDefinition::default`] and [`PositionalDefinition::default`].
+    Default,
+}
+
+/// Something given on the command line, see [`Args::events`].
+#[derive(Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ArgEvent {
+    /// A positional argument.
+    Positional {
+        /// The position it was given at, where the binary is 0
+        index: usize,
+        /// Its parsed value
+        value: Variant,
+    },
+    /// A named argument.
+    Named {
+        /// The position its flag was given at, where the binary is 0
+        index: usize,
+        /// The name of its definition, whichever alias or abbreviation was used, shared with the other events for it
+        name: Arc<str>,
+        /// The value given this time
+        value: Variant,
+        /// Whether the flag is [`FlagDefinition::sensitive`], so Debug output shows `***` in place of its value
+        sensitive: bool,
+    },
+}
+
+/// The values of [`FlagDefinition::sensitive`] flags are shown as `***`.
+impl std::fmt::Debug for ArgEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgEvent::Positional { index, value } => f
+                .debug_struct("Positional")
+                .field("index", index)
+                .field("value", value)
+                .finish(),
+            ArgEvent::Named {
+                index,
+                name,
+                value,
+                sensitive,
+            } => {
+                let mut debug = f.debug_struct("Named");
+                debug.field("index", index).field("name", name);
+                if *sensitive {
+                    debug.field("value", &format_args!("***"));
+                } else {
+                    debug.field("value", value);
+                }
+                debug.field("sensitive", sensitive).finish()
+            }
+        }
+    }
+}
+
+/// What parsing found, telling apart an end user asking for help or the version from arguments to run with.
+///
+/// When they ask for help, or the version, nothing else is parsed, so mistakes in the other arguments don't get in the way.
+/// The help is rendered for the binary as it was run, the first argument.
+///
+/// ```
+/// # use flagged_cl_args::{FlagDefinition, ParseOutcome, Parser, ParserConfig, VariantFlag};
+/// let parser = Parser::new("my-tool")
+///     .positional(VariantFlag::path())
+///     .flag(FlagDefinition::new("threads", VariantFlag::int()))
+///     .version("1.0.0");
+///
+/// let Ok(ParseOutcome::HelpRequested(help)) =
+///     parser.parse_outcome_from(["./target/my-tool", "--threads", "many", "--help"])
+/// else {
+///     panic!("--help was given");
+/// };
+/// assert!(help.starts_with("usage: ./target/my-tool <path> [--threads <int>]\n"));
+/// assert!(help.contains("--threads <int>"));
+///
+/// let outcome = parser.parse_outcome_from(["my-tool", "-V"]).unwrap();
+/// assert_eq!(outcome, ParseOutcome::VersionRequested("1.0.0".to_string()));
+///
+/// let Ok(ParseOutcome::Parsed(args)) = parser.parse_outcome_from(["my-tool", "in.txt"]) else {
+///     panic!("nothing was asked for");
+/// };
+/// assert_eq!(args.get_positional_path(0), Some(&"in.txt".into()));
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+// Parsed arguments are the usual outcome, so they aren't boxed to make the others smaller
+#[allow(clippy::large_enum_variant)]
+pub enum ParseOutcome {
+    /// The arguments were parsed, so run with them.
+    Parsed(Args),
+    /// The end user asked for help with `--help` or `-h`, see [`ParserConfig::auto_help`]. Show them this, and exit successfully.
+    HelpRequested(String),
+    /// The end user asked for the version with `--version` or `-V`, see [`ParserConfig::version`]. Show them this, and exit successfully.
+    VersionRequested(String),
+}
+
+impl ParseOutcome {
+    /// Tells apart parsed arguments from a request for help or the version.
+    pub(crate) fn new(
+        args: Args,
+        config: &ParserConfig,
+        positional_definitions: &[PositionalDefinition],
+        flag_definitions: &[FlagDefinition],
+    ) -> ParseOutcome {
+        // Automatic help and version are set without a source, unlike a flag of your own that takes their place
+        let automatic = |name: &str| args.source_of(name).is_none();
+        if config.auto_help && automatic("help") && args.help_requested() {
+            return ParseOutcome::HelpRequested(Args::help_text(
+                args.binary(),
+                config,
+                positional_definitions,
+                flag_definitions,
+            ));
+        }
+        if config.version.is_some()
+            && automatic("version")
+            && let Some(version) = args.version_requested()
+        {
+            return ParseOutcome::VersionRequested(version.to_string());
+        }
+        ParseOutcome::Parsed(args)
+    }
+}
+
+/// How the arguments in a response file are written, see [`ParserConfig::response_files`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ResponseFiles {
+    /// Each line is one argument, as it is. Empty lines are skipped.
+    Lines,
+    /// The whole file is split like a shell would, see [`split_command_line`].
+    ShellSplit,
+}
+
+/// What happens when one of your flags uses a name or abbreviation of an automatic flag, see [`ParserConfig::reserved_names`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum ReservedNames {
+    /// Your flag takes its place, and [`Parser::warnings`] says so.
+    #[default]
+    Yield,
+    /// The definitions are rejected before parsing anything.
+    Reject,
+}
+
+/// Controls behavior that isn't specific to a single argument, see [`Args::new_with_config`].
+///
+/// ```
+/// # use flagged_cl_args::ParserConfig;
+/// ParserConfig {
+///     env_prefix: Some("MYTOOL_".to_string()),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ParserConfig {
+    /// Every named argument without an explicit [`FlagDefinition::env_var`] falls back to an environment variable derived from its name.
+    ///
+    /// The variable's name is the prefix followed by the flag's name in uppercase, with dashes replaced by underscores.
+    /// Digits and underscores are kept as they are.
+    /// With a prefix of `MYTOOL_`, `--log-level` reads `MYTOOL_LOG_LEVEL`, and `--retry_count2` reads `MYTOOL_RETRY_COUNT2`.
+    /// Set [`FlagDefinition::skip_env`] to keep an argument, like a secret, out of the environment entirely.
+    pub env_prefix: Option<String>,
+    /// Recognize `--help` and `-h`, even though they aren't in your flag definitions. On by default.
+    ///
+    /// When either is given, parsing stops and only the named argument `help` is set to `Variant::Bool(true)`, see [`Args::help_requested`] and [`ParseOutcome::HelpRequested`].
+    /// The functions that take no [`ParserConfig`], like [`Args::new`], [`Args::from_iter`], and [`gather_command_line_flags`], leave this off, so `--help` is an unknown flag to them like it always was.
+    /// Use a [`Parser`], [`gather_command_line_outcome`], or [`Args::new_with_config`] for automatic help.
+    /// If one of your flags is named `help` or abbreviated `h`, your flag takes its place, see [`ParserConfig::reserved_names`].
+    pub auto_help: bool,
+    /// The width, in columns, that [`Args::help_text`] wraps text to fit in. 80 by default.
+    pub help_width: usize,
+    /// Recognize `--version` and `-V`, even though they aren't in your flag definitions, like `Some(env!("CARGO_PKG_VERSION").to_string())`.
+    ///
+    /// When either is given, parsing stops and only the named argument `version` is set to this string, see [`Args::version_requested`] and [`ParseOutcome::VersionRequested`].
+    /// If one of your flags is named `version` or abbreviated `V`, your flag takes its place, see [`ParserConfig::reserved_names`].
+    pub version: Option<String>,
+    /// Add a line showing how to run your program, see [`Args::usage`], to every [`ArgumentError`] from parsing.
+    pub usage_in_errors: bool,
+    /// Keep parsing past arguments that are wrong, and report every problem at once. Off by default.
+    ///
+    /// Unknown flags, values that can't be parsed, and positional arguments that are wrong or too many are all reported, in the order they were given, see [`ArgumentError::errors`].
+    /// A flag missing its value at the very end of the arguments is still reported along with everything before it.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{ArgumentErrorKind, FlagDefinition, Parser, ParserConfig, VariantFlag};
+    /// let parser = Parser::new("my-tool")
+    ///     .positional(VariantFlag::int())
+    ///     .flag(FlagDefinition::new("threads", VariantFlag::int()))
+    ///     .config(ParserConfig {
+    ///         collect_errors: true,
+    ///         ..Default::default()
+    ///     });
+    ///
+    /// let Err(error) = parser.parse_from(["my-tool", "--thread", "five", "--threads", "4.5"]) else {
+    ///     panic!("nothing here is right");
+    /// };
+    /// assert_eq!(error.errors().len(), 3);
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "--thread at position 1 does not match any known flag name (did you mean --threads?)\n\
+    ///      Positional argument 0 at position 2: 'five' cannot be parsed as type int: invalid digit found in string\n\
+    ///      --threads at position 4: '4.5' cannot be parsed as type int: invalid digit found in string"
+    /// );
+    /// ```
+    pub collect_errors: bool,
+    /// Accept the start of a flag's name or alias, like `--verb` for `--verbose`, as long as only one flag starts that way. Off by default.
+    ///
+    /// A flag spelled exactly the way it was given always wins, even when other flags start the same way.
+    /// When several flags could be meant, the error lists them, see [`ArgumentErrorKind::AmbiguousFlag`].
+    ///
+    /// ```
+    /// # use flagged_cl_args::{FlagDefinition, Parser, ParserConfig, VariantFlag};
+    /// let parser = Parser::new("my-tool")
+    ///     .flag(FlagDefinition::new("log", VariantFlag::new_unit()))
+    ///     .flag(FlagDefinition::new("log-level", VariantFlag::int()))
+    ///     .flag(FlagDefinition::new("verbose", VariantFlag::new_unit()))
+    ///     .config(ParserConfig {
+    ///         prefix_matching: true,
+    ///         ..Default::default()
+    ///     });
+    ///
+    /// let args = parser.parse_from(["my-tool", "--verb", "--log", "--log-l", "3"]).unwrap();
+    /// assert_eq!(args.get_named_bool("verbose"), Some(true));
+    /// assert_eq!(args.get_named_bool("log"), Some(true));
+    /// assert_eq!(args.get_named_int("log-level"), Some(3));
+    ///
+    /// let Err(error) = parser.parse_from(["my-tool", "--lo"]) else {
+    ///     panic!("--lo could be either --log or --log-level");
+    /// };
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "--lo at position 1 is ambiguous, it could be --log or --log-level"
+    /// );
+    /// ```
+    pub prefix_matching: bool,
+    /// Match long flag names and aliases regardless of case, so `--Output` and `--OUTPUT` both set `--output`. Off by default.
+    ///
+    /// Named arguments are still stored under the name in their [`FlagDefinition`], however they were typed.
+    /// Abbreviations stay case sensitive, since `-v` and `-V` are often different flags.
+    /// Flags whose names only differ by case are rejected, since they can't be told apart.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{FlagDefinition, Parser, ParserConfig, VariantFlag};
+    /// let config = ParserConfig {
+    ///     case_insensitive_names: true,
+    ///     ..Default::default()
+    /// };
+    /// let parser = Parser::new("my-tool")
+    ///     .flag(FlagDefinition::new("output", VariantFlag::path()))
+    ///     .config(config.clone());
+    ///
+    /// let args = parser.parse_from(["my-tool", "--OUTPUT", "out.txt"]).unwrap();
+    /// assert!(args.get_named_path("output").is_some());
+    ///
+    /// let parser = Parser::new("my-tool")
+    ///     .flag(FlagDefinition::new("output", VariantFlag::path()))
+    ///     .flag(FlagDefinition::new("Output", VariantFlag::path()))
+    ///     .config(config);
+    /// let Err(error) = parser.parse_from(["my-tool"]) else {
+    ///     panic!("--output and --Output can't be told apart");
+    /// };
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "--Output only differs by case from --output, so they can't be told apart while names are case insensitive"
+    /// );
+    /// ```
+    pub case_insensitive_names: bool,
+    /// Replace an argument like `@args.txt` with the arguments in the file `args.txt`, before anything is parsed. Off by default.
+    ///
+    /// Response files can include other response files, up to 10 deep.
+    /// Write `@@` to pass an argument starting with `@` as it is, like `@@home` for `@home`.
+    /// A lone `@`, anything after a bare `--`, and the values of [`FlagDefinition::value_from_file`] flags are left alone.
+    /// Positions in errors count the arguments after they were replaced.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{FlagDefinition, Parser, ParserConfig, ResponseFiles, VariantFlag};
+    /// let path = std::env::temp_dir().join("flagged_cl_args_response_file_example.txt");
+    /// std::fs::write(&path, "--name\nsomeone else\n").unwrap();
+    /// let parser = Parser::new("my-tool")
+    ///     .positional(VariantFlag::string())
+    ///     .flag(FlagDefinition::new("name", VariantFlag::string()))
+    ///     .config(ParserConfig {
+    ///         response_files: Some(ResponseFiles::Lines),
+    ///         ..Default::default()
+    ///     });
+    ///
+    /// let args = parser.parse_from(["my-tool".into(), format!("@{}", path.display()), "@@me".into()]).unwrap();
+    /// assert_eq!(args.get_named_string("name"), Some("someone else"));
+    /// assert_eq!(args.get_positional_string(0), Some("@me"));
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    pub response_files: Option<ResponseFiles>,
+    /// Keep arguments that look like flags but don't match any definition in [`Args::unknown`], instead of failing. Off by default.
+    ///
+    /// An unknown flag never takes the argument after it, since there's no way to know whether it needs one.
+    /// Its value only goes along with it when attached with `=`, like `--color=always`, otherwise the next argument is parsed as usual.
+    /// When several abbreviations are combined, like `-vx`, the whole argument is unknown if any of them are.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{FlagDefinition, Parser, ParserConfig, VariantFlag};
+    /// let parser = Parser::new("wrapper")
+    ///     .positional(VariantFlag::path())
+    ///     .flag(FlagDefinition::new("verbose", VariantFlag::new_unit()))
+    ///     .config(ParserConfig {
+    ///         allow_unknown_flags: true,
+    ///         ..Default::default()
+    ///     });
+    ///
+    /// let args = parser
+    ///     .parse_from(["wrapper", "--color=always", "--verbose", "--jobs", "in.txt"])
+    ///     .unwrap();
+    /// assert_eq!(args.unknown(), ["--color=always", "--jobs"]);
+    /// assert_eq!(args.get_named_bool("verbose"), Some(true));
+    /// // --jobs doesn't take in.txt as its value
+    /// assert!(args.get_positional_path(0).is_some());
+    /// ```
+    pub allow_unknown_flags: bool,
+    /// What happens when your flags use the names of automatic flags, which are `--help` and `-h` (see [`ParserConfig::auto_help`]), `--version` and `-V` (see [`ParserConfig::version`]), and `--no-<name>` for a negatable flag (see [`FlagDefinition::negatable`]).
+    ///
+    /// Your definitions always win over automatic flags, each spelling on its own, so a flag abbreviated `h` still leaves `--help` for automatic help.
+    /// By default they do so with a warning from [`Parser::warnings`], and [`ReservedNames::Reject`] makes it a problem with the definitions instead.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{FlagDefinition, ParseOutcome, Parser, ParserConfig, ReservedNames, VariantFlag};
+    /// let parser = Parser::new("my-tool")
+    ///     .flag(FlagDefinition::new("help", VariantFlag::string()))
+    ///     .flag(FlagDefinition::new("cache", VariantFlag::new_unit()).negatable())
+    ///     .flag(FlagDefinition::new("no-cache", VariantFlag::new_unit()));
+    /// assert_eq!(
+    ///     parser.warnings(),
+    ///     [
+    ///         "--help uses --help, so it isn't used for automatic help",
+    ///         "--no-cache uses --no-cache, so it isn't used for negating --cache",
+    ///     ]
+    /// );
+    /// let Ok(ParseOutcome::Parsed(args)) = parser.parse_outcome_from(["my-tool", "--help", "topic"]) else {
+    ///     panic!("--help is taken");
+    /// };
+    /// assert_eq!(args.get_named_string("help"), Some("topic"));
+    /// // -h is still automatic help
+    /// assert!(matches!(parser.parse_outcome_from(["my-tool", "-h"]), Ok(ParseOutcome::HelpRequested(_))));
+    ///
+    /// // Without automatic help there's nothing to collide with
+    /// let parser = parser.config(ParserConfig {
+    ///     auto_help: false,
+    ///     reserved_names: ReservedNames::Reject,
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(parser.warnings(), Vec::<String>::new());
+    /// let Err(error) = parser.parse_from(["my-tool", "--help", "topic"]) else {
+    ///     panic!("--no-cache collides with negating --cache");
+    /// };
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "--no-cache can't use --no-cache, because it is used for negating --cache"
+    /// );
+    ///
+    /// let parser = Parser::new("my-tool")
+    ///     .flag(FlagDefinition::new("help", VariantFlag::new_unit()))
+    ///     .config(ParserConfig {
+    ///         reserved_names: ReservedNames::Reject,
+    ///         ..Default::default()
+    ///     });
+    /// let Err(error) = parser.parse_from(["my-tool"]) else {
+    ///     panic!("--help is automatic help");
+    /// };
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "--help can't use --help, because it is used for automatic help"
+    /// );
+    /// ```
+    pub reserved_names: ReservedNames,
+    /// Also recognize flags written like on Windows, `/name value` or `/name:value`, and `/a` for an abbreviation. Off by default.
+    ///
+    /// Only an argument that is exactly `/` followed by one of your flag's names, aliases, or abbreviations is a flag, optionally followed by `:` and its value.
+    /// Anything else starting with `/`, like the path `/usr/bin/tool`, is left as a positional argument.
+    /// So a path that is spelled exactly like a flag, like `/output`, needs to come after a bare `--`, or be written another way, like `//output`.
+    /// Prefix matching and `--no-` negation only apply to the `--` spelling.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// # use flagged_cl_args::{FlagDefinition, Parser, ParserConfig, VariantFlag};
+    /// let parser = Parser::new("legacy-tool")
+    ///     .positional(VariantFlag::path())
+    ///     .flag(FlagDefinition::new("output", VariantFlag::path()).abbrev('o'))
+    ///     .flag(FlagDefinition::new("verbose", VariantFlag::new_unit()).abbrev('v'))
+    ///     .config(ParserConfig {
+    ///         slash_flags: true,
+    ///         ..Default::default()
+    ///     });
+    ///
+    /// for args in [
+    ///     ["legacy-tool", "/output", r"C:\x", "/v", "/usr/bin/foo"],
+    ///     ["legacy-tool", r"/output:C:\x", "/verbose", "/usr/bin/foo", "--"],
+    ///     ["legacy-tool", "/usr/bin/foo", r"/o:C:\x", "/verbose:true", "--"],
+    ///     ["legacy-tool", "--output", r"C:\x", "-v", "/usr/bin/foo"],
+    /// ] {
+    ///     let args = parser.parse_from(args).unwrap();
+    ///     assert_eq!(args.get_named_path("output"), Some(&PathBuf::from(r"C:\x")));
+    ///     assert_eq!(args.get_named_bool("verbose"), Some(true));
+    ///     assert_eq!(args.get_positional_path(0), Some(&PathBuf::from("/usr/bin/foo")));
+    /// }
+    ///
+    /// // Only exact names are flags, whichever separator follows
+    /// for path in ["/out", "/outputs", "/output/file", "/out:x", "/vv", "/x:y", "/", "//output"] {
+    ///     let args = parser.parse_from(["legacy-tool", path]).unwrap();
+    ///     assert_eq!(args.get_positional_path(0), Some(&PathBuf::from(path)), "{path}");
+    ///     assert_eq!(args.get_named("output"), None);
+    /// }
+    /// let args = parser.parse_from(["legacy-tool", "--", "/output"]).unwrap();
+    /// assert_eq!(args.get_positional_path(0), Some(&PathBuf::from("/output")));
+    ///
+    /// // Without it, an argument starting with / is never a flag
+    /// let args = Parser::new("legacy-tool")
+    ///     .positional(VariantFlag::path())
+    ///     .flag(FlagDefinition::new("verbose", VariantFlag::new_unit()))
+    ///     .parse_from(["legacy-tool", "/verbose"])
+    ///     .unwrap();
+    /// assert_eq!(args.get_positional_path(0), Some(&PathBuf::from("/verbose")));
+    /// ```
+    pub slash_flags: bool,
+    /// Also recognize long names and aliases after a single dash, like `-verbose` and `-threads=4`, like Go and Java tools do. Off by default.
+    ///
+    /// A token starting with a single dash is read the first way that matches:
+    ///
+    /// | Token | Read as |
+    /// |-------|---------|
+    /// | `-threads`, `-threads=4` | The flag named or aliased `threads`, with its value attached if `=` follows the name |
+    /// | `-rfv` | The unit or counted flags abbreviated `r`, `f`, and `v`, like without this option |
+    /// | `-n5`, `-rn5`, `-Xmx2g` | Abbreviations, the last of which takes the rest of the token as its value, like without this option |
+    /// | `-5`, `-.5` | A negative number, unless a flag is abbreviated `5`, like without this option |
+    /// | `-v`, `-v=3` | A single abbreviation, since one character is never a long name |
+    ///
+    /// So a long name always wins over abbreviations that happen to spell it, and a token that matches neither is an error saying so.
+    /// Prefix matching and `--no-` negation only apply to the `--` spelling, which still works as usual.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{FlagDefinition, Parser, ParserConfig, VariantFlag};
+    /// let parser = Parser::new("wrapped-tool")
+    ///     .flag(FlagDefinition::new("verbose", VariantFlag::new_unit()).abbrev('v'))
+    ///     .flag(FlagDefinition::new("threads", VariantFlag::int()).abbrev('t'))
+    ///     .flag(FlagDefinition::new("extra", VariantFlag::string()).abbrev('X'))
+    ///     .flag(FlagDefinition::new("recursive", VariantFlag::new_unit()).abbrev('r'))
+    ///     .config(ParserConfig {
+    ///         single_dash_long: true,
+    ///         ..Default::default()
+    ///     });
+    ///
+    /// let args = parser.parse_from(["wrapped-tool", "-verbose", "-threads", "4", "-Xmx2g"]).unwrap();
+    /// assert_eq!(args.get_named_bool("verbose"), Some(true));
+    /// assert_eq!(args.get_named_int("threads"), Some(4));
+    /// assert_eq!(args.get_named_string("extra"), Some("mx2g"));
+    ///
+    /// let args = parser.parse_from(["wrapped-tool", "-threads=8", "-rv", "--extra", "x"]).unwrap();
+    /// assert_eq!(args.get_named_int("threads"), Some(8));
+    /// assert_eq!(args.get_named_bool("recursive"), Some(true));
+    /// assert_eq!(args.get_named_bool("verbose"), Some(true));
+    ///
+    /// let args = parser.parse_from(["wrapped-tool", "-rt5"]).unwrap();
+    /// assert_eq!(args.get_named_int("threads"), Some(5));
+    ///
+    /// let Err(error) = parser.parse_from(["wrapped-tool", "-verbos"]) else {
+    ///     panic!("-verbos is neither a name nor abbreviations");
+    /// };
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "-verbos at position 1 does not match any known flag name, or combined abbreviations (did you mean --verbose?)"
+    /// );
+    /// ```
+    pub single_dash_long: bool,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        ParserConfig {
+            env_prefix: None,
+            auto_help: true,
+            help_width: 80,
+            version: None,
+            usage_in_errors: false,
+            collect_errors: false,
+            prefix_matching: false,
+            case_insensitive_names: false,
+            response_files: None,
+            allow_unknown_flags: false,
+            reserved_names: ReservedNames::Yield,
+            slash_flags: false,
+            single_dash_long: false,
+        }
+    }
 }
 
 /// A simple error type.
 /// If something is wrong with the user's input, showing them this error will guide them to correcting it!
-#[derive(Debug)]
-pub struct ArgumentError(String);
+///
+/// To react to specific problems, match on [`ArgumentError::kind`].
+///
+/// ```
+/// # use flagged_cl_args::{ArgumentErrorKind, FlagDefinition, Parser, VariantFlag};
+/// use std::error::Error;
+/// let parser = Parser::new("my-tool").flag(FlagDefinition::new("threads", VariantFlag::int()));
+///
+/// let Err(error) = parser.parse_from(["my-tool", "--thread", "4"]) else {
+///     panic!("--thread is misspelled");
+/// };
+/// assert!(matches!(
+///     error.kind(),
+///     ArgumentErrorKind::UnknownFlag { name, position: 1, .. } if name == "--thread"
+/// ));
+/// assert_eq!(
+///     error.to_string(),
+///     "--thread at position 1 does not match any known flag name (did you mean --threads?)"
+/// );
+///
+/// // Nothing is suggested when no flag is close enough
+/// let Err(error) = parser.parse_from(["my-tool", "--x"]) else {
+///     panic!("--x isn't a flag");
+/// };
+/// assert_eq!(error.to_string(), "--x at position 1 does not match any known flag name");
+///
+/// let Err(error) = parser.parse_from(["my-tool", "--threads", "four"]) else {
+///     panic!("four is not an int");
+/// };
+/// let ArgumentErrorKind::ParseFailed { value, .. } = error.kind() else {
+///     panic!("four can't be parsed");
+/// };
+/// assert_eq!(value, "four");
+///
+/// // When a value only had one type to be, the error from that type's parser is the source
+/// let Err(error) = parser.parse_from(["my-tool", "--threads", "99999999999"]) else {
+///     panic!("99999999999 is too big for an int");
+/// };
+/// let source = error.source().unwrap();
+/// assert!(source.is::<std::num::ParseIntError>());
+/// assert_eq!(
+///     error.to_string(),
+///     "--threads at position 2: '99999999999' cannot be parsed as type int: number too large to fit in target type"
+/// );
+///
+/// // With several types, the source says why each of them didn't accept the value
+/// let peers = Parser::new("my-tool").flag(FlagDefinition::new("peer", VariantFlag::int().or_socket()));
+/// let Err(error) = peers.parse_from(["my-tool", "--peer", "localhos:8080"]) else {
+///     panic!("localhos:8080 is neither an int nor a socket");
+/// };
+/// assert_eq!(
+///     error.to_string(),
+///     "--peer at position 2: 'localhos:8080' cannot be parsed as type int|socket: \
+///      tried int: invalid digit found in string; tried socket: invalid socket address syntax"
+/// );
+///
+/// // Errors can be compared, so they can be checked as a whole
+/// let Err(error) = parser.parse_from(["my-tool", "--threads"]) else {
+///     panic!("--threads needs a value");
+/// };
+/// assert_eq!(
+///     error,
+///     ArgumentErrorKind::MissingValue { flag: "threads".to_string() }.into()
+/// );
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct ArgumentError {
+    /// Boxed, since parse failures carry a whole [`VariantFlag`] and errors are returned everywhere
+    kind: Box<ArgumentErrorKind>,
+    /// Lines added after the description, see [`ArgumentError::with_usage`]
+    usage: Option<String>,
+    /// The error behind this one, see [`Error::source`]
+    source: Option<Cause>,
+}
+
+/// An error that caused an [`ArgumentError`], shared so the error can still be cloned.
+/// Causes are compared by their description, since errors can't be compared in general.
+#[derive(Clone)]
+struct Cause(Arc<dyn Error + Send + Sync>);
+
+impl PartialEq for Cause {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_string() == other.0.to_string()
+    }
+}
+
+impl std::fmt::Debug for Cause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.0, f)
+    }
+}
 
 impl ArgumentError {
     fn new(description: &str) -> ArgumentError {
-        ArgumentError(description.to_string())
+        ArgumentErrorKind::Other(description.to_string()).into()
+    }
+
+    /// Adds a usage line, like the one from [`Args::usage`], after the description.
+    #[must_use]
+    pub fn with_usage(mut self, usage: &str) -> ArgumentError {
+        self.usage = Some(match self.usage {
+            Some(existing) => format!("{existing}\n{usage}"),
+            None => usage.to_string(),
+        });
+        self
+    }
+
+    /// Sets the error behind this one, which is shown after the description, see [`Error::source`].
+    pub(crate) fn with_source(mut self, source: Arc<dyn Error + Send + Sync>) -> ArgumentError {
+        self.source = Some(Cause(source));
+        self
+    }
+
+    /// What went wrong.
+    #[must_use]
+    pub fn kind(&self) -> &ArgumentErrorKind {
+        &self.kind
+    }
+
+    /// Every problem this error is made of, see [`ParserConfig::collect_errors`].
+    /// Unless there were several, this is just the error itself.
+    #[must_use]
+    pub fn errors(&self) -> &[ArgumentError] {
+        match &*self.kind {
+            ArgumentErrorKind::Multiple(errors) => errors,
+            _ => std::slice::from_ref(self),
+        }
+    }
+}
+
+impl From<ArgumentErrorKind> for ArgumentError {
+    fn from(kind: ArgumentErrorKind) -> Self {
+        ArgumentError {
+            kind: Box::new(kind),
+            usage: None,
+            source: None,
+        }
     }
 }
 
-impl Error for ArgumentError {}
+impl Error for ArgumentError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &*source.0 as &(dyn Error + 'static))
+    }
+}
 
 impl Display for ArgumentError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.kind)?;
+        if let Some(source) = &self.source {
+            write!(f, ": {}", source.0)?;
+        }
+        if let Some(usage) = &self.usage {
+            write!(f, "\n{usage}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The problem behind an [`ArgumentError`].
+/// It displays the same description as the error, without any usage line.
+#[derive(Clone, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum ArgumentErrorKind {
+    /// An argument looks like a flag, but doesn't match any definition.
+    UnknownFlag {
+        /// The flag as it was given, like `--colour` or `-x`
+        name: String,
+        /// Where the argument is, the binary being position 0
+        position: usize,
+        /// A defined flag that is spelled similarly, like `--verbose` for `--verbos`, or `-v` for `-V`
+        suggestion: Option<String>,
+    },
+    /// An argument is the start of several flags' names, see [`ParserConfig::prefix_matching`].
+    AmbiguousFlag {
+        /// The flag as it was given, like `--lo`
+        name: String,
+        /// Where the argument is, the binary being position 0
+        position: usize,
+        /// The flags it could be, like `--log` and `--log-level`
+        candidates: Vec<String>,
+    },
+    /// A flag that needs a value was the last argument.
+    MissingValue {
+        /// The flag's name, from its [`FlagDefinition`]
+        flag: String,
+    },
+    /// A value could not be parsed into any of the types its argument allows.
+    ParseFailed {
+        /// The argument the value is for, like `--threads` or `Positional argument 0`
+        flag_or_position: String,
+        /// Where the value is, the binary being position 0
+        position: usize,
+        /// The value as it was given
+        value: String,
+        /// The types the value was allowed to become
+        allowed: VariantFlag,
+        /// Why the value wasn't accepted, when more is known than its type being wrong
+        reason: Option<String>,
+    },
+    /// More positional arguments were given than were defined.
+    TooManyPositionals {
+        /// How many are defined
+        max: usize,
+        /// How many were given
+        got: usize,
+    },
+    /// Fewer positional arguments were given than are required.
+    NotEnoughPositionals {
+        /// How many are required
+        expected: usize,
+        /// How many were given
+        got: usize,
+        /// The required arguments that weren't given, like `<output>` or `<POS2:path>`
+        missing: Vec<String>,
+    },
+    /// Several problems, in the order they were found, see [`ParserConfig::collect_errors`].
+    Multiple(Vec<ArgumentError>),
+    /// Anything else, described by the text.
+    Other(String),
+}
+
+impl Display for ArgumentErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgumentErrorKind::UnknownFlag {
+                name,
+                position,
+                suggestion,
+            } => {
+                let known = if name.starts_with("--") {
+                    "name"
+                } else if name.chars().nth(2).is_some() {
+                    // Only with `ParserConfig::single_dash_long`, when neither way of reading it matched
+                    "name, or combined abbreviations"
+                } else {
+                    "abbreviation"
+                };
+                write!(
+                    f,
+                    "{name} at position {position} does not match any known flag {known}"
+                )?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, " (did you mean {suggestion}?)")?;
+                }
+                Ok(())
+            }
+            ArgumentErrorKind::AmbiguousFlag {
+                name,
+                position,
+                candidates,
+            } => {
+                write!(
+                    f,
+                    "{name} at position {position} is ambiguous, it could be "
+                )?;
+                match candidates.split_last() {
+                    Some((last, rest)) if !rest.is_empty() => {
+                        write!(f, "{} or {last}", rest.join(", "))
+                    }
+                    _ => write!(f, "{}", candidates.join(", ")),
+                }
+            }
+            ArgumentErrorKind::MissingValue { flag } => {
+                write!(f, "Unexpected end of arguments, --{flag} needs a value")
+            }
+            ArgumentErrorKind::ParseFailed {
+                flag_or_position,
+                position,
+                value,
+                allowed,
+                reason,
+            } => match reason {
+                Some(reason) => write!(f, "{flag_or_position} at position {position}: {reason}"),
+                None => write!(
+                    f,
+                    "{flag_or_position} at position {position}: '{value}' cannot be parsed as type {allowed}"
+                ),
+            },
+            ArgumentErrorKind::TooManyPositionals { max, got } => write!(
+                f,
+                "There are too many positional arguments, expected {max} but got {got}"
+            ),
+            ArgumentErrorKind::NotEnoughPositionals {
+                expected,
+                got,
+                missing,
+            } => write!(
+                f,
+                "Not enough positional arguments were supplied, expected {expected} but got {got}, missing {}",
+                missing.join(", ")
+            ),
+            ArgumentErrorKind::Multiple(errors) => {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "{}", errors.join("\n"))
+            }
+            ArgumentErrorKind::Other(description) => write!(f, "{description}"),
+        }
     }
 }
 
@@ -62,9 +1619,136 @@ impl Display for ArgumentError {
 /// - A named argument wasn't listed in the flag definitions
 /// - A non-unit named argument didn't have another argument after it
 /// - A value could not be parsed into any of the types it is allowed to become
+/// - A default value is not one of the types its flag is allowed to become
+/// - An environment variable could not be parsed into any of the types its flag is allowed to become
+/// - The definitions themselves have a problem, see [`validate_definitions`]
+/// - Flags that conflict with each other were both given, or a flag was given without a flag it requires
+/// - A validator rejected a value
 pub fn gather_command_line_flags(
     positional_types: &[VariantFlag],
     flag_definitions: &[FlagDefinition],
 ) -> Result<Args, ArgumentError> {
     Args::new(positional_types, flag_definitions)
 }
+
+/// Like [`gather_command_line_flags`], but problems end your program, for small programs that have nothing better to do with them.
+///
+/// The error is written to stderr, followed by a usage line, and your program exits with status 2.
+/// When your end user asks for help, it's written to stdout and your program exits with status 0, see [`ParseOutcome::HelpRequested`].
+#[must_use]
+pub fn gather_command_line_flags_or_exit(
+    positional_types: &[VariantFlag],
+    flag_definitions: &[FlagDefinition],
+) -> Args {
+    match gather_command_line_outcome(positional_types, flag_definitions) {
+        Ok(ParseOutcome::Parsed(args)) => args,
+        Ok(ParseOutcome::HelpRequested(help)) => {
+            print!("{help}");
+            std::process::exit(0);
+        }
+        Ok(ParseOutcome::VersionRequested(version)) => {
+            println!("{version}");
+            std::process::exit(0);
+        }
+        Err(error) => {
+            let binary = std::env::args_os()
+                .next()
+                .map(|binary| binary.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            eprintln!(
+                "{}",
+                error.with_usage(&Args::usage(&binary, positional_types, flag_definitions))
+            );
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Like [`gather_command_line_flags`], but an end user asking for help gets help for your program, rendered for the binary as they ran it.
+///
+/// # Errors
+///
+/// See [`gather_command_line_flags`].
+pub fn gather_command_line_outcome(
+    positional_types: &[VariantFlag],
+    flag_definitions: &[FlagDefinition],
+) -> Result<ParseOutcome, ArgumentError> {
+    let positional_definitions: Vec<PositionalDefinition> = positional_types
+        .iter()
+        .cloned()
+        .map(PositionalDefinition::from)
+        .collect();
+    let config = ParserConfig::default();
+    let args = Args::new_with_config(&config, &positional_definitions, flag_definitions)?;
+    Ok(ParseOutcome::new(
+        args,
+        &config,
+        &positional_definitions,
+        flag_definitions,
+    ))
+}
+
+/// Checks flag definitions for mistakes, which [`Args`] and [`Parser`] do before parsing anything.
+///
+/// # Errors
+///
+/// Every problem found, one per line:
+/// - A name or abbreviation is used by more than one flag, including aliases and extra abbreviations
+/// - A name is empty, starts with `-`, or contains whitespace or `=`
+/// - An abbreviation isn't a letter or digit
+/// - A flag allows a kind of value it can never parse, see [`VariantFlag::unreachable_kinds`]
+/// - A flag's constraints refer to a flag that doesn't exist
+/// - A flag's default or optional value isn't a valid type for it
+///
+/// Positional arguments that are unit flags are rejected when parsing as well, since they can never have a value.
+///
+/// ```
+/// # use flagged_cl_args::{FlagDefinition, VariantFlag, validate_definitions};
+/// let flags = [
+///     FlagDefinition::new("force", VariantFlag::new_unit()).abbrev('f'),
+///     FlagDefinition::new("follow", VariantFlag::new_unit()).abbrev('f'),
+///     FlagDefinition::new("dry run", VariantFlag::new_unit()).abbrev('?'),
+/// ];
+/// let error = validate_definitions(&flags).unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "The abbreviation -f of --follow is already used by --force\n\
+///      \"dry run\" can't be a flag name, because it starts with - or contains whitespace or =\n\
+///      The abbreviation '?' of --dry run isn't a letter or digit"
+/// );
+/// ```
+///
+/// A default that isn't a valid type is the program's mistake, so it is found here rather than when the flag is left out:
+///
+/// ```
+/// # use flagged_cl_args::{FlagDefinition, Variant, VariantFlag, validate_definitions};
+/// let flags = [FlagDefinition::new("threads", VariantFlag::int()).default(Variant::String("four".to_string()))];
+/// assert_eq!(
+///     validate_definitions(&flags).unwrap_err().to_string(),
+///     "The default value four for --threads is not a valid type for it, which is int"
+/// );
+/// ```
+pub fn validate_definitions(flag_definitions: &[FlagDefinition]) -> Result<(), ArgumentError> {
+    let problems = args::definition_problems(flag_definitions);
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(ArgumentError::new(&problems.join("\n")))
+    }
+}
+
+/// An alias to [`crate::Args::new_with_subcommands`], using the default [`ParserConfig`].
+///
+/// # Errors
+///
+/// See [`crate::Args::new_with_subcommands`].
+pub fn gather_subcommand(
+    global_flag_definitions: &[FlagDefinition],
+    subcommands: &[Subcommand],
+) -> Result<(Args, Option<(String, Args)>), ArgumentError> {
+    Args::new_with_subcommands(
+        &ParserConfig::default(),
+        global_flag_definitions,
+        subcommands,
+    )
+}
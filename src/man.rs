@@ -0,0 +1,143 @@
+//! Generates man pages from your argument definitions.
+
+use crate::{
+    FlagDefinition, ParserConfig, PositionalDefinition,
+    args::{abbreviations, names},
+    help,
+    help::flag_help,
+};
+
+/// Generates a man page, in roff, for the program `name`.
+///
+/// The page has NAME, SYNOPSIS, ARGUMENTS (if there are positional arguments), and OPTIONS sections.
+/// Each option shows its abbreviations, names, and accepted types, followed by its help, default value, and environment variable.
+/// Text is filled by the man page viewer, so long help wraps to fit the reader's terminal.
+///
+/// ```
+/// # use flagged_cl_args::{FlagDefinition, PositionalDefinition, Variant, VariantFlag, man::generate_man};
+/// let positionals = [PositionalDefinition {
+///     allowed_type: VariantFlag::path(),
+///     help: Some("The file to read".to_string()),
+///     ..Default::default()
+/// }];
+/// let flags = [
+///     FlagDefinition::new("dry-run", VariantFlag::new_unit())
+///         .abbrev('n')
+///         .help("Show what would happen, without doing it"),
+///     FlagDefinition::new("threads", VariantFlag::int())
+///         .default(Variant::Int(4))
+///         .help(".5 threads aren't allowed"),
+/// ];
+/// assert_eq!(
+///     generate_man("my-tool", "1.0.0", "Reads files", &positionals, &flags),
+///     r#".TH MY\-TOOL 1 "" "my\-tool 1.0.0"
+/// .SH NAME
+/// my\-tool \- Reads files
+/// .SH SYNOPSIS
+/// \fBmy\-tool\fR [\fIflags\fR] \fI<path>\fR
+/// .SH ARGUMENTS
+/// .TP
+/// \fI<POS1:path>\fR
+/// The file to read
+/// .SH OPTIONS
+/// .TP
+/// \fB\-n\fR, \fB\-\-dry\-run\fR
+/// Show what would happen, without doing it
+/// .TP
+/// \fB\-\-threads\fR \fI<int>\fR
+/// \&.5 threads aren't allowed [default: 4]
+/// "#
+/// );
+/// ```
+#[must_use]
+pub fn generate_man(
+    name: &str,
+    version: &str,
+    about: &str,
+    positional: &[PositionalDefinition],
+    flags: &[FlagDefinition],
+) -> String {
+    let escaped_name = escape(name);
+    let mut page = format!(
+        ".TH {} 1 \"\" \"{escaped_name} {}\"\n.SH NAME\n{escaped_name} \\- {}\n.SH SYNOPSIS\n\\fB{escaped_name}\\fR",
+        escape(&name.to_uppercase()),
+        escape(version),
+        escape(about),
+    );
+    if !flags.is_empty() {
+        page.push_str(" [\\fIflags\\fR]");
+    }
+    for definition in positional {
+        page.push_str(&format!(
+            " \\fI{}\\fR",
+            escape(&help::decorated(definition, help::shown(definition)))
+        ));
+    }
+    page.push('\n');
+
+    if !positional.is_empty() {
+        page.push_str(".SH ARGUMENTS\n");
+        for (index, definition) in positional.iter().enumerate() {
+            let shown = help::positional_column(index, definition);
+            page.push_str(&format!(
+                ".TP\n\\fI{}\\fR\n",
+                escape(&help::decorated(definition, shown))
+            ));
+            push_text(&mut page, &help::positional_help(definition));
+        }
+    }
+
+    if !flags.is_empty() {
+        page.push_str(".SH OPTIONS\n");
+        for definition in flags {
+            let mut spellings: Vec<String> = abbreviations(definition)
+                .map(|abbreviation| format!("\\fB{}\\fR", escape(&format!("-{abbreviation}"))))
+                .collect();
+            for name in names(definition) {
+                spellings.push(format!("\\fB{}\\fR", escape(&format!("--{name}"))));
+            }
+            if definition.negatable {
+                spellings.push(format!(
+                    "\\fB{}\\fR",
+                    escape(&format!("--no-{}", definition.name))
+                ));
+            }
+            page.push_str(&format!(".TP\n{}", spellings.join(", ")));
+            let allowed_type = &definition.allowed_type;
+            if allowed_type.is_unit() || allowed_type.is_count() {
+                // Takes no value
+            } else if definition.optional_value.is_some() {
+                page.push_str(&format!(
+                    "[=\\fI<{}>\\fR]",
+                    escape(&allowed_type.to_string())
+                ));
+            } else {
+                page.push_str(
+                    &format!(" \\fI<{}>\\fR", escape(&allowed_type.to_string()))
+                        .repeat(definition.num_values),
+                );
+            }
+            page.push('\n');
+            push_text(&mut page, &flag_help(&ParserConfig::default(), definition));
+        }
+    }
+    page
+}
+
+/// Writes text as filled lines, so that nothing in it is mistaken for a roff request.
+fn push_text(page: &mut String, text: &str) {
+    for line in text.lines() {
+        let line = escape(line);
+        if line.starts_with(['.', '\'']) {
+            // A leading dot or quote would start a request
+            page.push_str("\\&");
+        }
+        page.push_str(&line);
+        page.push('\n');
+    }
+}
+
+/// Escapes backslashes and hyphens, which roff would otherwise treat as escapes and typographic hyphens.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
@@ -0,0 +1,316 @@
+use std::{ffi::OsString, sync::OnceLock};
+
+use crate::{
+    Args, ArgumentError, FlagDefinition, NoPrompt, ParseOutcome, ParserConfig,
+    PositionalDefinition, PromptSource, ReservedNames, TerminalPrompt, args,
+    args::{FlagIndex, check_definitions},
+    help,
+};
+
+/// Everything needed to parse your program's arguments, so the definitions are written once and shared by parsing, help, and tests.
+///
+/// Finish building with [`Parser::build`] to check the definitions right away, see [`crate::validate_definitions`].
+/// Otherwise, problems with them are returned by the first parse, before any arguments are looked at.
+///
+/// ```
+/// # use flagged_cl_args::{FlagDefinition, Parser, Variant, VariantFlag};
+/// let parser = Parser::new("my-tool")
+///     .positional(VariantFlag::path())
+///     .flag(FlagDefinition::new("force", VariantFlag::new_unit()).abbrev('f'));
+///
+/// let args = parser.parse_from(["my-tool", "out.txt", "-f"]).unwrap();
+/// assert_eq!(args.get_positional(0), Some(&Variant::Path("out.txt".into())));
+/// assert_eq!(args.get_named_bool("force"), Some(true));
+/// assert!(parser.help_text().starts_with("usage: my-tool <path> [-f]\n"));
+///
+/// let Err(error) = parser
+///     .flag(FlagDefinition::new("follow", VariantFlag::new_unit()).abbrev('f'))
+///     .build()
+/// else {
+///     panic!("-f is used twice");
+/// };
+/// assert_eq!(
+///     error.to_string(),
+///     "The abbreviation -f of --follow is already used by --force"
+/// );
+/// ```
+///
+/// The definitions are checked, and the tables used to find flags by name are built, by [`Parser::build`] or the first time a parser parses.
+/// Every parse after that reuses them, so keep the parser around when parsing many argument lists.
+/// It can be shared between threads, like in a static.
+///
+/// ```
+/// # use flagged_cl_args::{FlagDefinition, Parser, VariantFlag};
+/// use std::sync::OnceLock;
+///
+/// fn parser() -> &'static Parser {
+///     static PARSER: OnceLock<Parser> = OnceLock::new();
+///     PARSER.get_or_init(|| {
+///         Parser::new("my-tool")
+///             .positional(VariantFlag::path())
+///             .flag(FlagDefinition::new("threads", VariantFlag::int()).abbrev('t'))
+///     })
+/// }
+///
+/// let recorded = [vec!["my-tool", "a.txt", "-t", "2"], vec!["my-tool", "b.txt", "--threads=8"]];
+/// let threads: Vec<i32> = std::thread::scope(|scope| {
+///     let handles: Vec<_> = recorded
+///         .iter()
+///         .map(|args| scope.spawn(move || parser().parse_from(args).unwrap().get_named_int("threads")))
+///         .collect();
+///     handles.into_iter().map(|handle| handle.join().unwrap().unwrap()).collect()
+/// });
+/// assert_eq!(threads, [2, 8]);
+/// ```
+pub struct Parser {
+    name: String,
+    config: ParserConfig,
+    positional_definitions: Vec<PositionalDefinition>,
+    flag_definitions: Vec<FlagDefinition>,
+    /// Built by the first parse, or None when the definitions have a problem
+    index: OnceLock<Option<FlagIndex>>,
+}
+
+impl Parser {
+    /// Creates a parser for the program `name`, without any arguments.
+    /// The name is used by help text, the binary in parsed arguments is whatever your program was run as.
+    #[must_use]
+    pub fn new(name: &str) -> Parser {
+        Parser {
+            name: name.to_string(),
+            config: ParserConfig::default(),
+            positional_definitions: Vec::new(),
+            flag_definitions: Vec::new(),
+            index: OnceLock::new(),
+        }
+    }
+
+    /// Adds a positional argument, after the ones already added.
+    /// Takes a [`PositionalDefinition`], or just a [`crate::VariantFlag`].
+    #[must_use]
+    pub fn positional(mut self, definition: impl Into<PositionalDefinition>) -> Parser {
+        self.positional_definitions.push(definition.into());
+        self.index = OnceLock::new();
+        self
+    }
+
+    /// Adds a named argument.
+    #[must_use]
+    pub fn flag(mut self, definition: FlagDefinition) -> Parser {
+        self.flag_definitions.push(definition);
+        self.index = OnceLock::new();
+        self
+    }
+
+    /// Replaces the behavior that isn't specific to a single argument, see [`ParserConfig`].
+    #[must_use]
+    pub fn config(mut self, config: ParserConfig) -> Parser {
+        self.config = config;
+        self.index = OnceLock::new();
+        self
+    }
+
+    /// Sets [`ParserConfig::env_prefix`].
+    #[must_use]
+    pub fn env_prefix(mut self, env_prefix: &str) -> Parser {
+        self.config.env_prefix = Some(env_prefix.to_string());
+        self.index = OnceLock::new();
+        self
+    }
+
+    /// Sets [`ParserConfig::version`].
+    #[must_use]
+    pub fn version(mut self, version: &str) -> Parser {
+        self.config.version = Some(version.to_string());
+        self.index = OnceLock::new();
+        self
+    }
+
+    /// Checks the definitions, see [`crate::validate_definitions`], and prepares to parse, so mistakes in them are found before any arguments are.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{FlagDefinition, Parser, ParserConfig, ReservedNames, VariantFlag};
+    /// let parser = Parser::new("my-tool")
+    ///     .flag(FlagDefinition::new("force", VariantFlag::new_unit()).abbrev('f'))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(parser.parse_from(["my-tool", "-f"]).unwrap().get_named_bool("force"), Some(true));
+    ///
+    /// let Err(error) = Parser::new("my-tool")
+    ///     .flag(FlagDefinition::new("", VariantFlag::new_unit()))
+    ///     .flag(FlagDefinition::new("help", VariantFlag::string()))
+    ///     .config(ParserConfig {
+    ///         reserved_names: ReservedNames::Reject,
+    ///         ..Default::default()
+    ///     })
+    ///     .build()
+    /// else {
+    ///     panic!("the definitions have problems");
+    /// };
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "A flag has an empty name\n\
+    ///      --help can't use --help, because it is used for automatic help"
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Every problem with the definitions, one per line, including the ones that depend on the [`ParserConfig`].
+    pub fn build(self) -> Result<Parser, ArgumentError> {
+        check_definitions(
+            &self.config,
+            &self.positional_definitions,
+            &self.flag_definitions,
+        )?;
+        self.index
+            .get_or_init(|| Some(FlagIndex::new(&self.config, &self.flag_definitions)));
+        Ok(self)
+    }
+
+    /// The name of the program, as given to [`Parser::new`].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The positional arguments, in order.
+    pub fn positional_definitions(&self) -> &[PositionalDefinition] {
+        &self.positional_definitions
+    }
+
+    /// The named arguments, in the order they were added.
+    pub fn flag_definitions(&self) -> &[FlagDefinition] {
+        &self.flag_definitions
+    }
+
+    /// Flags of yours that use the spelling of an automatic flag, which take its place, see [`ParserConfig::reserved_names`].
+    /// Empty with [`ReservedNames::Reject`], since those are problems with the definitions instead.
+    #[must_use]
+    pub fn warnings(&self) -> Vec<String> {
+        if self.config.reserved_names == ReservedNames::Reject {
+            return Vec::new();
+        }
+        args::reserved_name_uses(&self.config, &self.flag_definitions)
+            .into_iter()
+            .map(|(definition, spelling, purpose)| {
+                format!(
+                    "--{} uses {spelling}, so it isn't used for {purpose}",
+                    definition.name
+                )
+            })
+            .collect()
+    }
+
+    /// Parses the arguments your program was run with.
+    /// Arguments don't need to be valid UTF-8, see [`Args::new_os`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Args::new`].
+    /// Problems with the definitions themselves are returned before any arguments are parsed.
+    pub fn parse(&self) -> Result<Args, ArgumentError> {
+        self.parse_from_with_prompt(std::env::args_os(), &mut TerminalPrompt)
+    }
+
+    /// Parses `args`, which starts with the binary, like the arguments your program was run with.
+    /// [`FlagDefinition::prompt`] flags are never asked for, see [`NoPrompt`], so this never waits on the terminal.
+    ///
+    /// # Errors
+    ///
+    /// See [`Parser::parse`].
+    pub fn parse_from(
+        &self,
+        args: impl IntoIterator<Item = impl Into<OsString>>,
+    ) -> Result<Args, ArgumentError> {
+        self.parse_from_with_prompt(args, &mut NoPrompt)
+    }
+
+    /// Parses `args` like [`Parser::parse_from`], asking `prompt` for the values of [`FlagDefinition::prompt`] flags instead of the terminal.
+    ///
+    /// # Errors
+    ///
+    /// See [`Parser::parse`].
+    pub fn parse_from_with_prompt(
+        &self,
+        args: impl IntoIterator<Item = impl Into<OsString>>,
+        prompt: &mut dyn PromptSource,
+    ) -> Result<Args, ArgumentError> {
+        let index = self.index.get_or_init(|| {
+            check_definitions(
+                &self.config,
+                &self.positional_definitions,
+                &self.flag_definitions,
+            )
+            .ok()
+            .map(|()| FlagIndex::new(&self.config, &self.flag_definitions))
+        });
+        // Without an index, the definitions are checked again so the problem is reported like any other error
+        Args::from_iter_os_with_index(
+            args.into_iter().map(Into::into),
+            &self.config,
+            &self.positional_definitions,
+            &self.flag_definitions,
+            index.as_ref(),
+            |name| std::env::var_os(name),
+            prompt,
+        )
+    }
+
+    /// Parses the arguments your program was run with, telling apart a request for help or the version, see [`ParseOutcome`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Parser::parse`].
+    pub fn parse_outcome(&self) -> Result<ParseOutcome, ArgumentError> {
+        let args = self.parse_from_with_prompt(std::env::args_os(), &mut TerminalPrompt)?;
+        Ok(self.outcome(args))
+    }
+
+    /// Parses `args` like [`Parser::parse_from`], telling apart a request for help or the version, see [`ParseOutcome`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Parser::parse`].
+    pub fn parse_outcome_from(
+        &self,
+        args: impl IntoIterator<Item = impl Into<OsString>>,
+    ) -> Result<ParseOutcome, ArgumentError> {
+        let args = self.parse_from(args)?;
+        Ok(self.outcome(args))
+    }
+
+    fn outcome(&self, args: Args) -> ParseOutcome {
+        ParseOutcome::new(
+            args,
+            &self.config,
+            &self.positional_definitions,
+            &self.flag_definitions,
+        )
+    }
+
+    /// Help for your end user, see [`Args::help_text`].
+    #[must_use]
+    pub fn help_text(&self) -> String {
+        help::help_text(
+            &self.name,
+            &self.config,
+            &self.positional_definitions,
+            &self.flag_definitions,
+        )
+    }
+
+    /// A one line summary of how to run your program, see [`Args::usage`].
+    #[must_use]
+    pub fn usage(&self) -> String {
+        help::usage(
+            &self.name,
+            &self.positional_definitions,
+            &self.flag_definitions,
+        )
+    }
+
+    /// Prints [`Parser::help_text`] to stdout.
+    pub fn print_help(&self) {
+        print!("{}", self.help_text());
+    }
+}
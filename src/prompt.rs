@@ -0,0 +1,86 @@
+use std::io::{BufRead, IsTerminal, Write};
+
+/// Where [`crate::FlagDefinition::prompt`] asks for values that weren't given, see [`crate::Parser::parse_from_with_prompt`].
+///
+/// Implement this to answer prompts from somewhere other than the terminal, like a test.
+pub trait PromptSource {
+    /// Whether anyone is there to answer.
+    /// When nobody is, flags take their default, or are reported as missing.
+    fn is_interactive(&self) -> bool;
+
+    /// Shows `prompt` and reads a line of answer, without its line ending.
+    /// `hidden` asks for the answer not to be shown while it is typed, for [`crate::FlagDefinition::sensitive`] flags.
+    ///
+    /// # Errors
+    ///
+    /// When the answer can't be read, which is reported for the flag that asked.
+    fn read_line(&mut self, prompt: &str, hidden: bool) -> std::io::Result<String>;
+}
+
+/// Asks on the terminal, writing prompts to stderr and reading answers from stdin.
+/// This is what parsing the arguments your program was run with uses, like [`crate::Parser::parse`] or [`crate::Args::new`].
+///
+/// Hidden answers turn off echo with `stty`, which only works on Unix, so elsewhere they are shown as they are typed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct TerminalPrompt;
+
+impl PromptSource for TerminalPrompt {
+    fn is_interactive(&self) -> bool {
+        std::io::stdin().is_terminal()
+    }
+
+    fn read_line(&mut self, prompt: &str, hidden: bool) -> std::io::Result<String> {
+        let mut stderr = std::io::stderr();
+        stderr.write_all(prompt.as_bytes())?;
+        stderr.flush()?;
+        let hidden = hidden && set_echo(false);
+        let mut answer = String::new();
+        let read = std::io::stdin().lock().read_line(&mut answer);
+        if hidden {
+            set_echo(true);
+            // The newline that was typed wasn't shown either
+            stderr.write_all(b"\n")?;
+        }
+        read?;
+        if answer.ends_with('\n') {
+            answer.pop();
+            if answer.ends_with('\r') {
+                answer.pop();
+            }
+        }
+        Ok(answer)
+    }
+}
+
+/// Never asks, as if there were no terminal, so flags that would be asked for take their default or are reported as missing.
+/// This is what parsing arguments you pass in uses, like [`crate::Parser::parse_from`] or [`crate::Args::from_iter`], so tests never wait on the terminal.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct NoPrompt;
+
+impl PromptSource for NoPrompt {
+    fn is_interactive(&self) -> bool {
+        false
+    }
+
+    fn read_line(&mut self, _prompt: &str, _hidden: bool) -> std::io::Result<String> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "there is nobody to ask",
+        ))
+    }
+}
+
+/// Turns the terminal's echo on or off, returning whether that worked.
+#[cfg(unix)]
+fn set_echo(on: bool) -> bool {
+    std::process::Command::new("stty")
+        .arg(if on { "echo" } else { "-echo" })
+        .stdin(std::process::Stdio::inherit())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(not(unix))]
+fn set_echo(_on: bool) -> bool {
+    false
+}
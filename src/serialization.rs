@@ -0,0 +1,636 @@
+//! Serde support for [`Variant`] and [`crate::Args`], enabled by the `serde` feature.
+
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    iter::Enumerate,
+    net::SocketAddr,
+    path::PathBuf,
+    slice::Iter,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{
+        self, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+        value::{BorrowedStrDeserializer, MapDeserializer, SeqDeserializer},
+    },
+    forward_to_deserialize_any,
+    ser::{self, SerializeMap},
+};
+
+#[cfg(feature = "regex")]
+use crate::Regex;
+use crate::{ArgumentError, Variant, VariantFlag};
+
+/// Variants are serialized as the closest self-describing value.
+/// Numbers are numbers, lists are sequences, and pairs are maps with a single entry.
+/// Everything else is serialized as a string, in the same form as its Display implementation.
+///
+/// This loses the kind of the value, see [`crate::tagged`] for a representation that round-trips exactly.
+///
+/// ```
+/// # use flagged_cl_args::Variant;
+/// let value = Variant::List(vec![Variant::Int(5), Variant::String("five".to_string())]);
+/// assert_eq!(serde_json::to_string(&value).unwrap(), r#"[5,"five"]"#);
+/// assert_eq!(serde_json::from_str::<Variant>(r#"[5,"five"]"#).unwrap(), value);
+///
+/// let value = Variant::Pair("level".to_string(), Box::new(Variant::Long(1 << 40)));
+/// assert_eq!(serde_json::to_string(&value).unwrap(), r#"{"level":1099511627776}"#);
+/// assert_eq!(serde_json::from_str::<Variant>(r#"{"level":1099511627776}"#).unwrap(), value);
+///
+/// let value = Variant::Socket("127.0.0.1:8080".parse().unwrap());
+/// assert_eq!(serde_json::to_string(&value).unwrap(), r#""127.0.0.1:8080""#);
+/// ```
+impl Serialize for Variant {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Variant::Bool(inner) => serializer.serialize_bool(*inner),
+            Variant::Int(inner) => serializer.serialize_i32(*inner),
+            Variant::Long(inner) => serializer.serialize_i64(*inner),
+            Variant::Uint(inner) | Variant::Bytes(inner) => serializer.serialize_u64(*inner),
+            Variant::Port(inner) => serializer.serialize_u16(*inner),
+            Variant::Float(inner) => serializer.serialize_f32(*inner),
+            Variant::Double(inner) => serializer.serialize_f64(*inner),
+            Variant::String(inner) => serializer.serialize_str(inner),
+            Variant::List(inner) => serializer.collect_seq(inner),
+            Variant::Pair(key, value) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(key, value)?;
+                map.end()
+            }
+            Variant::Duration(_)
+            | Variant::DateTime(_)
+            | Variant::Socket(_)
+            | Variant::HostPort { .. }
+            | Variant::Path(_)
+            | Variant::Stdin => serializer.collect_str(self),
+            #[cfg(feature = "regex")]
+            Variant::Regex(_) => serializer.collect_str(self),
+        }
+    }
+}
+
+/// Numbers are deserialized with the same precedence as [`crate::VariantFlag::parse`].
+/// Integers become an Int if they fit, then a Long, then a Uint.
+/// Floating point numbers become a Float unless they are out of its range, then a Double.
+/// Strings always become a String, sequences a List, and maps with a single entry a Pair.
+///
+/// ```
+/// # use flagged_cl_args::Variant;
+/// assert_eq!(serde_json::from_str::<Variant>("5").unwrap(), Variant::Int(5));
+/// assert_eq!(serde_json::from_str::<Variant>("5.0").unwrap(), Variant::Float(5.0));
+/// assert_eq!(serde_json::from_str::<Variant>("1e300").unwrap(), Variant::Double(1e300));
+/// assert_eq!(serde_json::from_str::<Variant>("18446744073709551615").unwrap(), Variant::Uint(u64::MAX));
+/// assert!(serde_json::from_str::<Variant>(r#"{"a":1,"b":2}"#).is_err());
+/// ```
+impl<'de> Deserialize<'de> for Variant {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(VariantVisitor)
+    }
+}
+
+struct VariantVisitor;
+
+impl<'de> Visitor<'de> for VariantVisitor {
+    type Value = Variant;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a bool, number, string, sequence, or map with a single entry")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Variant, E> {
+        Ok(Variant::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Variant, E> {
+        Ok(match i32::try_from(v) {
+            Ok(i) => Variant::Int(i),
+            Err(_) => Variant::Long(v),
+        })
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Variant, E> {
+        match i64::try_from(v) {
+            Ok(l) => self.visit_i64(l),
+            Err(_) => Ok(Variant::Uint(v)),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Variant, E> {
+        #[allow(clippy::cast_possible_truncation)]
+        let f = v as f32;
+        if f.is_finite() || !v.is_finite() {
+            Ok(Variant::Float(f))
+        } else {
+            Ok(Variant::Double(v))
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Variant, E> {
+        Ok(Variant::String(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Variant, E> {
+        Ok(Variant::String(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Variant, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Variant::List(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Variant, A::Error> {
+        let Some((key, value)) = map.next_entry::<String, Variant>()? else {
+            return Err(de::Error::invalid_length(0, &self));
+        };
+        if map.next_key::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(2, &self));
+        }
+        Ok(Variant::Pair(key, Box::new(value)))
+    }
+}
+
+/// Serializes [`Variant`]s tagged with their kind, like `{"Int":5}`, so that they round-trip exactly.
+///
+/// Use it on fields with `#[serde(with = "flagged_cl_args::tagged")]`.
+///
+/// ```
+/// # use flagged_cl_args::Variant;
+/// #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+/// struct Recorded {
+///     #[serde(with = "flagged_cl_args::tagged")]
+///     value: Variant,
+/// }
+///
+/// let recorded = Recorded {
+///     value: Variant::List(vec![Variant::Double(0.1), Variant::Path("out".into())]),
+/// };
+/// let json = serde_json::to_string(&recorded).unwrap();
+/// assert_eq!(json, r#"{"value":{"List":[{"Double":0.1},{"Path":"out"}]}}"#);
+/// assert_eq!(serde_json::from_str::<Recorded>(&json).unwrap(), recorded);
+///
+/// for value in [
+///     Variant::Port(8080),
+///     Variant::Bytes(1024),
+///     Variant::Duration(std::time::Duration::from_millis(1500)),
+///     Variant::Socket("[::1]:22".parse().unwrap()),
+///     Variant::HostPort { host: "db.internal".to_string(), port: 5432 },
+///     Variant::Pair("key".to_string(), Box::new(Variant::Uint(7))),
+/// ] {
+///     let recorded = Recorded { value };
+///     let json = serde_json::to_string(&recorded).unwrap();
+///     assert_eq!(serde_json::from_str::<Recorded>(&json).unwrap(), recorded);
+/// }
+/// ```
+pub mod tagged {
+    use serde::{Deserializer, Serializer};
+
+    use super::Tagged;
+    use crate::Variant;
+
+    /// Serializes a [`Variant`] tagged with its kind.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the serializer does, or for paths that aren't valid UTF-8 in formats that require it.
+    pub fn serialize<S: Serializer>(value: &Variant, serializer: S) -> Result<S::Ok, S::Error> {
+        Tagged::serialize(value, serializer)
+    }
+
+    /// Deserializes a [`Variant`] tagged with its kind.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the value isn't tagged with a known kind, or its value doesn't fit that kind.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Variant, D::Error> {
+        Tagged::deserialize(deserializer)
+    }
+}
+
+/// The tagged representation, mirroring [`Variant`].
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Variant")]
+enum Tagged {
+    Bool(bool),
+    Int(i32),
+    Long(i64),
+    Uint(u64),
+    Port(u16),
+    Float(f32),
+    Double(f64),
+    Duration(Duration),
+    Bytes(u64),
+    DateTime(SystemTime),
+    Socket(SocketAddr),
+    HostPort {
+        host: String,
+        port: u16,
+    },
+    Path(PathBuf),
+    String(String),
+    List(#[serde(with = "tagged_list")] Vec<Variant>),
+    Pair(String, #[serde(with = "tagged_box")] Box<Variant>),
+    #[cfg(feature = "regex")]
+    Regex(#[serde(with = "regex_pattern")] Regex),
+    Stdin,
+}
+
+/// Serializes a nested variant, in a list or pair, with its tag.
+struct NestedRef<'a>(&'a Variant);
+
+impl Serialize for NestedRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Tagged::serialize(self.0, serializer)
+    }
+}
+
+/// Deserializes a nested variant, in a list or pair, from its tag.
+struct Nested(Variant);
+
+impl<'de> Deserialize<'de> for Nested {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Tagged::deserialize(deserializer).map(Nested)
+    }
+}
+
+mod tagged_list {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::{Nested, NestedRef};
+    use crate::Variant;
+
+    pub(super) fn serialize<S: Serializer>(
+        values: &[Variant],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(values.iter().map(NestedRef))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Variant>, D::Error> {
+        let values = Vec::<Nested>::deserialize(deserializer)?;
+        Ok(values.into_iter().map(|Nested(value)| value).collect())
+    }
+}
+
+mod tagged_box {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{Nested, NestedRef};
+    use crate::Variant;
+
+    pub(super) fn serialize<S: Serializer>(
+        value: &Variant,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        NestedRef(value).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Box<Variant>, D::Error> {
+        let Nested(value) = Nested::deserialize(deserializer)?;
+        Ok(Box::new(value))
+    }
+}
+
+#[cfg(feature = "regex")]
+mod regex_pattern {
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    use crate::Regex;
+
+    pub(super) fn serialize<S: Serializer>(
+        value: &Regex,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(value.0.as_str())
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Regex, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        regex::Regex::new(&pattern)
+            .map(Regex)
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Variant flags are serialized as a string in the same form as their Display implementation, like `int|float|string`, see [`VariantFlag`]'s [`FromStr`](std::str::FromStr) implementation.
+/// Flags that can't be read back from that form, like those with choices or a custom parser, fail to serialize.
+///
+/// ```
+/// # use flagged_cl_args::VariantFlag;
+/// let flag = VariantFlag::int().or_socket();
+/// assert_eq!(serde_json::to_string(&flag).unwrap(), r#""int|socket""#);
+/// assert_eq!(serde_json::from_str::<VariantFlag>(r#""INT|Socket""#).unwrap(), flag);
+///
+/// assert!(serde_json::to_string(&VariantFlag::choice(&["json", "yaml"])).is_err());
+/// assert!(serde_json::from_str::<VariantFlag>(r#""integer""#).is_err());
+/// ```
+impl Serialize for VariantFlag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = self.to_string();
+        if text.parse().as_ref() != Ok(self) {
+            return Err(ser::Error::custom(format!(
+                "the type {text} can't be written as text"
+            )));
+        }
+        serializer.serialize_str(&text)
+    }
+}
+
+impl<'de> Deserialize<'de> for VariantFlag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(de::Error::custom)
+    }
+}
+
+/// The struct field that receives positional arguments, see [`crate::Args::deserialize`].
+const POSITIONAL_FIELD: &str = "_positional";
+
+impl de::Error for ArgumentError {
+    fn custom<T: Display>(msg: T) -> Self {
+        ArgumentError::new(&msg.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        if field == POSITIONAL_FIELD {
+            ArgumentError::new("missing positional arguments")
+        } else {
+            ArgumentError::new(&format!("--{} is required", field.replace('_', "-")))
+        }
+    }
+}
+
+/// Deserializes structs and maps from named arguments, and sequences from positional arguments.
+pub(crate) struct ArgsDeserializer<'a> {
+    positional: &'a [Variant],
+    named: &'a HashMap<Arc<str>, Variant>,
+}
+
+impl<'a> ArgsDeserializer<'a> {
+    pub(crate) fn new(
+        positional: &'a [Variant],
+        named: &'a HashMap<Arc<str>, Variant>,
+    ) -> ArgsDeserializer<'a> {
+        ArgsDeserializer { positional, named }
+    }
+
+    /// Finds the value for a struct field, trying its name as is and then with dashes instead of underscores.
+    fn field(&self, field: &'static str) -> Option<(&'a str, Entry<'a>)> {
+        if field == POSITIONAL_FIELD {
+            return Some((field, Entry::Positional(self.positional)));
+        }
+        self.named
+            .get_key_value(field)
+            .or_else(|| self.named.get_key_value(field.replace('_', "-").as_str()))
+            .map(|(name, value)| (field, Entry::Named(name, value)))
+    }
+}
+
+impl<'de> Deserializer<'de> for ArgsDeserializer<'de> {
+    type Error = ArgumentError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ArgumentError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ArgumentError> {
+        let entries = self
+            .named
+            .iter()
+            .map(|(name, value)| (&**name, Entry::Named(name, value)));
+        visitor.visit_map(ArgsAccess {
+            entries,
+            next: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ArgumentError> {
+        let entries = fields.iter().filter_map(|field| self.field(field));
+        visitor.visit_map(ArgsAccess {
+            entries,
+            next: None,
+        })
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ArgumentError> {
+        PositionalDeserializer(self.positional).deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ArgumentError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ArgumentError> {
+        self.deserialize_seq(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct enum identifier ignored_any
+    }
+}
+
+/// A value found for a struct field or map key.
+enum Entry<'a> {
+    Positional(&'a [Variant]),
+    Named(&'a str, &'a Variant),
+}
+
+struct ArgsAccess<'a, I> {
+    entries: I,
+    next: Option<Entry<'a>>,
+}
+
+impl<'de, I: Iterator<Item = (&'de str, Entry<'de>)>> MapAccess<'de> for ArgsAccess<'de, I> {
+    type Error = ArgumentError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ArgumentError> {
+        let Some((key, entry)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.next = Some(entry);
+        seed.deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, ArgumentError> {
+        match self.next.take() {
+            Some(Entry::Positional(values)) => seed.deserialize(PositionalDeserializer(values)),
+            Some(Entry::Named(name, value)) => seed
+                .deserialize(VariantDeserializer(value))
+                .map_err(|error| ArgumentError::new(&format!("--{name}: {error}"))),
+            None => Err(de::Error::custom("value requested before its key")),
+        }
+    }
+}
+
+/// Deserializes positional arguments as a sequence.
+struct PositionalDeserializer<'a>(&'a [Variant]);
+
+impl<'de> Deserializer<'de> for PositionalDeserializer<'de> {
+    type Error = ArgumentError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ArgumentError> {
+        let mut access = PositionalAccess(self.0.iter().enumerate());
+        let value = visitor.visit_seq(&mut access)?;
+        match access.0.next() {
+            Some((index, _)) => Err(ArgumentError::new(&format!(
+                "expected {index} positional arguments, got {}",
+                self.0.len()
+            ))),
+            None => Ok(value),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct PositionalAccess<'a>(Enumerate<Iter<'a, Variant>>);
+
+impl<'de> SeqAccess<'de> for PositionalAccess<'de> {
+    type Error = ArgumentError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, ArgumentError> {
+        let Some((index, value)) = self.0.next() else {
+            return Ok(None);
+        };
+        seed.deserialize(VariantDeserializer(value))
+            .map(Some)
+            .map_err(|error| {
+                ArgumentError::new(&format!("positional argument {}: {error}", index + 1))
+            })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+/// Deserializes a single value.
+/// Durations and dates and times are deserialized like [`Duration`] and [`SystemTime`] are serialized.
+/// Strings can be deserialized as enums with unit variants, which suits flags with choices.
+struct VariantDeserializer<'a>(&'a Variant);
+
+impl<'de> IntoDeserializer<'de, ArgumentError> for VariantDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for VariantDeserializer<'de> {
+    type Error = ArgumentError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ArgumentError> {
+        match self.0 {
+            Variant::Bool(inner) => visitor.visit_bool(*inner),
+            Variant::Int(inner) => visitor.visit_i32(*inner),
+            Variant::Long(inner) => visitor.visit_i64(*inner),
+            Variant::Uint(inner) | Variant::Bytes(inner) => visitor.visit_u64(*inner),
+            Variant::Port(inner) => visitor.visit_u16(*inner),
+            Variant::Float(inner) => visitor.visit_f32(*inner),
+            Variant::Double(inner) => visitor.visit_f64(*inner),
+            Variant::Duration(inner) => visit_duration(*inner, visitor),
+            Variant::DateTime(inner) => match inner.duration_since(UNIX_EPOCH) {
+                Ok(since_epoch) => visit_duration(since_epoch, visitor),
+                Err(_) => visitor.visit_string(self.0.to_string()),
+            },
+            Variant::Path(inner) => match inner.to_str() {
+                Some(path) => visitor.visit_borrowed_str(path),
+                None => visitor.visit_string(self.0.to_string()),
+            },
+            Variant::String(inner) => visitor.visit_borrowed_str(inner),
+            Variant::List(inner) => {
+                SeqDeserializer::new(inner.iter().map(VariantDeserializer)).deserialize_any(visitor)
+            }
+            Variant::Pair(key, value) => {
+                MapDeserializer::new(std::iter::once((key.as_str(), VariantDeserializer(value))))
+                    .deserialize_any(visitor)
+            }
+            Variant::Socket(_) | Variant::HostPort { .. } => {
+                visitor.visit_string(self.0.to_string())
+            }
+            #[cfg(feature = "regex")]
+            Variant::Regex(inner) => visitor.visit_borrowed_str(inner.0.as_str()),
+            Variant::Stdin => visitor.visit_borrowed_str("-"),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ArgumentError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ArgumentError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ArgumentError> {
+        match self.0 {
+            Variant::String(inner) => visitor.visit_enum(BorrowedStrDeserializer::new(inner)),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Visits a duration as seconds and nanoseconds, the way [`Duration`] is serialized.
+fn visit_duration<'de, V: Visitor<'de>>(
+    duration: Duration,
+    visitor: V,
+) -> Result<V::Value, ArgumentError> {
+    let parts = [duration.as_secs(), u64::from(duration.subsec_nanos())];
+    SeqDeserializer::new(parts.into_iter()).deserialize_any(visitor)
+}
@@ -1,14 +1,140 @@
 use std::{
+    borrow::Cow,
+    error::Error,
+    ffi::OsStr,
     fmt::Display,
     net::{SocketAddr, ToSocketAddrs},
-    path::PathBuf,
+    num::ParseIntError,
+    ops::{BitOr, BitOrAssign, Bound, RangeBounds},
+    path::{Path, PathBuf},
     str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+use crate::ArgumentError;
+
 /// Variant flag determines what types an argument is allowed to become!
 /// If the argument can become a string, parsing it will never fail, but it will only become a string if it can't become any of the other types it is allowed to.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct VariantFlag {
+    bits: u32,
+    choices: Vec<String>,
+    ignore_case: bool,
+    element: Option<Box<VariantFlag>>,
+    delimiter: char,
+    decimal_only: bool,
+    lenient_numbers: bool,
+    finite: bool,
+    stdin_dash: bool,
+    custom: Option<CustomParser>,
+    path_check: PathCheck,
+    int_range: (Bound<i64>, Bound<i64>),
+    /// Stored as bits, so the flag can still be compared and hashed
+    float_range: (Bound<u64>, Bound<u64>),
+}
+
+/// Filesystem checks applied to paths, see [`VariantFlag::must_exist`].
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-pub struct VariantFlag(u8);
+enum PathCheck {
+    None,
+    Exists,
+    File,
+    Dir,
+}
+
+type ParseFn = dyn Fn(&str) -> Result<Variant, String> + Send + Sync;
+
+/// A user supplied parser, see [`VariantFlag::custom`].
+#[derive(Clone)]
+struct CustomParser {
+    name: String,
+    parse: Arc<ParseFn>,
+}
+
+impl PartialEq for CustomParser {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && Arc::ptr_eq(&self.parse, &other.parse)
+    }
+}
+
+impl Eq for CustomParser {}
+
+impl std::hash::Hash for CustomParser {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
+
+impl std::fmt::Debug for CustomParser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomParser")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for VariantFlag {
+    fn default() -> Self {
+        VariantFlag::new_unit()
+    }
+}
+
+/// Allows the types of both flags, like chaining the `or_` methods.
+/// Choices are combined, and a custom parser on the right replaces one on the left, like [`VariantFlag::or_custom`].
+/// Settings like [`VariantFlag::int_range`] come from the left, unless only the right changed them.
+///
+/// ```
+/// # use flagged_cl_args::{Variant, VariantFlag};
+/// let flag = VariantFlag::string() | VariantFlag::float() | VariantFlag::int();
+/// assert_eq!(flag, VariantFlag::int().or_float().or_string());
+/// // Displayed in the order the types are tried, not the order they were combined
+/// assert_eq!(flag.to_string(), "int|float|string");
+///
+/// let mut flag = VariantFlag::choice(&["auto"]);
+/// flag |= VariantFlag::int_in(1..=64);
+/// assert_eq!(flag.to_string(), "int|auto");
+/// assert_eq!(flag.parse("auto"), Some(Variant::String("auto".to_string())));
+/// assert_eq!(flag.parse("128"), None);
+/// ```
+impl BitOr for VariantFlag {
+    type Output = VariantFlag;
+
+    fn bitor(mut self, other: VariantFlag) -> VariantFlag {
+        self.bits |= other.bits;
+        for choice in other.choices {
+            if !self.choices.contains(&choice) {
+                self.choices.push(choice);
+            }
+        }
+        self.ignore_case |= other.ignore_case;
+        self.element = self.element.or(other.element);
+        if self.delimiter == ',' {
+            self.delimiter = other.delimiter;
+        }
+        self.decimal_only |= other.decimal_only;
+        self.lenient_numbers |= other.lenient_numbers;
+        self.finite |= other.finite;
+        self.stdin_dash |= other.stdin_dash;
+        self.custom = other.custom.or(self.custom);
+        if self.path_check == PathCheck::None {
+            self.path_check = other.path_check;
+        }
+        if self.int_range == (Bound::Unbounded, Bound::Unbounded) {
+            self.int_range = other.int_range;
+        }
+        if self.float_range == (Bound::Unbounded, Bound::Unbounded) {
+            self.float_range = other.float_range;
+        }
+        self
+    }
+}
+
+impl BitOrAssign for VariantFlag {
+    fn bitor_assign(&mut self, other: VariantFlag) {
+        *self = std::mem::take(self) | other;
+    }
+}
 
 impl VariantFlag {
     const BOOL_BIT: u8 = 0;
@@ -17,6 +143,45 @@ impl VariantFlag {
     const SOCKET_BIT: u8 = 3;
     const PATH_BIT: u8 = 4;
     const STRING_BIT: u8 = 5;
+    const LONG_BIT: u8 = 6;
+    const UINT_BIT: u8 = 7;
+    const DOUBLE_BIT: u8 = 8;
+    const DURATION_BIT: u8 = 9;
+    const SOCKET_RESOLVE_BIT: u8 = 10;
+    const HOST_PORT_BIT: u8 = 11;
+    const CHOICE_BIT: u8 = 12;
+    const LIST_BIT: u8 = 13;
+    const PAIR_BIT: u8 = 14;
+    const BYTES_BIT: u8 = 15;
+    const DATETIME_BIT: u8 = 16;
+    const PORT_BIT: u8 = 17;
+    #[cfg(feature = "regex")]
+    const REGEX_BIT: u8 = 18;
+    const COUNT_BIT: u8 = 19;
+    const BOOL_LENIENT_BIT: u8 = 20;
+
+    fn from_bits(bits: u32) -> VariantFlag {
+        VariantFlag {
+            bits,
+            choices: Vec::new(),
+            ignore_case: false,
+            element: None,
+            delimiter: ',',
+            decimal_only: false,
+            lenient_numbers: false,
+            finite: false,
+            stdin_dash: false,
+            custom: None,
+            path_check: PathCheck::None,
+            int_range: (Bound::Unbounded, Bound::Unbounded),
+            float_range: (Bound::Unbounded, Bound::Unbounded),
+        }
+    }
+
+    fn with_bits(mut self, bits: u32) -> VariantFlag {
+        self.bits |= bits;
+        self
+    }
 
     // #[must_use]
     // pub fn new(
@@ -48,327 +213,2817 @@ impl VariantFlag {
 
     /// The argument doesn't have a value. It is either present or it is not.
     /// If present, it will have a value of Variant::Bool(true).
+    ///
+    /// It can still be explicitly set with `=`, like `--force=false`, for scripts that always give the flag.
+    /// A separate `true` or `false` is never taken as its value, so it stays a positional argument.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+    /// let flags = [FlagDefinition::new("force", VariantFlag::new_unit()).abbrev('f')];
+    /// let args = Args::from_slice(&["my-tool", "--force=false"], &[], &flags).unwrap();
+    /// assert_eq!(args.get_named_bool("force"), Some(false));
+    /// let args = Args::from_slice(&["my-tool", "-f=true"], &[], &flags).unwrap();
+    /// assert_eq!(args.get_named_bool("force"), Some(true));
+    ///
+    /// let args = Args::from_slice(&["my-tool", "--force", "false"], &[VariantFlag::bool()], &flags).unwrap();
+    /// assert_eq!(args.get_named_bool("force"), Some(true));
+    /// assert_eq!(args.get_positional(0), Some(&Variant::Bool(false)));
+    ///
+    /// let Err(error) = Args::from_slice(&["my-tool", "--force=banana"], &[], &flags) else {
+    ///     panic!("banana isn't a boolean");
+    /// };
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "--force at position 1 does not take a value other than true or false, like --force=false"
+    /// );
+    /// // Only with lenient_bools
+    /// assert!(Args::from_slice(&["my-tool", "--force=no"], &[], &flags).is_err());
+    /// ```
     #[must_use]
     pub fn new_unit() -> VariantFlag {
-        VariantFlag(0)
+        VariantFlag::from_bits(0)
+    }
+
+    /// Like [`VariantFlag::new_unit`], the argument doesn't have a value, but it counts how many times it was given.
+    /// If present, it will have a value of Variant::Int(n), so `-v -v -v`, `-vvv`, and `--verbose --verbose --verbose` all store Variant::Int(3).
+    /// If absent, it will not be present in the map at all.
+    #[must_use]
+    pub fn count() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::COUNT_BIT)
     }
 
     /// An argument parsed with the resulting VariantFlag will only become a [`bool`].
-    /// Booleans are parsed exclusively from 'true' and 'false'.
+    /// Booleans are parsed exclusively from 'true' and 'false', see [`VariantFlag::bool_lenient`] for other spellings.
     #[must_use]
     pub fn bool() -> VariantFlag {
-        VariantFlag(1 << VariantFlag::BOOL_BIT)
+        VariantFlag::from_bits(1 << VariantFlag::BOOL_BIT)
     }
 
     /// Adds [`bool`] to the list of types an argument can support.
     /// Supports method chaining.
     #[must_use]
     pub fn or_bool(self) -> VariantFlag {
-        VariantFlag(1 << VariantFlag::BOOL_BIT | self.0)
+        self.with_bits(1 << VariantFlag::BOOL_BIT)
     }
 
-    /// An argument parsed with the resulting VariantFlag will only become an [`i32`].
+    /// Like [`VariantFlag::bool`], but `yes`/`no`, `on`/`off`, and `1`/`0` are accepted too, ignoring case.
+    ///
+    /// Booleans are tried before every other type, so a lenient bool that also allows ints parses `1` and `0` as bools, and only other numbers as ints.
+    /// A strict bool leaves `1` and `0` for the int.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Variant, VariantFlag};
+    /// let color = VariantFlag::bool_lenient();
+    /// assert_eq!(color.parse("on"), Some(Variant::Bool(true)));
+    /// assert_eq!(color.parse("FALSE"), Some(Variant::Bool(false)));
+    /// assert_eq!(color.parse("maybe"), None);
+    ///
+    /// let lenient = VariantFlag::bool_lenient().or_int();
+    /// assert_eq!(lenient.parse("1"), Some(Variant::Bool(true)));
+    /// assert_eq!(lenient.parse("2"), Some(Variant::Int(2)));
+    ///
+    /// let strict = VariantFlag::bool().or_int();
+    /// assert_eq!(strict.parse("1"), Some(Variant::Int(1)));
+    /// assert_eq!(strict.parse("yes"), None);
+    /// ```
     #[must_use]
-    pub fn int() -> VariantFlag {
-        VariantFlag(1 << VariantFlag::INT_BIT)
+    pub fn bool_lenient() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::BOOL_BIT | 1 << VariantFlag::BOOL_LENIENT_BIT)
     }
 
-    /// Adds [`i32`] to the list of types an argument can support.
+    /// Adds a [`bool`] that accepts `yes`/`no`, `on`/`off`, and `1`/`0` to the list of types an argument can support, see [`VariantFlag::bool_lenient`].
     /// Supports method chaining.
     #[must_use]
-    pub fn or_int(self) -> VariantFlag {
-        VariantFlag(1 << VariantFlag::INT_BIT | self.0)
+    pub fn or_bool_lenient(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::BOOL_BIT | 1 << VariantFlag::BOOL_LENIENT_BIT)
     }
 
-    /// An argument parsed with the resulting VariantFlag will only become an [`f32`].
+    /// Accepts the spellings of [`VariantFlag::bool_lenient`] wherever a boolean is read, including values explicitly given to unit flags, like `--force=off`.
+    /// Supports method chaining.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, VariantFlag};
+    /// let flags = [FlagDefinition::new("force", VariantFlag::new_unit().lenient_bools())];
+    /// let args = Args::from_slice(&["my-tool", "--force=off"], &[], &flags).unwrap();
+    /// assert_eq!(args.get_named_bool("force"), Some(false));
+    /// let args = Args::from_slice(&["my-tool", "--force=YES"], &[], &flags).unwrap();
+    /// assert_eq!(args.get_named_bool("force"), Some(true));
+    /// ```
     #[must_use]
-    pub fn float() -> VariantFlag {
-        VariantFlag(1 << VariantFlag::FLOAT_BIT)
+    pub fn lenient_bools(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::BOOL_LENIENT_BIT)
     }
 
-    /// An argument parsed with the resulting VariantFlag will only become an [`std::net::SocketAddr`].
+    /// An argument parsed with the resulting VariantFlag will only become an [`i32`].
+    /// Integers may be written in hexadecimal, octal, or binary using `0x`, `0o`, or `0b` prefixes, see [`VariantFlag::decimal_only`].
     #[must_use]
-    pub fn socket() -> VariantFlag {
-        VariantFlag(1 << VariantFlag::SOCKET_BIT)
+    pub fn int() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::INT_BIT)
     }
 
-    /// Adds [`std::net::SocketAddr`] to the list of types an argument can support.
+    /// Adds [`i32`] to the list of types an argument can support.
     /// Supports method chaining.
     #[must_use]
-    pub fn or_socket(self) -> VariantFlag {
-        VariantFlag(1 << VariantFlag::SOCKET_BIT | self.0)
+    pub fn or_int(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::INT_BIT)
     }
 
-    /// An argument parsed with the resulting VariantFlag will only become an [`std::path::PathBuf`].
-    /// This conversion will never fail, but that doesn't mean the path points to anything meaningful.
+    /// An argument parsed with the resulting VariantFlag will only become an [`i64`].
     #[must_use]
-    pub fn path() -> VariantFlag {
-        VariantFlag(1 << VariantFlag::PATH_BIT)
+    pub fn long() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::LONG_BIT)
     }
 
-    /// Adds [`std::path::Path`] to the list of types an argument can support.
+    /// Adds [`i64`] to the list of types an argument can support.
     /// Supports method chaining.
     #[must_use]
-    pub fn or_path(self) -> VariantFlag {
-        VariantFlag(1 << VariantFlag::PATH_BIT | self.0)
+    pub fn or_long(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::LONG_BIT)
     }
 
-    /// An argument parsed with the resulting VariantFlag will be passed directly as a [`String`].
-    /// This conversion will never fail.
+    /// An argument parsed with the resulting VariantFlag will only become a [`u64`].
+    /// Negative values are rejected with an error saying so.
     #[must_use]
-    pub fn string() -> VariantFlag {
-        VariantFlag(1 << VariantFlag::STRING_BIT)
+    pub fn uint() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::UINT_BIT)
     }
 
-    /// Adds [`String`] to the list of types an argument can support.
+    /// Adds [`u64`] to the list of types an argument can support.
     /// Supports method chaining.
     #[must_use]
-    pub fn or_string(self) -> VariantFlag {
-        VariantFlag(1 << VariantFlag::STRING_BIT | self.0)
+    pub fn or_uint(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::UINT_BIT)
     }
 
+    /// An argument parsed with the resulting VariantFlag will only become a port number stored as a [`u16`].
+    /// Values outside of 0-65535 are rejected with an error saying so.
     #[must_use]
-    fn check_bit(&self, bit: u8) -> bool {
-        self.0 >> bit & 1 != 0
+    pub fn port() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::PORT_BIT)
     }
 
+    /// Adds port numbers to the list of types an argument can support.
+    /// Supports method chaining.
     #[must_use]
-    pub(crate) fn bool_allowed(&self) -> bool {
-        self.check_bit(VariantFlag::BOOL_BIT)
+    pub fn or_port(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::PORT_BIT)
     }
 
+    /// An argument parsed with the resulting VariantFlag will be handed to `parse`, for types this crate doesn't support.
+    /// `name` is used when displaying the flag, such as in error messages.
+    /// An error returned by `parse` is passed along to the end user.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Variant, VariantFlag};
+    /// let even = VariantFlag::custom("even", |raw| match raw.parse::<i32>() {
+    ///     Ok(i) if i % 2 == 0 => Ok(Variant::Int(i)),
+    ///     _ => Err(format!("{raw} is not an even number")),
+    /// });
+    /// assert_eq!(even.parse("4"), Some(Variant::Int(4)));
+    /// assert_eq!(even.parse("5"), None);
+    /// ```
     #[must_use]
-    pub(crate) fn int_allowed(&self) -> bool {
-        self.check_bit(VariantFlag::INT_BIT)
+    pub fn custom(
+        name: &str,
+        parse: impl Fn(&str) -> Result<Variant, String> + Send + Sync + 'static,
+    ) -> VariantFlag {
+        VariantFlag::from_bits(0).or_custom(name, parse)
     }
 
+    /// Adds a custom parser to the list of types an argument can support.
+    /// The custom parser is tried before any other type, and replaces any custom parser added previously.
+    /// Supports method chaining.
     #[must_use]
-    pub(crate) fn float_allowed(&self) -> bool {
-        self.check_bit(VariantFlag::FLOAT_BIT)
+    pub fn or_custom(
+        mut self,
+        name: &str,
+        parse: impl Fn(&str) -> Result<Variant, String> + Send + Sync + 'static,
+    ) -> VariantFlag {
+        self.custom = Some(CustomParser {
+            name: name.to_string(),
+            parse: Arc::new(parse),
+        });
+        self
     }
 
+    /// Disables `0x`, `0o`, and `0b` prefixes for integer types, so values like `0x1` are left for other types to parse.
+    /// Supports method chaining.
     #[must_use]
-    pub(crate) fn socket_allowed(&self) -> bool {
-        self.check_bit(VariantFlag::SOCKET_BIT)
+    pub fn decimal_only(mut self) -> VariantFlag {
+        self.decimal_only = true;
+        self
     }
 
+    /// Ignores ASCII whitespace around numbers, and `_` between digits like `1_000_000`, for integer and floating point types.
+    /// A `_` that isn't between two digits, like in `_100` or `1__000`, is still rejected.
+    /// Supports method chaining.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Variant, VariantFlag};
+    /// let limit = VariantFlag::int().lenient_numbers();
+    /// assert_eq!(limit.parse("1_000"), Some(Variant::Int(1000)));
+    /// assert_eq!(limit.parse(" 42\n"), Some(Variant::Int(42)));
+    /// assert_eq!(limit.parse("0xff_ff"), Some(Variant::Int(0xffff)));
+    /// assert_eq!(limit.parse("1__000"), None);
+    /// assert_eq!(limit.parse("_100"), None);
+    /// assert_eq!(limit.parse("100_"), None);
+    /// assert_eq!(VariantFlag::int().parse("1_000"), None);
+    ///
+    /// let ratio = VariantFlag::float().lenient_numbers();
+    /// assert_eq!(ratio.parse(" 3.5 "), Some(Variant::Float(3.5)));
+    /// assert_eq!(ratio.parse("1_000.000_1"), Some(Variant::Float(1000.0001)));
+    /// assert_eq!(ratio.parse("1_.5"), None);
+    /// assert_eq!(VariantFlag::float().parse(" 3.5 "), None);
+    /// ```
     #[must_use]
-    pub(crate) fn path_allowed(&self) -> bool {
-        self.check_bit(VariantFlag::PATH_BIT)
+    pub fn lenient_numbers(mut self) -> VariantFlag {
+        self.lenient_numbers = true;
+        self
     }
 
+    /// Parses a value of exactly `-` as [`Variant::Stdin`], following the convention that `-` in place of a file means standard input (or output).
+    /// Meant for path and string types, and works for positional arguments as well as flags.
+    /// Supports method chaining.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+    /// let input = VariantFlag::path().stdin_dash();
+    /// assert_eq!(input.parse("-"), Some(Variant::Stdin));
+    /// assert_eq!(input.parse("--"), Some(Variant::Path("--".into())));
+    /// assert_eq!(VariantFlag::path().parse("-"), Some(Variant::Path("-".into())));
+    ///
+    /// let flags = [
+    ///     FlagDefinition::new("output", VariantFlag::path().stdin_dash()).abbrev('o'),
+    ///     FlagDefinition::new("verbose", VariantFlag::new_unit()).abbrev('v'),
+    /// ];
+    /// // A lone - is never an abbreviation, so it's left for the positional argument
+    /// let args = Args::from_slice(&["my-tool", "-v", "-", "-o", "-"], &[input.clone()], &flags).unwrap();
+    /// assert_eq!(args.get_positional(0), Some(&Variant::Stdin));
+    /// assert_eq!(args.get_named("output"), Some(&Variant::Stdin));
+    /// assert_eq!(args.get_named_bool("verbose"), Some(true));
+    ///
+    /// let args = Args::from_slice(&["my-tool", "--output=-", "in.txt"], &[input], &flags).unwrap();
+    /// match args.get_positional(0) {
+    ///     Some(Variant::Stdin) => panic!("in.txt is a file"),
+    ///     Some(Variant::Path(path)) => assert_eq!(path.to_str(), Some("in.txt")),
+    ///     _ => unreachable!(),
+    /// }
+    /// assert_eq!(args.get_named("output"), Some(&Variant::Stdin));
+    /// ```
     #[must_use]
-    pub(crate) fn string_allowed(&self) -> bool {
-        self.check_bit(VariantFlag::STRING_BIT)
+    pub fn stdin_dash(mut self) -> VariantFlag {
+        self.stdin_dash = true;
+        self
+    }
+
+    /// Trims whitespace and removes `_` between digits, for [`VariantFlag::lenient_numbers`].
+    /// Misplaced separators are left in, so the number fails to parse.
+    fn number_text<'a>(&self, raw: &'a str) -> Cow<'a, str> {
+        if !self.lenient_numbers {
+            return Cow::Borrowed(raw);
+        }
+        let trimmed = raw.trim_ascii();
+        let unsigned = trimmed.trim_start_matches(['-', '+']).as_bytes();
+        let is_digit: fn(&u8) -> bool = match unsigned.get(..2) {
+            Some(b"0x" | b"0X") => u8::is_ascii_hexdigit,
+            _ => u8::is_ascii_digit,
+        };
+        let bytes = trimmed.as_bytes();
+        let separated = bytes.iter().enumerate().all(|(index, byte)| {
+            *byte != b'_'
+                || (index > 0
+                    && is_digit(&bytes[index - 1])
+                    && bytes.get(index + 1).is_some_and(is_digit))
+        });
+        if separated && trimmed.contains('_') {
+            Cow::Owned(trimmed.replace('_', ""))
+        } else {
+            Cow::Borrowed(trimmed)
+        }
     }
 
+    /// Like [`VariantFlag::int`], but the value must be within `range`, like `1..=256`.
+    /// Ranges may be open-ended, like `1..` or `..=10`.
     #[must_use]
-    pub(crate) fn is_unit(&self) -> bool {
-        self.0 == 0
+    pub fn int_in(range: impl RangeBounds<i64>) -> VariantFlag {
+        VariantFlag::int().int_range(range)
     }
 
-    /// Parse a string into one of the types this VariantFlag supports.
-    /// The precedence is bool, i32, f32, SocketAddr, PathBuf, and lastly String
+    /// Limits integer types (int, long, uint, and port) to values within `range`.
+    /// Values outside of the range are rejected with an error saying so, unless another allowed type accepts them.
+    /// Supports method chaining.
     #[must_use]
-    pub fn parse(&self, raw: &str) -> Option<Variant> {
-        if self.bool_allowed()
-            && let Ok(b) = bool::from_str(raw)
-        {
-            Some(Variant::Bool(b))
-        } else if self.int_allowed()
-            && let Ok(i) = i32::from_str(raw)
-        {
-            Some(Variant::Int(i))
-        } else if self.float_allowed()
-            && let Ok(f) = f32::from_str(raw)
-        {
-            Some(Variant::Float(f))
-        } else if self.socket_allowed()
-            && let Ok(mut sockets) = raw.to_socket_addrs()
-            && let Some(s) = sockets.next()
-        {
-            Some(Variant::Socket(s))
-        } else if self.path_allowed() {
-            Some(Variant::Path(PathBuf::from(raw)))
-        } else if self.string_allowed() {
-            Some(Variant::String(raw.to_string()))
-        } else {
-            None
-        }
+    pub fn int_range(mut self, range: impl RangeBounds<i64>) -> VariantFlag {
+        self.int_range = (range.start_bound().cloned(), range.end_bound().cloned());
+        self
     }
-}
 
-/// A value of a particular type.
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
-pub enum Variant {
-    /// Booleans are represented as [`bool`]
-    Bool(bool),
-    /// Integers are represented as [`i32`]
-    Int(i32),
-    /// Floats are represented as [`f32`]
-    Float(f32),
-    /// Sockets are represented as [`std::net::SocketAddr`]
-    Socket(SocketAddr),
-    /// Paths are represented as [`std::path::PathBuf`]
-    Path(PathBuf),
-    /// Strings are represented as [`String`]
-    String(String),
-}
+    /// Like [`VariantFlag::float`], but the value must be within `range`, like `0.0..=1.0`.
+    /// Ranges may be open-ended, like `0.0..` or `..=1.0`.
+    #[must_use]
+    pub fn float_in(range: impl RangeBounds<f64>) -> VariantFlag {
+        VariantFlag::float().float_range(range)
+    }
 
-impl Variant {
-    /// Orders values according to their precedence. Values of the same type are sorted using Ord, or their specialized sorting function as needed.
+    /// Limits floating point types (float and double) to values within `range`.
+    /// Values outside of the range are rejected with an error saying so, unless another allowed type accepts them.
+    /// Supports method chaining.
     #[must_use]
-    pub fn total_cmp(&self, other: &Variant) -> std::cmp::Ordering {
-        let lhs = match self {
-            Variant::Bool(_) => (0, self),
-            Variant::Int(_) => (1, self),
-            Variant::Float(_) => (2, self),
-            Variant::Socket(_) => (3, self),
-            Variant::Path(_) => (4, self),
-            Variant::String(_) => (5, self),
-        };
-        let rhs = match other {
-            Variant::Bool(_) => (0, other),
-            Variant::Int(_) => (1, other),
-            Variant::Float(_) => (2, other),
-            Variant::Socket(_) => (3, self),
-            Variant::Path(_) => (4, self),
-            Variant::String(_) => (5, self),
-        };
-        if lhs.0 == rhs.0 {
-            match (self, other) {
-                (Variant::Bool(lhs), Variant::Bool(rhs)) => lhs.cmp(rhs),
-                (Variant::Int(lhs), Variant::Int(rhs)) => lhs.cmp(rhs),
-                (Variant::Float(lhs), Variant::Float(rhs)) => lhs.total_cmp(rhs),
-                (Variant::Socket(lhs), Variant::Socket(rhs)) => lhs.cmp(rhs),
-                (Variant::Path(lhs), Variant::Path(rhs)) => lhs.as_os_str().cmp(rhs.as_os_str()),
-                (Variant::String(lhs), Variant::String(rhs)) => lhs.cmp(rhs),
-                _ => unreachable!(),
-            }
-        } else {
-            lhs.0.cmp(&rhs.0)
-        }
+    pub fn float_range(mut self, range: impl RangeBounds<f64>) -> VariantFlag {
+        self.float_range = (
+            range.start_bound().map(|f| f.to_bits()),
+            range.end_bound().map(|f| f.to_bits()),
+        );
+        self
     }
 
-    /// Maps from Variant to Option\<bool\>
-    pub fn as_bool(&self) -> Option<bool> {
-        if let Variant::Bool(b) = self {
-            Some(*b)
+    /// Checks an integer against [`VariantFlag::int_range`].
+    fn check_int_range(&self, raw: &str, value: i128) -> Result<(), String> {
+        let (start, end) = (
+            self.int_range.0.map(i128::from),
+            self.int_range.1.map(i128::from),
+        );
+        if (start, end).contains(&value) {
+            Ok(())
         } else {
-            None
+            Err(format!(
+                "value {raw} is out of range {}",
+                format_range(self.int_range.0, self.int_range.1)
+            ))
         }
     }
 
-    /// Maps from Variant to Option\<i32\>
-    pub fn as_int(&self) -> Option<i32> {
-        if let Variant::Int(i) = self {
-            Some(*i)
-        } else {
-            None
-        }
+    /// Like [`VariantFlag::float`], but `NaN`, `inf`, and `-inf` are rejected, see [`VariantFlag::finite`].
+    #[must_use]
+    pub fn float_finite() -> VariantFlag {
+        VariantFlag::float().finite()
     }
 
-    /// Maps from Variant to Option\<f32\>
-    pub fn as_float(&self) -> Option<f32> {
-        if let Variant::Float(f) = self {
-            Some(*f)
-        } else {
-            None
-        }
+    /// Rejects values that aren't finite numbers, like `NaN`, `inf`, or `-inf`, for floating point types (float and double).
+    /// Values too large for the type round to infinity when parsed, so they are rejected too, like `1e40` for a float.
+    /// Supports method chaining.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Variant, VariantFlag};
+    /// let timeout = VariantFlag::float_finite();
+    /// assert_eq!(timeout.parse("1.5"), Some(Variant::Float(1.5)));
+    /// for raw in ["NaN", "nan", "inf", "-inf", "1e40"] {
+    ///     assert_eq!(timeout.parse(raw), None);
+    /// }
+    /// assert_eq!(
+    ///     timeout.try_parse("inf").unwrap_err().to_string(),
+    ///     "tried float: value inf must be a finite number"
+    /// );
+    /// // A double can still hold 1e40
+    /// assert_eq!(timeout.or_double().parse("1e40"), Some(Variant::Double(1e40)));
+    ///
+    /// // Plain floats accept them
+    /// assert_eq!(VariantFlag::float().parse("-inf"), Some(Variant::Float(f32::NEG_INFINITY)));
+    /// assert_eq!(VariantFlag::float().parse("1e40"), Some(Variant::Float(f32::INFINITY)));
+    /// ```
+    #[must_use]
+    pub fn finite(mut self) -> VariantFlag {
+        self.finite = true;
+        self
     }
 
-    /// Maps from Variant to Option\<SocketAddr\>
-    pub fn as_socket(&self) -> Option<SocketAddr> {
-        if let Variant::Socket(s) = self {
-            Some(*s)
+    /// Checks a floating point number against [`VariantFlag::finite`] and [`VariantFlag::float_range`].
+    /// The bounds are converted to the number's own type with `convert`, so `0.1` includes an [`f32`] parsed from `0.1`, which is a little more than the [`f64`].
+    fn check_float_range<T: PartialOrd + Into<f64> + Copy>(
+        &self,
+        raw: &str,
+        value: T,
+        convert: impl Fn(f64) -> T,
+    ) -> Result<(), String> {
+        if self.finite && !value.into().is_finite() {
+            return Err(format!("value {raw} must be a finite number"));
+        }
+        let (start, end) = (
+            self.float_range.0.map(f64::from_bits),
+            self.float_range.1.map(f64::from_bits),
+        );
+        if (start.map(&convert), end.map(&convert)).contains(&value) {
+            Ok(())
         } else {
-            None
+            Err(format!(
+                "value {raw} is out of range {}",
+                format_range(
+                    start.map(|f| format!("{f:?}")),
+                    end.map(|f| format!("{f:?}"))
+                )
+            ))
         }
     }
 
-    /// Maps from Variant to Option\<&PathBuf\>
-    pub fn as_path(&self) -> Option<&PathBuf> {
-        if let Variant::Path(p) = self {
-            Some(p)
-        } else {
-            None
-        }
+    /// An argument parsed with the resulting VariantFlag will only become an [`f32`].
+    #[must_use]
+    pub fn float() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::FLOAT_BIT)
     }
 
-    /// Maps from Variant to Option\<Pathbuf\>
-    pub fn into_path(self) -> Option<PathBuf> {
-        if let Variant::Path(p) = self {
-            Some(p)
-        } else {
-            None
-        }
+    /// Adds [`f32`] to the list of types an argument can support.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn or_float(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::FLOAT_BIT)
     }
 
-    /// Maps from Variant to Option\<&str\>
-    pub fn as_string(&self) -> Option<&str> {
-        if let Variant::String(s) = self {
-            Some(s)
-        } else {
-            None
-        }
+    /// An argument parsed with the resulting VariantFlag will become an [`i32`], or an [`f32`] if it isn't a whole number.
+    #[must_use]
+    pub fn numeric() -> VariantFlag {
+        VariantFlag::int().or_float()
     }
 
-    /// Maps from Variant to Option\<String\>
-    pub fn into_string(self) -> Option<String> {
-        if let Variant::String(s) = self {
-            Some(s)
-        } else {
-            None
-        }
+    /// An argument parsed with the resulting VariantFlag will become the first of bool, i32, i64, u64, f32, Duration, byte sizes, SystemTime, SocketAddr, or host:port that accepts it.
+    /// Anything else becomes a [`String`], so parsing will never fail.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{Variant, VariantFlag};
+    /// let any = VariantFlag::any();
+    /// assert_eq!(any.parse("true"), Some(Variant::Bool(true)));
+    /// assert_eq!(any.parse("1.5"), Some(Variant::Float(1.5)));
+    /// assert_eq!(any.parse("10s"), Some(Variant::Duration(std::time::Duration::from_secs(10))));
+    /// assert_eq!(any.parse("out.txt"), Some(Variant::String("out.txt".to_string())));
+    /// assert_eq!(any.unreachable_kinds().count(), 0);
+    /// ```
+    #[must_use]
+    pub fn any() -> VariantFlag {
+        VariantFlag::bool()
+            | VariantFlag::int()
+            | VariantFlag::long()
+            | VariantFlag::uint()
+            | VariantFlag::float()
+            | VariantFlag::duration()
+            | VariantFlag::byte_size()
+            | VariantFlag::datetime()
+            | VariantFlag::socket()
+            | VariantFlag::host_port()
+            | VariantFlag::string()
     }
-}
 
-impl Display for Variant {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Variant::Bool(inner) => inner.fmt(f),
-            Variant::Int(inner) => inner.fmt(f),
-            Variant::Float(inner) => inner.fmt(f),
-            Variant::Socket(inner) => inner.fmt(f),
-            Variant::Path(inner) => inner.to_string_lossy().fmt(f),
-            Variant::String(inner) => inner.fmt(f),
-        }
+    /// Allows every [`VariantKind`], for narrowing down with [`VariantFlag::without`].
+    /// Paths accept everything strings would, so use [`VariantFlag::any`] for a flag that accepts everything.
+    ///
+    /// ```
+    /// # use flagged_cl_args::VariantFlag;
+    /// assert!(VariantFlag::all().contains(VariantFlag::any()));
+    /// assert_eq!(VariantFlag::all().kinds().count(), VariantFlag::all().to_string().split('|').count());
+    /// ```
+    #[must_use]
+    pub fn all() -> VariantFlag {
+        VariantKind::ALL
+            .iter()
+            .fold(VariantFlag::from_bits(0), |flag, kind| {
+                flag.with_bits(1 << kind.bit())
+            })
     }
-}
 
-impl Display for VariantFlag {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.is_unit() {
-            return write!(f, "flag");
-        }
-        fn inner(
-            first: &mut Option<&str>,
-            val: &str,
-            f: &mut std::fmt::Formatter<'_>,
-        ) -> std::fmt::Result {
-            let res = write!(f, "{}{}", first.unwrap_or(""), val);
-            *first = Some("|");
-            res
-        }
-        let mut separator = None;
-        if self.bool_allowed() {
-            inner(&mut separator, "bool", f)?;
-        }
-        if self.int_allowed() {
-            inner(&mut separator, "int", f)?;
+    /// Whether this flag allows every type `other` does, including its choices and custom parser.
+    ///
+    /// ```
+    /// # use flagged_cl_args::VariantFlag;
+    /// let flag = VariantFlag::numeric().or_choice(&["auto", "never"]);
+    /// assert!(flag.contains(VariantFlag::int()));
+    /// assert!(flag.contains(VariantFlag::float() | VariantFlag::choice(&["auto"])));
+    /// assert!(!flag.contains(VariantFlag::long()));
+    /// assert!(!flag.contains(VariantFlag::choice(&["always"])));
+    /// ```
+    #[must_use]
+    pub fn contains(&self, other: VariantFlag) -> bool {
+        other.bits & !self.bits == 0
+            && other
+                .choices
+                .iter()
+                .all(|choice| self.choices.contains(choice))
+            && (other.custom.is_none() || self.custom == other.custom)
+    }
+
+    /// Removes the types `other` allows, including its choices and custom parser if it has one.
+    /// Settings like [`VariantFlag::int_range`] are kept.
+    ///
+    /// ```
+    /// # use flagged_cl_args::VariantFlag;
+    /// let flag = VariantFlag::all().without(VariantFlag::path() | VariantFlag::list_of(VariantFlag::int()));
+    /// assert!(flag.contains(VariantFlag::string()));
+    /// assert!(!flag.contains(VariantFlag::path()));
+    /// assert_eq!(flag.unreachable_kinds().count(), 1);
+    ///
+    /// let flag = VariantFlag::choice(&["json", "yaml", "text"]).or_int();
+    /// assert_eq!(flag.clone().without(VariantFlag::choice(&["yaml"])).to_string(), "int|json|text");
+    /// assert_eq!(flag.without(VariantFlag::choice(&["json", "yaml", "text"])), VariantFlag::int());
+    /// ```
+    #[must_use]
+    pub fn without(mut self, other: VariantFlag) -> VariantFlag {
+        let choice_allowed = self.choice_allowed();
+        self.bits &= !other.bits;
+        self.choices
+            .retain(|choice| !other.choices.contains(choice));
+        if choice_allowed && !self.choices.is_empty() {
+            self.bits |= 1 << VariantFlag::CHOICE_BIT;
+        } else {
+            self.bits &= !(1 << VariantFlag::CHOICE_BIT);
         }
-        if self.float_allowed() {
-            inner(&mut separator, "float", f)?;
+        if !self.allows(VariantKind::Socket) {
+            self.bits &= !(1 << VariantFlag::SOCKET_RESOLVE_BIT);
         }
-        if self.socket_allowed() {
-            inner(&mut separator, "socket", f)?;
+        if other.allows(VariantKind::Bool) {
+            self.bits &= !(1 << VariantFlag::BOOL_LENIENT_BIT);
         }
-        if self.path_allowed() {
-            inner(&mut separator, "path", f)?;
+        if !self.allows(VariantKind::List) && !self.allows(VariantKind::Pair) {
+            self.element = None;
         }
-        if self.string_allowed() {
-            inner(&mut separator, "string", f)?;
+        if other.custom.is_some() {
+            self.custom = None;
         }
-        Ok(())
+        self
+    }
+
+    /// An argument parsed with the resulting VariantFlag will only become an [`f64`].
+    /// Since [`f32`] parsing never fails on a well-formed number, allowing both float and double means double will never be produced.
+    #[must_use]
+    pub fn double() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::DOUBLE_BIT)
+    }
+
+    /// Adds [`f64`] to the list of types an argument can support.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn or_double(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::DOUBLE_BIT)
+    }
+
+    /// An argument parsed with the resulting VariantFlag will only become a [`std::time::Duration`].
+    /// Durations are written as numbers followed by a unit (`ms`, `s`, `m`, or `h`), and may be compounded like `1h30m`.
+    /// A bare number is rejected, since there is no obvious unit to assume.
+    #[must_use]
+    pub fn duration() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::DURATION_BIT)
+    }
+
+    /// Adds [`std::time::Duration`] to the list of types an argument can support.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn or_duration(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::DURATION_BIT)
+    }
+
+    /// An argument parsed with the resulting VariantFlag will only become a byte count stored as a [`u64`].
+    /// Accepts a bare number of bytes, or a number followed by a decimal (`KB`, `MB`, `GB`, `TB`) or binary (`KiB`, `MiB`, `GiB`, `TiB`) suffix, ignoring case.
+    /// Fractional values like `1.5GB` are rounded down to whole bytes.
+    #[must_use]
+    pub fn byte_size() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::BYTES_BIT)
+    }
+
+    /// Adds byte counts to the list of types an argument can support.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn or_byte_size(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::BYTES_BIT)
+    }
+
+    /// An argument parsed with the resulting VariantFlag will only become a [`std::time::SystemTime`].
+    /// Values must be RFC 3339 timestamps like `2024-06-01T12:00:00Z` or `2024-06-01T14:00:00.5+02:00`.
+    /// A date on its own, like `2024-06-01`, is accepted as midnight UTC.
+    /// Fractions of a second are kept to the nanosecond, and any digits past that are dropped.
+    #[must_use]
+    pub fn datetime() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::DATETIME_BIT)
+    }
+
+    /// Adds [`std::time::SystemTime`] to the list of types an argument can support.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn or_datetime(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::DATETIME_BIT)
+    }
+
+    /// An argument parsed with the resulting VariantFlag will only become an [`std::net::SocketAddr`].
+    /// Only literal addresses like `127.0.0.1:80` or `[::1]:80` are accepted, no DNS lookup is performed.
+    #[must_use]
+    pub fn socket() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::SOCKET_BIT)
+    }
+
+    /// Adds [`std::net::SocketAddr`] to the list of types an argument can support.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn or_socket(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::SOCKET_BIT)
+    }
+
+    /// Like [`VariantFlag::socket`], but hostnames like `example.com:443` are resolved using DNS.
+    /// The lookup blocks until it completes, and the first resolved address is used.
+    #[must_use]
+    pub fn socket_resolving() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::SOCKET_BIT | 1 << VariantFlag::SOCKET_RESOLVE_BIT)
+    }
+
+    /// Adds a DNS resolving [`std::net::SocketAddr`] to the list of types an argument can support.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn or_socket_resolving(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::SOCKET_BIT | 1 << VariantFlag::SOCKET_RESOLVE_BIT)
+    }
+
+    /// An argument parsed with the resulting VariantFlag will only become a [`Variant::HostPort`].
+    /// The hostname is kept as written, only the port is validated. IPv6 hosts must be bracketed like `[::1]:8080`.
+    #[must_use]
+    pub fn host_port() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::HOST_PORT_BIT)
+    }
+
+    /// Adds [`Variant::HostPort`] to the list of types an argument can support.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn or_host_port(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::HOST_PORT_BIT)
+    }
+
+    /// An argument parsed with the resulting VariantFlag will only become a [`String`], and only if it is one of `choices`.
+    ///
+    /// ```
+    /// # use flagged_cl_args::VariantFlag;
+    /// let format = VariantFlag::choice(&["json", "yaml", "text"]);
+    /// assert_eq!(format.to_string(), "json|yaml|text");
+    /// ```
+    #[must_use]
+    pub fn choice(choices: &[&str]) -> VariantFlag {
+        VariantFlag::from_bits(0).or_choice(choices)
+    }
+
+    /// Adds a set of allowed strings to the list of types an argument can support.
+    /// Calling this more than once extends the set.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn or_choice(mut self, choices: &[&str]) -> VariantFlag {
+        self.choices
+            .extend(choices.iter().map(|choice| choice.to_string()));
+        self.with_bits(1 << VariantFlag::CHOICE_BIT)
+    }
+
+    /// Matches choices without regard to ASCII case.
+    /// The resulting value is always spelled the way the choice was declared.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn ignore_case(mut self) -> VariantFlag {
+        self.ignore_case = true;
+        self
+    }
+
+    /// An argument parsed with the resulting VariantFlag will only become a [`Variant::List`].
+    /// The value is split on commas (see [`VariantFlag::delimiter`]) and each element is parsed using `element`.
+    ///
+    /// An empty value becomes an empty list, and a single trailing delimiter is ignored.
+    /// Any other empty element is parsed like a normal value, so `a,,b` only succeeds if `element` accepts an empty string.
+    #[must_use]
+    pub fn list_of(element: VariantFlag) -> VariantFlag {
+        let mut flag = VariantFlag::from_bits(1 << VariantFlag::LIST_BIT);
+        flag.element = Some(Box::new(element));
+        flag
+    }
+
+    /// Changes the character list elements are separated by.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn delimiter(mut self, delimiter: char) -> VariantFlag {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// An argument parsed with the resulting VariantFlag will only become a [`Variant::Pair`].
+    /// The value is split on the first `=`, the key is kept as a string, and the rest is parsed using `value_type`.
+    /// This is intended for `-D name=value` style defines.
+    #[must_use]
+    pub fn key_value(value_type: VariantFlag) -> VariantFlag {
+        let mut flag = VariantFlag::from_bits(1 << VariantFlag::PAIR_BIT);
+        flag.element = Some(Box::new(value_type));
+        flag
+    }
+
+    /// An argument parsed with the resulting VariantFlag will only become a [`regex::Regex`].
+    /// Patterns that fail to compile are rejected with the error from the regex crate.
+    #[cfg(feature = "regex")]
+    #[must_use]
+    pub fn regex() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::REGEX_BIT)
+    }
+
+    /// Adds [`regex::Regex`] to the list of types an argument can support.
+    /// Supports method chaining.
+    #[cfg(feature = "regex")]
+    #[must_use]
+    pub fn or_regex(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::REGEX_BIT)
+    }
+
+    /// An argument parsed with the resulting VariantFlag will only become an [`std::path::PathBuf`].
+    /// This conversion will never fail, but that doesn't mean the path points to anything meaningful.
+    /// Use [`VariantFlag::must_exist`], [`VariantFlag::must_be_file`], or [`VariantFlag::must_be_dir`] to check the filesystem.
+    #[must_use]
+    pub fn path() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::PATH_BIT)
+    }
+
+    /// Adds [`std::path::Path`] to the list of types an argument can support.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn or_path(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::PATH_BIT)
+    }
+
+    /// Paths must point to something that exists when they are parsed.
+    /// Symlinks are followed, and relative paths are checked against the current directory.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn must_exist(mut self) -> VariantFlag {
+        self.path_check = PathCheck::Exists;
+        self
+    }
+
+    /// Paths must point to an existing file when they are parsed.
+    /// Symlinks are followed, and relative paths are checked against the current directory.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn must_be_file(mut self) -> VariantFlag {
+        self.path_check = PathCheck::File;
+        self
+    }
+
+    /// Paths must point to an existing directory when they are parsed.
+    /// Symlinks are followed, and relative paths are checked against the current directory.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn must_be_dir(mut self) -> VariantFlag {
+        self.path_check = PathCheck::Dir;
+        self
+    }
+
+    /// An argument parsed with the resulting VariantFlag will be passed directly as a [`String`].
+    /// This conversion will never fail.
+    #[must_use]
+    pub fn string() -> VariantFlag {
+        VariantFlag::from_bits(1 << VariantFlag::STRING_BIT)
+    }
+
+    /// Adds [`String`] to the list of types an argument can support.
+    /// Supports method chaining.
+    #[must_use]
+    pub fn or_string(self) -> VariantFlag {
+        self.with_bits(1 << VariantFlag::STRING_BIT)
+    }
+
+    #[must_use]
+    fn check_bit(&self, bit: u8) -> bool {
+        self.bits >> bit & 1 != 0
+    }
+
+    /// Parses an integer, accepting radix prefixes unless this flag is decimal only.
+    fn parse_integer<T>(
+        &self,
+        raw: &str,
+        from_str_radix: fn(&str, u32) -> Result<T, ParseIntError>,
+    ) -> Result<T, ParseIntError> {
+        if self.decimal_only {
+            return from_str_radix(raw, 10);
+        }
+        let (sign, unsigned) = match raw.strip_prefix('-') {
+            Some(unsigned) => ("-", unsigned),
+            None => ("", raw.strip_prefix('+').unwrap_or(raw)),
+        };
+        let (radix, digits) = match unsigned.get(..2).map(str::to_ascii_lowercase).as_deref() {
+            Some("0x") => (16, &unsigned[2..]),
+            Some("0o") => (8, &unsigned[2..]),
+            Some("0b") => (2, &unsigned[2..]),
+            _ => return from_str_radix(raw, 10),
+        };
+        if digits.starts_with(['-', '+']) {
+            // from_str_radix would otherwise accept a second sign after the prefix
+            return from_str_radix("", radix);
+        }
+        from_str_radix(&format!("{sign}{digits}"), radix)
+    }
+
+    #[must_use]
+    pub(crate) fn socket_resolves(&self) -> bool {
+        self.check_bit(VariantFlag::SOCKET_RESOLVE_BIT)
+    }
+
+    #[must_use]
+    pub(crate) fn choice_allowed(&self) -> bool {
+        self.check_bit(VariantFlag::CHOICE_BIT)
+    }
+
+    #[must_use]
+    pub(crate) fn choices(&self) -> &[String] {
+        &self.choices
+    }
+
+    #[must_use]
+    fn match_choice(&self, raw: &str) -> Option<&str> {
+        self.choices
+            .iter()
+            .find(|choice| {
+                if self.ignore_case {
+                    choice.eq_ignore_ascii_case(raw)
+                } else {
+                    *choice == raw
+                }
+            })
+            .map(String::as_str)
+    }
+
+    /// Splits a list value into its elements and parses each of them.
+    /// The error describes the first element that failed.
+    fn parse_list(&self, raw: &str) -> Result<Vec<Variant>, String> {
+        let Some(element) = &self.element else {
+            return Err(format!("'{raw}' cannot be parsed as a list"));
+        };
+        let trimmed = raw.strip_suffix(self.delimiter).unwrap_or(raw);
+        if trimmed.is_empty() {
+            return Ok(Vec::new());
+        }
+        trimmed
+            .split(self.delimiter)
+            .enumerate()
+            .map(|(index, item)| {
+                element.parse_or_reason(item).map_err(|reason| match reason {
+                    Some(reason) => format!("element {index} of '{raw}': {reason}"),
+                    None => format!(
+                        "element {index} ('{item}') of '{raw}' cannot be parsed as type {element}"
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// Splits a `key=value` pair and parses the value.
+    fn parse_pair(&self, raw: &str) -> Result<(String, Variant), String> {
+        let Some(value_type) = &self.element else {
+            return Err(format!("'{raw}' cannot be parsed as a key=value pair"));
+        };
+        let (key, value) = raw
+            .split_once('=')
+            .ok_or_else(|| format!("'{raw}' is missing '=', expected key=value"))?;
+        if key.is_empty() {
+            return Err(format!("'{raw}' is missing a key, expected key=value"));
+        }
+        let parsed = value_type
+            .parse_or_reason(value)
+            .map_err(|reason| match reason {
+                Some(reason) => format!("value of '{key}': {reason}"),
+                None => format!("value of '{key}' cannot be parsed as type {value_type}"),
+            })?;
+        Ok((key.to_string(), parsed))
+    }
+
+    /// Like [`VariantFlag::parse_or_reason`], but values that aren't valid UTF-8 can still become paths.
+    pub(crate) fn parse_os_or_reason(&self, raw: &OsStr) -> Result<Variant, Option<String>> {
+        self.try_parse_os(raw).map_err(|failure| failure.reason)
+    }
+
+    /// Like [`VariantFlag::try_parse`], but values that aren't valid UTF-8 can still become paths.
+    pub(crate) fn try_parse_os(&self, raw: &OsStr) -> Result<Variant, ParseFailure> {
+        let mut failure = ParseFailure::default();
+        match raw.to_str() {
+            Some(raw) => return self.try_parse(raw),
+            None if self.allows(VariantKind::Path) => match self.check_path(PathBuf::from(raw)) {
+                Ok(path) => return Ok(path),
+                Err(error) => failure.rejected(VariantKind::Path.name(), error),
+            },
+            None => {
+                failure.reason = Some(format!("'{}' is not valid UTF-8", raw.to_string_lossy()));
+            }
+        }
+        Err(failure)
+    }
+
+    /// Applies the filesystem checks for this flag to a path.
+    fn check_path(&self, path: PathBuf) -> Result<Variant, String> {
+        let problem = match self.path_check {
+            PathCheck::None => None,
+            PathCheck::Exists | PathCheck::File | PathCheck::Dir if !path.exists() => {
+                Some("does not exist")
+            }
+            PathCheck::File if !path.is_file() => Some("is not a file"),
+            PathCheck::Dir if !path.is_dir() => Some("is not a directory"),
+            _ => None,
+        };
+        match problem {
+            None => Ok(Variant::Path(path)),
+            Some(problem) => Err(format!("path '{}' {problem}", path.display())),
+        }
+    }
+
+    /// Whether values of `kind` are allowed by this flag.
+    /// Choices aren't included in [`VariantKind::String`], since only the listed strings are allowed.
+    /// Custom parsers may produce values of any kind, regardless of this.
+    #[must_use]
+    pub fn allows(&self, kind: VariantKind) -> bool {
+        self.check_bit(kind.bit())
+    }
+
+    /// Lists the kinds of values this flag allows, in the order they are tried when parsing.
+    ///
+    /// ```
+    /// # use flagged_cl_args::{VariantFlag, VariantKind};
+    /// let kinds: Vec<VariantKind> = VariantFlag::string().or_path().or_int().kinds().collect();
+    /// assert_eq!(kinds, [VariantKind::Int, VariantKind::Path, VariantKind::String]);
+    /// ```
+    pub fn kinds(&self) -> impl Iterator<Item = VariantKind> + '_ {
+        VariantKind::ALL
+            .iter()
+            .copied()
+            .filter(|kind| self.allows(*kind))
+    }
+
+    /// Lists the kinds this flag allows that parsing can never produce, because a kind tried before them accepts everything they would.
+    /// Such flags are rejected by [`crate::validate_definitions`].
+    ///
+    /// ```
+    /// # use flagged_cl_args::{VariantFlag, VariantKind};
+    /// let unreachable: Vec<VariantKind> = VariantFlag::path().or_string().unreachable_kinds().collect();
+    /// assert_eq!(unreachable, [VariantKind::String]);
+    ///
+    /// // Ports are always valid ints
+    /// let unreachable: Vec<VariantKind> = VariantFlag::int().or_port().unreachable_kinds().collect();
+    /// assert_eq!(unreachable, [VariantKind::Port]);
+    ///
+    /// // Paths that must exist can fail, leaving strings for everything else
+    /// assert_eq!(VariantFlag::path().must_exist().or_string().unreachable_kinds().count(), 0);
+    /// assert_eq!(VariantFlag::string().or_int().unreachable_kinds().count(), 0);
+    /// ```
+    pub fn unreachable_kinds(&self) -> impl Iterator<Item = VariantKind> + '_ {
+        self.kinds()
+            .filter(|kind| self.shadowed_by(*kind).is_some())
+    }
+
+    /// The allowed kind that is tried before `kind` and accepts everything it would, if there is one.
+    pub(crate) fn shadowed_by(&self, kind: VariantKind) -> Option<VariantKind> {
+        let shadowing: &[VariantKind] = match kind {
+            VariantKind::Port => &[VariantKind::Int, VariantKind::Long, VariantKind::Uint],
+            VariantKind::String if self.path_check == PathCheck::None => &[VariantKind::Path],
+            _ => &[],
+        };
+        shadowing
+            .iter()
+            .copied()
+            .find(|shadow| self.allows(*shadow))
+    }
+
+    #[must_use]
+    pub(crate) fn is_count(&self) -> bool {
+        self.check_bit(VariantFlag::COUNT_BIT)
+    }
+
+    #[must_use]
+    pub(crate) fn is_unit(&self) -> bool {
+        self.bits & !(1 << VariantFlag::BOOL_LENIENT_BIT) == 0 && self.custom.is_none()
+    }
+
+    /// Parses a value explicitly given to a unit flag, like `--force=false`, see [`VariantFlag::lenient_bools`].
+    pub(crate) fn parse_unit_value(&self, raw: &str) -> Option<bool> {
+        if self.check_bit(VariantFlag::BOOL_LENIENT_BIT) {
+            parse_lenient_bool(raw)
+        } else {
+            bool::from_str(raw).ok()
+        }
+    }
+
+    /// Writes a value back out the way this flag parses it, joining lists with this flag's delimiter, see [`crate::Args::to_argv`].
+    pub(crate) fn unparse(&self, value: &Variant) -> String {
+        match (value, &self.element) {
+            (Variant::List(items), Some(element)) => items
+                .iter()
+                .map(|item| element.unparse(item))
+                .collect::<Vec<_>>()
+                .join(&self.delimiter.to_string()),
+            (Variant::Pair(key, value), Some(element)) => {
+                format!("{key}={}", element.unparse(value))
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    /// Whether a value could have been produced by this flag.
+    /// Values for custom parsers can't be checked, so they are always allowed.
+    #[must_use]
+    pub(crate) fn allows_value(&self, value: &Variant) -> bool {
+        if self.custom.is_some() {
+            return true;
+        }
+        match value {
+            Variant::Bool(_) => self.is_unit() || self.allows(VariantKind::Bool),
+            Variant::Int(i) => {
+                self.is_count()
+                    || (self.allows(VariantKind::Int)
+                        && self.check_int_range("", (*i).into()).is_ok())
+            }
+            Variant::Long(l) => {
+                self.allows(VariantKind::Long) && self.check_int_range("", (*l).into()).is_ok()
+            }
+            Variant::Uint(u) => {
+                self.allows(VariantKind::Uint) && self.check_int_range("", (*u).into()).is_ok()
+            }
+            Variant::Port(p) => {
+                self.allows(VariantKind::Port) && self.check_int_range("", (*p).into()).is_ok()
+            }
+            Variant::Float(f) => {
+                self.allows(VariantKind::Float)
+                    && (!self.finite || f.is_finite())
+                    && self.check_float_range("", *f, |bound| bound as f32).is_ok()
+            }
+            Variant::Double(d) => {
+                self.allows(VariantKind::Double)
+                    && (!self.finite || d.is_finite())
+                    && self.check_float_range("", *d, |bound| bound).is_ok()
+            }
+            Variant::Duration(_) => self.allows(VariantKind::Duration),
+            Variant::Bytes(_) => self.allows(VariantKind::Bytes),
+            Variant::DateTime(_) => self.allows(VariantKind::DateTime),
+            Variant::Socket(_) => self.allows(VariantKind::Socket),
+            Variant::HostPort { .. } => self.allows(VariantKind::HostPort),
+            Variant::Path(_) => self.allows(VariantKind::Path),
+            Variant::Stdin => self.stdin_dash,
+            Variant::String(s) => {
+                self.allows(VariantKind::String)
+                    || (self.choice_allowed() && self.match_choice(s).is_some())
+            }
+            Variant::List(values) => {
+                self.allows(VariantKind::List)
+                    && self.element.as_ref().is_some_and(|element| {
+                        values.iter().all(|value| element.allows_value(value))
+                    })
+            }
+            Variant::Pair(_, value) => {
+                self.allows(VariantKind::Pair)
+                    && self
+                        .element
+                        .as_ref()
+                        .is_some_and(|value_type| value_type.allows_value(value))
+            }
+            #[cfg(feature = "regex")]
+            Variant::Regex(_) => self.allows(VariantKind::Regex),
+        }
+    }
+
+    /// Parse a string into one of the types this VariantFlag supports.
+    /// The precedence is a custom parser, bool, i32, i64, u64, port, f32, f64, Duration, byte sizes, SystemTime, SocketAddr, host:port, choices, lists, key=value pairs, Regex, PathBuf, and lastly String.
+    /// This means a flag allowing both int and uint will only produce a uint for values too large for an i32 or i64.
+    #[must_use]
+    pub fn parse(&self, raw: &str) -> Option<Variant> {
+        self.try_parse(raw).ok()
+    }
+
+    /// Like [`VariantFlag::parse`], but explains why a value was rejected when there is something more specific to say than "not a valid type".
+    pub(crate) fn parse_or_reason(&self, raw: &str) -> Result<Variant, Option<String>> {
+        self.try_parse(raw).map_err(|failure| failure.reason)
+    }
+
+    /// Like [`VariantFlag::parse`], but when no type accepts the value, says which types were tried and why each didn't accept it.
+    /// Each allowed type is tried at most once, so custom parsers are called exactly once per value.
+    ///
+    /// ```
+    /// # use flagged_cl_args::VariantFlag;
+    /// let failure = VariantFlag::int().or_socket().try_parse("localhos:8080").unwrap_err();
+    /// assert_eq!(
+    ///     failure.attempts().collect::<Vec<_>>(),
+    ///     [("int", "invalid digit found in string"), ("socket", "invalid socket address syntax")]
+    /// );
+    ///
+    /// // With only one type to try, its parser's error is the cause
+    /// let failure = VariantFlag::int().try_parse("99999999999").unwrap_err();
+    /// assert_eq!(failure.cause().unwrap().to_string(), "number too large to fit in target type");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// None of the allowed types accept the value.
+    pub fn try_parse(&self, raw: &str) -> Result<Variant, ParseFailure> {
+        let mut failure = ParseFailure::default();
+        if self.stdin_dash && raw == "-" {
+            return Ok(Variant::Stdin);
+        }
+        if let Some(custom) = &self.custom {
+            match (custom.parse)(raw) {
+                Ok(value) => return Ok(value),
+                Err(error) => failure.rejected(&custom.name, error),
+            }
+        }
+        if self.allows(VariantKind::Bool) && self.check_bit(VariantFlag::BOOL_LENIENT_BIT) {
+            match parse_lenient_bool(raw) {
+                Some(b) => return Ok(Variant::Bool(b)),
+                None => failure.mismatched(
+                    VariantKind::Bool,
+                    "expected true, false, yes, no, on, off, 1, or 0".to_string(),
+                ),
+            }
+        } else if self.allows(VariantKind::Bool) {
+            match bool::from_str(raw) {
+                Ok(b) => return Ok(Variant::Bool(b)),
+                Err(error) => failure.failed(VariantKind::Bool, error),
+            }
+        }
+        let number = self.number_text(raw);
+        if self.allows(VariantKind::Int) {
+            match self.parse_integer(&number, i32::from_str_radix) {
+                Ok(i) => match self.check_int_range(&number, i.into()) {
+                    Ok(()) => return Ok(Variant::Int(i)),
+                    Err(error) => failure.rejected(VariantKind::Int.name(), error),
+                },
+                Err(error) => failure.failed(VariantKind::Int, error),
+            }
+        }
+        if self.allows(VariantKind::Long) {
+            match self.parse_integer(&number, i64::from_str_radix) {
+                Ok(l) => match self.check_int_range(&number, l.into()) {
+                    Ok(()) => return Ok(Variant::Long(l)),
+                    Err(error) => failure.rejected(VariantKind::Long.name(), error),
+                },
+                Err(error) => failure.failed(VariantKind::Long, error),
+            }
+        }
+        if self.allows(VariantKind::Uint) {
+            match self.parse_integer(&number, u64::from_str_radix) {
+                Ok(u) => match self.check_int_range(&number, u.into()) {
+                    Ok(()) => return Ok(Variant::Uint(u)),
+                    Err(error) => failure.rejected(VariantKind::Uint.name(), error),
+                },
+                Err(_)
+                    if self
+                        .parse_integer(&number, i128::from_str_radix)
+                        .is_ok_and(|i| i < 0) =>
+                {
+                    failure.rejected(
+                        VariantKind::Uint.name(),
+                        format!("value {number} must be non-negative"),
+                    );
+                }
+                Err(error) => failure.failed(VariantKind::Uint, error),
+            }
+        }
+        if self.allows(VariantKind::Port) {
+            match self.parse_integer(&number, u16::from_str_radix) {
+                Ok(p) => match self.check_int_range(&number, p.into()) {
+                    Ok(()) => return Ok(Variant::Port(p)),
+                    Err(error) => failure.rejected(VariantKind::Port.name(), error),
+                },
+                Err(_) if self.parse_integer(&number, i128::from_str_radix).is_ok() => {
+                    failure.rejected(
+                        VariantKind::Port.name(),
+                        format!(
+                            "value {number} is out of range, ports must be between 0 and 65535"
+                        ),
+                    );
+                }
+                Err(error) => failure.failed(VariantKind::Port, error),
+            }
+        }
+        if self.allows(VariantKind::Float) {
+            match f32::from_str(&number) {
+                Ok(f) => match self.check_float_range(&number, f, |bound| bound as f32) {
+                    Ok(()) => return Ok(Variant::Float(f)),
+                    Err(error) => failure.rejected(VariantKind::Float.name(), error),
+                },
+                Err(error) => failure.failed(VariantKind::Float, error),
+            }
+        }
+        if self.allows(VariantKind::Double) {
+            match f64::from_str(&number) {
+                Ok(d) => match self.check_float_range(&number, d, |bound| bound) {
+                    Ok(()) => return Ok(Variant::Double(d)),
+                    Err(error) => failure.rejected(VariantKind::Double.name(), error),
+                },
+                Err(error) => failure.failed(VariantKind::Double, error),
+            }
+        }
+        if self.allows(VariantKind::Duration) {
+            match parse_duration(raw) {
+                Ok(d) => return Ok(Variant::Duration(d)),
+                Err(error) => failure.rejected(VariantKind::Duration.name(), error),
+            }
+        }
+        if self.allows(VariantKind::Bytes) {
+            match parse_byte_size(raw) {
+                Ok(b) => return Ok(Variant::Bytes(b)),
+                Err(error) => failure.rejected(VariantKind::Bytes.name(), error),
+            }
+        }
+        if self.allows(VariantKind::DateTime) {
+            match parse_datetime(raw) {
+                Ok(t) => return Ok(Variant::DateTime(t)),
+                Err(error) => failure.rejected(VariantKind::DateTime.name(), error),
+            }
+        }
+        if self.allows(VariantKind::Socket) {
+            match SocketAddr::from_str(raw) {
+                Ok(s) => return Ok(Variant::Socket(s)),
+                // Resolving says why it failed instead
+                Err(_) if self.socket_resolves() => {}
+                Err(error) => failure.failed(VariantKind::Socket, error),
+            }
+        }
+        if self.socket_resolves() {
+            match raw.to_socket_addrs().map(|mut sockets| sockets.next()) {
+                Ok(Some(s)) => return Ok(Variant::Socket(s)),
+                Ok(None) => failure.attempts.push(Attempt {
+                    kind: VariantKind::Socket.name().to_string(),
+                    message: format!("{raw} didn't resolve to any addresses"),
+                    cause: None,
+                }),
+                Err(error) => failure.failed(VariantKind::Socket, error),
+            }
+        }
+        if self.allows(VariantKind::HostPort) {
+            match parse_host_port(raw) {
+                Ok((host, port)) => return Ok(Variant::HostPort { host, port }),
+                Err(error) => failure.rejected(VariantKind::HostPort.name(), error),
+            }
+        }
+        if self.choice_allowed() {
+            match self.match_choice(raw) {
+                Some(choice) => return Ok(Variant::String(choice.to_string())),
+                None => failure.rejected(
+                    VariantKind::String.name(),
+                    format!(
+                        "'{raw}' is not one of the valid options: {}",
+                        self.choices.join(", ")
+                    ),
+                ),
+            }
+        }
+        if self.allows(VariantKind::List) {
+            match self.parse_list(raw) {
+                Ok(list) => return Ok(Variant::List(list)),
+                Err(error) => failure.rejected(VariantKind::List.name(), error),
+            }
+        }
+        if self.allows(VariantKind::Pair) {
+            match self.parse_pair(raw) {
+                Ok((key, value)) => return Ok(Variant::Pair(key, Box::new(value))),
+                Err(error) => failure.rejected(VariantKind::Pair.name(), error),
+            }
+        }
+        #[cfg(feature = "regex")]
+        if self.allows(VariantKind::Regex) {
+            match regex::Regex::new(raw) {
+                Ok(regex) => return Ok(Variant::Regex(Regex(regex))),
+                Err(error) => {
+                    failure
+                        .reason
+                        .get_or_insert_with(|| format!("invalid regex: {error}"));
+                    failure.failed(VariantKind::Regex, error);
+                }
+            }
+        }
+        if self.allows(VariantKind::Path) {
+            match self.check_path(PathBuf::from(raw)) {
+                Ok(path) => return Ok(path),
+                Err(error) => failure.rejected(VariantKind::Path.name(), error),
+            }
+        }
+        if self.allows(VariantKind::String) {
+            return Ok(Variant::String(raw.to_string()));
+        }
+        Err(failure)
+    }
+}
+
+/// Why a value couldn't be parsed, see [`VariantFlag::try_parse`].
+///
+/// It displays every type that was tried, and why that type didn't accept the value, like `tried int: invalid digit found in string; tried socket: invalid socket address syntax`.
+#[derive(Clone, Debug, Default)]
+pub struct ParseFailure {
+    attempts: Vec<Attempt>,
+    /// The first explanation more specific than the value being the wrong type, like being out of range
+    reason: Option<String>,
+}
+
+/// A type that didn't accept a value.
+#[derive(Clone, Debug)]
+struct Attempt {
+    kind: String,
+    message: String,
+    cause: Option<Arc<dyn Error + Send + Sync>>,
+}
+
+impl ParseFailure {
+    /// Each type that was tried, like `int`, along with why it didn't accept the value, in the order they were tried.
+    /// Custom parsers are listed by their name.
+    pub fn attempts(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.attempts
+            .iter()
+            .map(|attempt| (attempt.kind.as_str(), attempt.message.as_str()))
+    }
+
+    /// The error from the parser of the only type that was tried, like a [`std::num::ParseIntError`].
+    /// With more than one type, none of their errors is more relevant than the others, so there's no cause.
+    pub fn cause(&self) -> Option<&(dyn Error + Send + Sync + 'static)> {
+        self.shared_cause().map(|cause| &**cause)
+    }
+
+    pub(crate) fn shared_cause(&self) -> Option<&Arc<dyn Error + Send + Sync>> {
+        match &self.attempts[..] {
+            [attempt] => attempt.cause.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The first explanation more specific than the value being the wrong type, like being out of range.
+    pub(crate) fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    /// The value isn't the type at all, as far as the type's parser is concerned.
+    fn failed(&mut self, kind: VariantKind, error: impl Error + Send + Sync + 'static) {
+        self.attempts.push(Attempt {
+            kind: kind.name().to_string(),
+            message: error.to_string(),
+            cause: Some(Arc::new(error)),
+        });
+    }
+
+    /// The value isn't the type at all, for a type without an error of its own.
+    fn mismatched(&mut self, kind: VariantKind, message: String) {
+        self.attempts.push(Attempt {
+            kind: kind.name().to_string(),
+            message,
+            cause: None,
+        });
+    }
+
+    /// The value isn't acceptable as the type, for a more specific reason, like being out of range.
+    fn rejected(&mut self, kind: &str, reason: String) {
+        self.reason.get_or_insert_with(|| reason.clone());
+        self.attempts.push(Attempt {
+            kind: kind.to_string(),
+            message: reason,
+            cause: None,
+        });
+    }
+}
+
+impl Error for ParseFailure {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause().map(|cause| cause as &(dyn Error + 'static))
+    }
+}
+
+impl Display for ParseFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.attempts.is_empty() {
+            return write!(
+                f,
+                "{}",
+                self.reason.as_deref().unwrap_or("no types are allowed")
+            );
+        }
+        for (index, attempt) in self.attempts.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "tried {}: {}", attempt.kind, attempt.message)?;
+        }
+        Ok(())
+    }
+}
+
+/// The kind of a [`Variant`], without its value.
+/// Kinds are ordered by the precedence they are parsed with, see [`VariantFlag::parse`].
+/// More kinds may be added, and some only exist with a feature, like `Regex` with the `regex` feature, so matches need a wildcard arm.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[non_exhaustive]
+pub enum VariantKind {
+    /// See [`Variant::Bool`]
+    Bool,
+    /// See [`Variant::Int`]
+    Int,
+    /// See [`Variant::Long`]
+    Long,
+    /// See [`Variant::Uint`]
+    Uint,
+    /// See [`Variant::Port`]
+    Port,
+    /// See [`Variant::Float`]
+    Float,
+    /// See [`Variant::Double`]
+    Double,
+    /// See [`Variant::Duration`]
+    Duration,
+    /// See [`Variant::Bytes`]
+    Bytes,
+    /// See [`Variant::DateTime`]
+    DateTime,
+    /// See [`Variant::Socket`]
+    Socket,
+    /// See [`Variant::HostPort`]
+    HostPort,
+    /// See [`Variant::List`]
+    List,
+    /// See [`Variant::Pair`]
+    Pair,
+    /// See [`Variant::Regex`]
+    #[cfg(feature = "regex")]
+    Regex,
+    /// See [`Variant::Path`]
+    Path,
+    /// See [`Variant::String`]
+    String,
+}
+
+impl VariantKind {
+    /// Every kind, in order of precedence.
+    const ALL: &[VariantKind] = &[
+        VariantKind::Bool,
+        VariantKind::Int,
+        VariantKind::Long,
+        VariantKind::Uint,
+        VariantKind::Port,
+        VariantKind::Float,
+        VariantKind::Double,
+        VariantKind::Duration,
+        VariantKind::Bytes,
+        VariantKind::DateTime,
+        VariantKind::Socket,
+        VariantKind::HostPort,
+        VariantKind::List,
+        VariantKind::Pair,
+        #[cfg(feature = "regex")]
+        VariantKind::Regex,
+        VariantKind::Path,
+        VariantKind::String,
+    ];
+
+    /// The name of the kind, as used by [`VariantFlag`]'s Display implementation.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            VariantKind::Bool => "bool",
+            VariantKind::Int => "int",
+            VariantKind::Long => "long",
+            VariantKind::Uint => "uint",
+            VariantKind::Port => "port",
+            VariantKind::Float => "float",
+            VariantKind::Double => "double",
+            VariantKind::Duration => "duration",
+            VariantKind::Bytes => "bytes",
+            VariantKind::DateTime => "datetime",
+            VariantKind::Socket => "socket",
+            VariantKind::HostPort => "host:port",
+            VariantKind::List => "list",
+            VariantKind::Pair => "pair",
+            #[cfg(feature = "regex")]
+            VariantKind::Regex => "regex",
+            VariantKind::Path => "path",
+            VariantKind::String => "string",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        match self {
+            VariantKind::Bool => VariantFlag::BOOL_BIT,
+            VariantKind::Int => VariantFlag::INT_BIT,
+            VariantKind::Long => VariantFlag::LONG_BIT,
+            VariantKind::Uint => VariantFlag::UINT_BIT,
+            VariantKind::Port => VariantFlag::PORT_BIT,
+            VariantKind::Float => VariantFlag::FLOAT_BIT,
+            VariantKind::Double => VariantFlag::DOUBLE_BIT,
+            VariantKind::Duration => VariantFlag::DURATION_BIT,
+            VariantKind::Bytes => VariantFlag::BYTES_BIT,
+            VariantKind::DateTime => VariantFlag::DATETIME_BIT,
+            VariantKind::Socket => VariantFlag::SOCKET_BIT,
+            VariantKind::HostPort => VariantFlag::HOST_PORT_BIT,
+            VariantKind::List => VariantFlag::LIST_BIT,
+            VariantKind::Pair => VariantFlag::PAIR_BIT,
+            #[cfg(feature = "regex")]
+            VariantKind::Regex => VariantFlag::REGEX_BIT,
+            VariantKind::Path => VariantFlag::PATH_BIT,
+            VariantKind::String => VariantFlag::STRING_BIT,
+        }
+    }
+}
+
+impl Display for VariantKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A value of a particular type.
+///
+/// Values are compared, ordered, and hashed according to [`Variant::total_cmp`], so they can be sorted or used as keys in a [`std::collections::BTreeMap`] or [`std::collections::HashMap`].
+/// Unlike the float types themselves, `NaN` is equal to itself, and `-0.0` is less than `0.0`.
+/// Like [`VariantKind`], matches need a wildcard arm.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Variant {
+    /// Booleans are represented as [`bool`]
+    Bool(bool),
+    /// Integers are represented as [`i32`]
+    Int(i32),
+    /// Longs are represented as [`i64`]
+    Long(i64),
+    /// Unsigned integers are represented as [`u64`]
+    Uint(u64),
+    /// Ports are represented as [`u16`]
+    Port(u16),
+    /// Floats are represented as [`f32`]
+    Float(f32),
+    /// Doubles are represented as [`f64`]
+    Double(f64),
+    /// Durations are represented as [`std::time::Duration`]
+    Duration(Duration),
+    /// Byte sizes are represented as a [`u64`] number of bytes
+    Bytes(u64),
+    /// Dates and times are represented as [`std::time::SystemTime`]
+    DateTime(SystemTime),
+    /// Sockets are represented as [`std::net::SocketAddr`]
+    Socket(SocketAddr),
+    /// Unresolved host and port pairs, like `db.internal:5432`
+    HostPort {
+        /// The hostname or address, without IPv6 brackets
+        host: String,
+        /// The port number
+        port: u16,
+    },
+    /// Paths are represented as [`std::path::PathBuf`]
+    Path(PathBuf),
+    /// Strings are represented as [`String`]
+    String(String),
+    /// Lists are represented as a [`Vec`] of other values
+    List(Vec<Variant>),
+    /// Key/value pairs are represented as a [`String`] key and another value
+    Pair(String, Box<Variant>),
+    /// Regular expressions are represented as [`Regex`]
+    #[cfg(feature = "regex")]
+    Regex(Regex),
+    /// A lone `-` in place of a file, meaning standard input or output, see [`VariantFlag::stdin_dash`]
+    Stdin,
+}
+
+/// A compiled [`regex::Regex`], compared by its pattern string.
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+pub struct Regex(pub regex::Regex);
+
+#[cfg(feature = "regex")]
+impl PartialEq for Regex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+#[cfg(feature = "regex")]
+impl PartialOrd for Regex {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.0.as_str().partial_cmp(other.0.as_str())
+    }
+}
+
+#[cfg(feature = "regex")]
+impl std::ops::Deref for Regex {
+    type Target = regex::Regex;
+
+    fn deref(&self) -> &regex::Regex {
+        &self.0
+    }
+}
+
+impl PartialEq for Variant {
+    fn eq(&self, other: &Self) -> bool {
+        self.total_cmp(other).is_eq()
+    }
+}
+
+impl Eq for Variant {}
+
+impl PartialOrd for Variant {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Variant {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.total_cmp(other)
+    }
+}
+
+/// Floats are hashed by their bits, and paths by their exact text, to agree with [`Variant::total_cmp`].
+impl std::hash::Hash for Variant {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            Variant::Bool(inner) => inner.hash(state),
+            Variant::Int(inner) => inner.hash(state),
+            Variant::Long(inner) => inner.hash(state),
+            Variant::Uint(inner) => inner.hash(state),
+            Variant::Port(inner) => inner.hash(state),
+            Variant::Float(inner) => inner.to_bits().hash(state),
+            Variant::Double(inner) => inner.to_bits().hash(state),
+            Variant::Duration(inner) => inner.hash(state),
+            Variant::Bytes(inner) => inner.hash(state),
+            Variant::DateTime(inner) => inner.hash(state),
+            Variant::Socket(inner) => inner.hash(state),
+            Variant::HostPort { host, port } => (host, port).hash(state),
+            Variant::Path(inner) => inner.as_os_str().hash(state),
+            Variant::String(inner) => inner.hash(state),
+            Variant::List(inner) => inner.hash(state),
+            Variant::Pair(key, value) => (key, value).hash(state),
+            #[cfg(feature = "regex")]
+            Variant::Regex(inner) => inner.as_str().hash(state),
+            Variant::Stdin => {}
+        }
+    }
+}
+
+impl Variant {
+    /// The kind of this value, without the value itself.
+    #[must_use]
+    pub fn kind(&self) -> VariantKind {
+        match self {
+            Variant::Bool(_) => VariantKind::Bool,
+            Variant::Int(_) => VariantKind::Int,
+            Variant::Long(_) => VariantKind::Long,
+            Variant::Uint(_) => VariantKind::Uint,
+            Variant::Port(_) => VariantKind::Port,
+            Variant::Float(_) => VariantKind::Float,
+            Variant::Double(_) => VariantKind::Double,
+            Variant::Duration(_) => VariantKind::Duration,
+            Variant::Bytes(_) => VariantKind::Bytes,
+            Variant::DateTime(_) => VariantKind::DateTime,
+            Variant::Socket(_) => VariantKind::Socket,
+            Variant::HostPort { .. } => VariantKind::HostPort,
+            Variant::List(_) => VariantKind::List,
+            Variant::Pair(_, _) => VariantKind::Pair,
+            #[cfg(feature = "regex")]
+            Variant::Regex(_) => VariantKind::Regex,
+            // It takes the place of a path
+            Variant::Path(_) | Variant::Stdin => VariantKind::Path,
+            Variant::String(_) => VariantKind::String,
+        }
+    }
+
+    /// Orders values according to their precedence. Values of the same type are sorted using Ord, or their specialized sorting function as needed.
+    /// Floats are sorted with [`f32::total_cmp`] and [`f64::total_cmp`], so this is a total order, which [`Ord`] for [`Variant`] follows.
+    ///
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use std::path::PathBuf;
+    /// # use std::time::{Duration, UNIX_EPOCH};
+    /// # use flagged_cl_args::Variant;
+    /// let values = [
+    ///     Variant::Bool(false),
+    ///     Variant::Bool(true),
+    ///     Variant::Int(-1),
+    ///     Variant::Long(1),
+    ///     Variant::Uint(2),
+    ///     Variant::Port(80),
+    ///     Variant::Float(-0.0),
+    ///     Variant::Float(0.0),
+    ///     Variant::Float(f32::NAN),
+    ///     Variant::Double(1.5),
+    ///     Variant::Duration(Duration::from_secs(1)),
+    ///     Variant::Bytes(1024),
+    ///     Variant::DateTime(UNIX_EPOCH),
+    ///     Variant::Socket("127.0.0.1:80".parse().unwrap()),
+    ///     Variant::HostPort { host: "localhost".to_string(), port: 80 },
+    ///     Variant::Path(PathBuf::from("a")),
+    ///     Variant::String("a".to_string()),
+    ///     Variant::String("b".to_string()),
+    ///     Variant::List(vec![Variant::Int(1)]),
+    ///     Variant::List(vec![Variant::Int(1), Variant::Int(2)]),
+    ///     Variant::Pair("a".to_string(), Box::new(Variant::Int(1))),
+    ///     Variant::Stdin,
+    /// ];
+    /// // They are listed in order, and every pair of them agrees with that
+    /// for (i, a) in values.iter().enumerate() {
+    ///     for (j, b) in values.iter().enumerate() {
+    ///         assert_eq!(a.total_cmp(b), i.cmp(&j), "{a:?} and {b:?}");
+    ///         assert_eq!(a.total_cmp(b), b.total_cmp(a).reverse());
+    ///         assert_eq!(a == b, i == j);
+    ///     }
+    /// }
+    /// assert_eq!(Variant::Float(f32::NAN), Variant::Float(f32::NAN));
+    ///
+    /// let mut counts = BTreeMap::new();
+    /// for value in [Variant::String("b".to_string()), Variant::Int(3), Variant::String("b".to_string())] {
+    ///     *counts.entry(value).or_insert(0) += 1;
+    /// }
+    /// assert_eq!(counts.into_iter().collect::<Vec<_>>(), [(Variant::Int(3), 1), (Variant::String("b".to_string()), 2)]);
+    /// ```
+    #[must_use]
+    pub fn total_cmp(&self, other: &Variant) -> std::cmp::Ordering {
+        if self.rank() == other.rank() {
+            match (self, other) {
+                (Variant::Bool(lhs), Variant::Bool(rhs)) => lhs.cmp(rhs),
+                (Variant::Int(lhs), Variant::Int(rhs)) => lhs.cmp(rhs),
+                (Variant::Long(lhs), Variant::Long(rhs)) => lhs.cmp(rhs),
+                (Variant::Uint(lhs), Variant::Uint(rhs)) => lhs.cmp(rhs),
+                (Variant::Port(lhs), Variant::Port(rhs)) => lhs.cmp(rhs),
+                (Variant::Float(lhs), Variant::Float(rhs)) => lhs.total_cmp(rhs),
+                (Variant::Double(lhs), Variant::Double(rhs)) => lhs.total_cmp(rhs),
+                (Variant::Duration(lhs), Variant::Duration(rhs)) => lhs.cmp(rhs),
+                (Variant::Bytes(lhs), Variant::Bytes(rhs)) => lhs.cmp(rhs),
+                (Variant::DateTime(lhs), Variant::DateTime(rhs)) => lhs.cmp(rhs),
+                (Variant::Socket(lhs), Variant::Socket(rhs)) => lhs.cmp(rhs),
+                (
+                    Variant::HostPort { host, port },
+                    Variant::HostPort {
+                        host: rhs_host,
+                        port: rhs_port,
+                    },
+                ) => (host, port).cmp(&(rhs_host, rhs_port)),
+                (Variant::Path(lhs), Variant::Path(rhs)) => lhs.as_os_str().cmp(rhs.as_os_str()),
+                (Variant::String(lhs), Variant::String(rhs)) => lhs.cmp(rhs),
+                (Variant::List(lhs), Variant::List(rhs)) => lhs
+                    .iter()
+                    .zip(rhs)
+                    .map(|(lhs, rhs)| lhs.total_cmp(rhs))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or_else(|| lhs.len().cmp(&rhs.len())),
+                (Variant::Pair(lhs_key, lhs), Variant::Pair(rhs_key, rhs)) => {
+                    lhs_key.cmp(rhs_key).then_with(|| lhs.total_cmp(rhs))
+                }
+                #[cfg(feature = "regex")]
+                (Variant::Regex(lhs), Variant::Regex(rhs)) => lhs.as_str().cmp(rhs.as_str()),
+                (Variant::Stdin, Variant::Stdin) => std::cmp::Ordering::Equal,
+                _ => unreachable!(),
+            }
+        } else {
+            self.rank().cmp(&other.rank())
+        }
+    }
+
+    /// Where values of this kind are ordered among the other kinds, see [`Variant::total_cmp`].
+    fn rank(&self) -> u8 {
+        match self {
+            Variant::Bool(_) => 0,
+            Variant::Int(_) => 1,
+            Variant::Long(_) => 2,
+            Variant::Uint(_) => 3,
+            Variant::Port(_) => 4,
+            Variant::Float(_) => 5,
+            Variant::Double(_) => 6,
+            Variant::Duration(_) => 7,
+            Variant::Bytes(_) => 8,
+            Variant::DateTime(_) => 9,
+            Variant::Socket(_) => 10,
+            Variant::HostPort { .. } => 11,
+            Variant::Path(_) => 12,
+            Variant::String(_) => 13,
+            Variant::List(_) => 14,
+            Variant::Pair(..) => 15,
+            #[cfg(feature = "regex")]
+            Variant::Regex(_) => 16,
+            Variant::Stdin => 17,
+        }
+    }
+
+    /// Maps from Variant to Option\<bool\>
+    pub fn as_bool(&self) -> Option<bool> {
+        if let Variant::Bool(b) = self {
+            Some(*b)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<i32\>
+    pub fn as_int(&self) -> Option<i32> {
+        if let Variant::Int(i) = self {
+            Some(*i)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<i64\>
+    /// Ints are widened, so callers don't need to match both.
+    pub fn as_long(&self) -> Option<i64> {
+        match self {
+            Variant::Int(i) => Some(i64::from(*i)),
+            Variant::Long(l) => Some(*l),
+            _ => None,
+        }
+    }
+
+    /// Maps from Variant to Option\<u64\>
+    pub fn as_uint(&self) -> Option<u64> {
+        if let Variant::Uint(u) = self {
+            Some(*u)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<u16\>
+    /// In-range integers are accepted too, and sockets and host:port pairs give their port.
+    pub fn as_port(&self) -> Option<u16> {
+        match self {
+            Variant::Int(i) => u16::try_from(*i).ok(),
+            Variant::Long(l) => u16::try_from(*l).ok(),
+            Variant::Uint(u) => u16::try_from(*u).ok(),
+            Variant::Port(p) => Some(*p),
+            Variant::Socket(s) => Some(s.port()),
+            Variant::HostPort { port, .. } => Some(*port),
+            _ => None,
+        }
+    }
+
+    /// Maps from Variant to Option\<f32\>
+    pub fn as_float(&self) -> Option<f32> {
+        if let Variant::Float(f) = self {
+            Some(*f)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<f64\>
+    /// Floats and Ints are widened losslessly.
+    pub fn as_double(&self) -> Option<f64> {
+        match self {
+            Variant::Int(i) => Some(f64::from(*i)),
+            Variant::Float(f) => Some(f64::from(*f)),
+            Variant::Double(d) => Some(*d),
+            _ => None,
+        }
+    }
+
+    /// Maps from Variant to Option\<Duration\>
+    pub fn as_duration(&self) -> Option<Duration> {
+        if let Variant::Duration(d) = self {
+            Some(*d)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<u64\>, for byte sizes
+    pub fn as_bytes(&self) -> Option<u64> {
+        if let Variant::Bytes(b) = self {
+            Some(*b)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<SystemTime\>
+    pub fn as_datetime(&self) -> Option<SystemTime> {
+        if let Variant::DateTime(t) = self {
+            Some(*t)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<SocketAddr\>
+    pub fn as_socket(&self) -> Option<SocketAddr> {
+        if let Variant::Socket(s) = self {
+            Some(*s)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<(&str, u16)\>
+    pub fn as_host_port(&self) -> Option<(&str, u16)> {
+        if let Variant::HostPort { host, port } = self {
+            Some((host, *port))
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<&PathBuf\>
+    pub fn as_path(&self) -> Option<&PathBuf> {
+        if let Variant::Path(p) = self {
+            Some(p)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<Pathbuf\>
+    pub fn into_path(self) -> Option<PathBuf> {
+        if let Variant::Path(p) = self {
+            Some(p)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<&str\>
+    pub fn as_string(&self) -> Option<&str> {
+        if let Variant::String(s) = self {
+            Some(s)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<String\>
+    pub fn into_string(self) -> Option<String> {
+        if let Variant::String(s) = self {
+            Some(s)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<&[Variant]\>
+    pub fn as_list(&self) -> Option<&[Variant]> {
+        if let Variant::List(l) = self {
+            Some(l)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<Vec\<Variant\>\>
+    pub fn into_list(self) -> Option<Vec<Variant>> {
+        if let Variant::List(l) = self {
+            Some(l)
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<(&str, &Variant)\>
+    pub fn as_pair(&self) -> Option<(&str, &Variant)> {
+        if let Variant::Pair(key, value) = self {
+            Some((key, value))
+        } else {
+            None
+        }
+    }
+
+    /// Maps from Variant to Option\<&regex::Regex\>
+    #[cfg(feature = "regex")]
+    pub fn as_regex(&self) -> Option<&regex::Regex> {
+        if let Variant::Regex(r) = self {
+            Some(r)
+        } else {
+            None
+        }
+    }
+
+    /// Converts any number to an [`f32`], unlike [`Variant::as_float`].
+    /// Integers and doubles are rounded to the nearest float, so this may lose precision.
+    /// Anything that isn't a number returns None.
+    #[must_use]
+    pub fn coerce_float(&self) -> Option<f32> {
+        match self {
+            Variant::Int(i) => Some(*i as f32),
+            Variant::Long(l) => Some(*l as f32),
+            Variant::Uint(u) => Some(*u as f32),
+            Variant::Port(p) => Some(f32::from(*p)),
+            Variant::Float(f) => Some(*f),
+            Variant::Double(d) => Some(*d as f32),
+            _ => None,
+        }
+    }
+
+    /// Converts any number to an [`i32`], unlike [`Variant::as_int`].
+    /// Floats and doubles are only converted if they are whole numbers, so `2.0` becomes `2`, but `2.5` returns None.
+    /// Numbers outside of the range of an [`i32`], and anything that isn't a number, return None.
+    #[must_use]
+    pub fn coerce_int(&self) -> Option<i32> {
+        let whole = |d: f64| {
+            (d.fract() == 0.0 && d >= f64::from(i32::MIN) && d <= f64::from(i32::MAX))
+                .then_some(d as i32)
+        };
+        match self {
+            Variant::Int(i) => Some(*i),
+            Variant::Long(l) => i32::try_from(*l).ok(),
+            Variant::Uint(u) => i32::try_from(*u).ok(),
+            Variant::Port(p) => Some(i32::from(*p)),
+            Variant::Float(f) => whole(f64::from(*f)),
+            Variant::Double(d) => whole(*d),
+            _ => None,
+        }
+    }
+
+    /// Converts any value to a [`String`] using its Display implementation, unlike [`Variant::as_string`].
+    #[must_use]
+    pub fn coerce_string(&self) -> String {
+        self.to_string()
+    }
+
+    /// Converts bools and the integers `0` and `1` to a [`bool`], unlike [`Variant::as_bool`].
+    /// Any other value returns None.
+    #[must_use]
+    pub fn coerce_bool(&self) -> Option<bool> {
+        match self {
+            Variant::Bool(b) => Some(*b),
+            Variant::Int(0) | Variant::Long(0) | Variant::Uint(0) | Variant::Port(0) => Some(false),
+            Variant::Int(1) | Variant::Long(1) | Variant::Uint(1) | Variant::Port(1) => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// The error when converting a [`Variant`] into a type it doesn't hold, see the [`TryFrom`] implementations on [`Variant`].
+///
+/// Conversions are strict, so a [`Variant::Int`] can't become an [`f32`], even though [`Variant::as_double`] would widen it.
+///
+/// ```
+/// # use flagged_cl_args::{Variant, VariantTypeError};
+/// let threads: i32 = Variant::Int(4).try_into().unwrap();
+/// assert_eq!(threads, 4);
+/// let ratio: Result<f32, _> = Variant::Int(4).try_into();
+/// assert_eq!(ratio, Err(VariantTypeError { expected: "float", found: "int" }));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct VariantTypeError {
+    /// The name of the type that was asked for
+    pub expected: &'static str,
+    /// The name of the type the value actually was
+    pub found: &'static str,
+}
+
+impl std::error::Error for VariantTypeError {}
+
+impl Display for VariantTypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected {}, found {}", self.expected, self.found)
+    }
+}
+
+impl VariantTypeError {
+    fn new(expected: &'static str, found: &Variant) -> VariantTypeError {
+        VariantTypeError {
+            expected,
+            found: found.kind().name(),
+        }
+    }
+}
+
+impl TryFrom<Variant> for bool {
+    type Error = VariantTypeError;
+
+    fn try_from(value: Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::Bool(inner) => Ok(inner),
+            _ => Err(VariantTypeError::new("bool", &value)),
+        }
+    }
+}
+
+impl TryFrom<&Variant> for bool {
+    type Error = VariantTypeError;
+
+    fn try_from(value: &Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::Bool(inner) => Ok(*inner),
+            _ => Err(VariantTypeError::new("bool", value)),
+        }
+    }
+}
+
+impl TryFrom<Variant> for i32 {
+    type Error = VariantTypeError;
+
+    fn try_from(value: Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::Int(inner) => Ok(inner),
+            _ => Err(VariantTypeError::new("int", &value)),
+        }
+    }
+}
+
+impl TryFrom<&Variant> for i32 {
+    type Error = VariantTypeError;
+
+    fn try_from(value: &Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::Int(inner) => Ok(*inner),
+            _ => Err(VariantTypeError::new("int", value)),
+        }
+    }
+}
+
+impl TryFrom<Variant> for f32 {
+    type Error = VariantTypeError;
+
+    fn try_from(value: Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::Float(inner) => Ok(inner),
+            _ => Err(VariantTypeError::new("float", &value)),
+        }
+    }
+}
+
+impl TryFrom<&Variant> for f32 {
+    type Error = VariantTypeError;
+
+    fn try_from(value: &Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::Float(inner) => Ok(*inner),
+            _ => Err(VariantTypeError::new("float", value)),
+        }
+    }
+}
+
+impl TryFrom<Variant> for SocketAddr {
+    type Error = VariantTypeError;
+
+    fn try_from(value: Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::Socket(inner) => Ok(inner),
+            _ => Err(VariantTypeError::new("socket", &value)),
+        }
+    }
+}
+
+impl TryFrom<&Variant> for SocketAddr {
+    type Error = VariantTypeError;
+
+    fn try_from(value: &Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::Socket(inner) => Ok(*inner),
+            _ => Err(VariantTypeError::new("socket", value)),
+        }
+    }
+}
+
+impl TryFrom<Variant> for PathBuf {
+    type Error = VariantTypeError;
+
+    fn try_from(value: Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::Path(inner) => Ok(inner),
+            _ => Err(VariantTypeError::new("path", &value)),
+        }
+    }
+}
+
+impl TryFrom<&Variant> for PathBuf {
+    type Error = VariantTypeError;
+
+    fn try_from(value: &Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::Path(inner) => Ok(inner.clone()),
+            _ => Err(VariantTypeError::new("path", value)),
+        }
+    }
+}
+
+impl TryFrom<Variant> for String {
+    type Error = VariantTypeError;
+
+    fn try_from(value: Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::String(inner) => Ok(inner),
+            _ => Err(VariantTypeError::new("string", &value)),
+        }
+    }
+}
+
+impl TryFrom<&Variant> for String {
+    type Error = VariantTypeError;
+
+    fn try_from(value: &Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::String(inner) => Ok(inner.clone()),
+            _ => Err(VariantTypeError::new("string", value)),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Variant> for &'a Path {
+    type Error = VariantTypeError;
+
+    fn try_from(value: &'a Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::Path(inner) => Ok(inner.as_path()),
+            _ => Err(VariantTypeError::new("path", value)),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Variant> for &'a str {
+    type Error = VariantTypeError;
+
+    fn try_from(value: &'a Variant) -> Result<Self, Self::Error> {
+        match value {
+            Variant::String(inner) => Ok(inner.as_str()),
+            _ => Err(VariantTypeError::new("string", value)),
+        }
+    }
+}
+
+/// Converts a [`Variant`] into another type, see [`crate::Args::get`].
+///
+/// Implement this for your own types to get them straight out of [`crate::Args`], like values produced by [`VariantFlag::custom`].
+///
+/// ```
+/// # use flagged_cl_args::{FromVariant, Variant};
+/// struct Threads(u8);
+///
+/// impl FromVariant<'_> for Threads {
+///     fn from_variant(value: &Variant) -> Option<Self> {
+///         value.as_int().and_then(|i| u8::try_from(i).ok()).map(Threads)
+///     }
+/// }
+///
+/// assert_eq!(Threads::from_variant(&Variant::Int(8)).map(|t| t.0), Some(8));
+/// assert!(Threads::from_variant(&Variant::Int(1000)).is_none());
+/// ```
+pub trait FromVariant<'a>: Sized {
+    /// Converts `value`, returning None if it is a different type.
+    fn from_variant(value: &'a Variant) -> Option<Self>;
+}
+
+impl FromVariant<'_> for bool {
+    fn from_variant(value: &Variant) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+impl FromVariant<'_> for i32 {
+    fn from_variant(value: &Variant) -> Option<Self> {
+        value.as_int()
+    }
+}
+
+impl FromVariant<'_> for i64 {
+    fn from_variant(value: &Variant) -> Option<Self> {
+        value.as_long()
+    }
+}
+
+impl FromVariant<'_> for u64 {
+    fn from_variant(value: &Variant) -> Option<Self> {
+        value.as_uint()
+    }
+}
+
+impl FromVariant<'_> for u16 {
+    fn from_variant(value: &Variant) -> Option<Self> {
+        value.as_port()
+    }
+}
+
+impl FromVariant<'_> for f32 {
+    fn from_variant(value: &Variant) -> Option<Self> {
+        value.as_float()
+    }
+}
+
+impl FromVariant<'_> for f64 {
+    fn from_variant(value: &Variant) -> Option<Self> {
+        value.as_double()
+    }
+}
+
+impl FromVariant<'_> for Duration {
+    fn from_variant(value: &Variant) -> Option<Self> {
+        value.as_duration()
+    }
+}
+
+impl FromVariant<'_> for SystemTime {
+    fn from_variant(value: &Variant) -> Option<Self> {
+        value.as_datetime()
+    }
+}
+
+impl FromVariant<'_> for SocketAddr {
+    fn from_variant(value: &Variant) -> Option<Self> {
+        value.as_socket()
+    }
+}
+
+impl FromVariant<'_> for PathBuf {
+    fn from_variant(value: &Variant) -> Option<Self> {
+        value.as_path().cloned()
+    }
+}
+
+impl<'a> FromVariant<'a> for &'a Path {
+    fn from_variant(value: &'a Variant) -> Option<Self> {
+        value.as_path().map(PathBuf::as_path)
+    }
+}
+
+impl FromVariant<'_> for String {
+    fn from_variant(value: &Variant) -> Option<Self> {
+        value.as_string().map(str::to_string)
+    }
+}
+
+impl<'a> FromVariant<'a> for &'a str {
+    fn from_variant(value: &'a Variant) -> Option<Self> {
+        value.as_string()
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Variant::Bool(inner) => inner.fmt(f),
+            Variant::Int(inner) => inner.fmt(f),
+            Variant::Long(inner) => inner.fmt(f),
+            Variant::Uint(inner) => inner.fmt(f),
+            Variant::Port(inner) => inner.fmt(f),
+            Variant::Float(inner) => inner.fmt(f),
+            Variant::Double(inner) => inner.fmt(f),
+            Variant::Duration(inner) => format_duration(inner, f),
+            Variant::Bytes(inner) => inner.fmt(f),
+            Variant::DateTime(inner) => format_datetime(inner, f),
+            Variant::Socket(inner) => inner.fmt(f),
+            Variant::HostPort { host, port } if host.contains(':') => write!(f, "[{host}]:{port}"),
+            Variant::HostPort { host, port } => write!(f, "{host}:{port}"),
+            Variant::Path(inner) => inner.to_string_lossy().fmt(f),
+            Variant::String(inner) => inner.fmt(f),
+            Variant::List(inner) => {
+                for (index, item) in inner.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    item.fmt(f)?;
+                }
+                Ok(())
+            }
+            Variant::Pair(key, value) => write!(f, "{key}={value}"),
+            #[cfg(feature = "regex")]
+            Variant::Regex(inner) => inner.as_str().fmt(f),
+            Variant::Stdin => write!(f, "-"),
+        }
+    }
+}
+
+impl Display for VariantFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_unit() {
+            return write!(f, "flag");
+        }
+        if self.is_count() {
+            return write!(f, "count");
+        }
+        fn inner(
+            first: &mut Option<&str>,
+            val: &str,
+            f: &mut std::fmt::Formatter<'_>,
+        ) -> std::fmt::Result {
+            let res = write!(f, "{}{}", first.unwrap_or(""), val);
+            *first = Some("|");
+            res
+        }
+        let mut separator = None;
+        if let Some(custom) = &self.custom {
+            inner(&mut separator, &custom.name, f)?;
+        }
+        let mut choices = self.choices.iter().filter(|_| self.choice_allowed());
+        for kind in self.kinds() {
+            if kind > VariantKind::HostPort {
+                // Choices are tried after host:port
+                for choice in choices.by_ref() {
+                    inner(&mut separator, choice, f)?;
+                }
+            }
+            match (kind, self.element.as_deref()) {
+                (VariantKind::List, Some(element)) => inner(
+                    &mut separator,
+                    &format!("{element}{}...", self.delimiter),
+                    f,
+                )?,
+                (VariantKind::Pair, Some(value_type)) => {
+                    inner(&mut separator, &format!("key={value_type}"), f)?;
+                }
+                _ => inner(&mut separator, kind.name(), f)?,
+            }
+        }
+        for choice in choices {
+            inner(&mut separator, choice, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a flag back from its [`Display`] form, like `int|float|string`, ignoring case.
+/// `flag` is [`VariantFlag::new_unit`], `count` is [`VariantFlag::count`], and lists and pairs are written like `int,...` and `key=int`.
+/// Choices and custom parsers can't be written this way.
+///
+/// ```
+/// # use flagged_cl_args::{VariantFlag, VariantKind};
+/// assert_eq!("int|float|string".parse(), Ok(VariantFlag::int().or_float().or_string()));
+/// assert_eq!("Socket | PATH".parse(), Ok(VariantFlag::socket().or_path()));
+/// assert_eq!("flag".parse(), Ok(VariantFlag::new_unit()));
+/// assert_eq!("port;...".parse(), Ok(VariantFlag::list_of(VariantFlag::port()).delimiter(';')));
+///
+/// let error = "int|integer".parse::<VariantFlag>().unwrap_err();
+/// assert!(error.to_string().starts_with("Unknown type 'integer', expected flag, count, or some of bool, int, long,"));
+///
+/// // Every combination of types survives the round trip
+/// let kinds: Vec<VariantKind> = VariantFlag::all()
+///     .without(VariantFlag::list_of(VariantFlag::int()) | VariantFlag::key_value(VariantFlag::int()))
+///     .kinds()
+///     .collect();
+/// for bits in 1..1u32 << kinds.len() {
+///     let flag = kinds
+///         .iter()
+///         .enumerate()
+///         .filter(|(index, _)| bits >> index & 1 != 0)
+///         .map(|(_, kind)| kind.to_string().parse::<VariantFlag>().unwrap())
+///         .fold(VariantFlag::new_unit(), |flag, kind| flag | kind);
+///     assert_eq!(flag.to_string().parse(), Ok(flag));
+/// }
+/// ```
+impl FromStr for VariantFlag {
+    type Err = ArgumentError;
+
+    fn from_str(s: &str) -> Result<VariantFlag, ArgumentError> {
+        let names: Vec<&str> = s.split('|').map(str::trim).collect();
+        if let [name] = names[..] {
+            if name.eq_ignore_ascii_case("flag") {
+                return Ok(VariantFlag::new_unit());
+            }
+            if name.eq_ignore_ascii_case("count") {
+                return Ok(VariantFlag::count());
+            }
+        }
+        names
+            .into_iter()
+            .try_fold(VariantFlag::new_unit(), |flag, name| {
+                Ok(flag | VariantFlag::from_kind_name(name)?)
+            })
+    }
+}
+
+impl VariantFlag {
+    /// Reads a single type written like [`Display`] does, for [`FromStr`].
+    fn from_kind_name(name: &str) -> Result<VariantFlag, ArgumentError> {
+        if let Some(element) = name.strip_suffix("...")
+            && let Some(delimiter) = element.chars().next_back()
+        {
+            let element = &element[..element.len() - delimiter.len_utf8()];
+            return Ok(VariantFlag::list_of(element.parse()?).delimiter(delimiter));
+        }
+        if let Some((key, value_type)) = name.split_once('=')
+            && key.eq_ignore_ascii_case("key")
+        {
+            return Ok(VariantFlag::key_value(value_type.parse()?));
+        }
+        let kinds = || {
+            VariantKind::ALL
+                .iter()
+                .filter(|kind| !matches!(kind, VariantKind::List | VariantKind::Pair))
+        };
+        match kinds().find(|kind| kind.name().eq_ignore_ascii_case(name)) {
+            Some(kind) => Ok(VariantFlag::from_bits(1 << kind.bit())),
+            None => {
+                let names: Vec<&str> = kinds().map(|kind| kind.name()).collect();
+                Err(ArgumentError::new(&format!(
+                    "Unknown type '{name}', expected flag, count, or some of {} separated by |, \
+                     or a list or pair like int,... or key=int",
+                    names.join(", ")
+                )))
+            }
+        }
+    }
+}
+
+const DURATION_UNITS: [(&str, Duration); 4] = [
+    ("ms", Duration::from_millis(1)),
+    ("s", Duration::from_secs(1)),
+    ("m", Duration::from_secs(60)),
+    ("h", Duration::from_secs(60 * 60)),
+];
+
+/// Formats a range like Rust does, such as `1..=10`, `1..`, or `..10`.
+/// Rust has no way to write an excluded start, so it is marked like `0 (excluded)..=1`.
+fn format_range<T: Display>(start: Bound<T>, end: Bound<T>) -> String {
+    let start = match start {
+        Bound::Included(start) => start.to_string(),
+        Bound::Excluded(start) => format!("{start} (excluded)"),
+        Bound::Unbounded => String::new(),
+    };
+    let end = match end {
+        Bound::Included(end) => format!("={end}"),
+        Bound::Excluded(end) => end.to_string(),
+        Bound::Unbounded => String::new(),
+    };
+    format!("{start}..{end}")
+}
+
+/// Parses the spellings accepted by [`VariantFlag::bool_lenient`].
+fn parse_lenient_bool(raw: &str) -> Option<bool> {
+    ["true", "yes", "on", "1"]
+        .iter()
+        .zip(["false", "no", "off", "0"])
+        .find_map(|(yes, no)| {
+            if raw.eq_ignore_ascii_case(yes) {
+                Some(true)
+            } else if raw.eq_ignore_ascii_case(no) {
+                Some(false)
+            } else {
+                None
+            }
+        })
+}
+
+/// Parses durations like `250ms`, `30s`, or `1h30m`.
+/// The error describes what went wrong and lists the accepted units.
+fn parse_duration(raw: &str) -> Result<Duration, String> {
+    let expected = || {
+        let units: Vec<&str> = DURATION_UNITS.iter().map(|(unit, _)| *unit).collect();
+        format!("expected a number followed by one of {}", units.join(", "))
+    };
+    if raw.is_empty() {
+        return Err(format!("'' is not a valid duration, {}", expected()));
+    }
+    let too_long = || format!("'{raw}' is too long to be represented as a duration");
+    // Every unit is a whole number of milliseconds, and this has room for any amount of any of them
+    let mut total_millis: u128 = 0;
+    let mut rest = raw;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount = match u64::from_str(&rest[..digits]) {
+            Ok(amount) => amount,
+            Err(_) if digits > 0 => return Err(too_long()),
+            Err(_) => return Err(format!("'{raw}' is not a valid duration, {}", expected())),
+        };
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit = &rest[..unit_len];
+        let scale = DURATION_UNITS
+            .iter()
+            .find(|(name, _)| *name == unit)
+            .map(|(_, scale)| *scale)
+            .ok_or_else(|| {
+                if unit.is_empty() {
+                    format!("'{raw}' is missing a unit, {}", expected())
+                } else {
+                    format!("'{unit}' is not a valid duration unit, {}", expected())
+                }
+            })?;
+        total_millis = u128::from(amount)
+            .checked_mul(scale.as_millis())
+            .and_then(|part| total_millis.checked_add(part))
+            .ok_or_else(too_long)?;
+        rest = &rest[unit_len..];
+    }
+    let secs = u64::try_from(total_millis / 1000).map_err(|_| too_long())?;
+    let nanos = u32::try_from(total_millis % 1000).expect("less than 1000") * 1_000_000;
+    Ok(Duration::new(secs, nanos))
+}
+
+/// Writes a duration in the same form [`parse_duration`] accepts, such as `1h30m`.
+fn format_duration(duration: &Duration, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if !duration.subsec_nanos().is_multiple_of(1_000_000) {
+        // Finer than the units we parse, fall back to the standard representation
+        return write!(f, "{duration:?}");
+    }
+    if duration.is_zero() {
+        return write!(f, "0s");
+    }
+    let mut remaining = duration.as_millis();
+    for (unit, scale) in DURATION_UNITS.iter().rev() {
+        let amount = remaining / scale.as_millis();
+        if amount > 0 {
+            write!(f, "{amount}{unit}")?;
+            remaining %= scale.as_millis();
+        }
+    }
+    Ok(())
+}
+
+/// Splits `host:port` or `[ipv6]:port` without resolving the host.
+fn parse_host_port(raw: &str) -> Result<(String, u16), String> {
+    let (host, port) = if let Some(bracketed) = raw.strip_prefix('[') {
+        let (host, rest) = bracketed
+            .split_once(']')
+            .ok_or_else(|| format!("'{raw}' is missing a closing ']'"))?;
+        let port = rest
+            .strip_prefix(':')
+            .ok_or_else(|| format!("'{raw}' is missing a port, expected [host]:port"))?;
+        (host, port)
+    } else {
+        let (host, port) = raw
+            .rsplit_once(':')
+            .ok_or_else(|| format!("'{raw}' is missing a port, expected host:port"))?;
+        if host.contains(':') {
+            return Err(format!(
+                "'{raw}' is ambiguous, IPv6 hosts must be written as [host]:port"
+            ));
+        }
+        (host, port)
+    };
+    if host.is_empty() {
+        return Err(format!("'{raw}' is missing a host"));
+    }
+    let port = u16::from_str(port)
+        .map_err(|_| format!("'{port}' in '{raw}' is not a valid port, expected 0-65535"))?;
+    Ok((host.to_string(), port))
+}
+
+const BYTE_SIZE_UNITS: [(&str, u64); 9] = [
+    ("B", 1),
+    ("KB", 1_000),
+    ("MB", 1_000_000),
+    ("GB", 1_000_000_000),
+    ("TB", 1_000_000_000_000),
+    ("KiB", 1 << 10),
+    ("MiB", 1 << 20),
+    ("GiB", 1 << 30),
+    ("TiB", 1 << 40),
+];
+
+/// Parses byte sizes like `512`, `10MB`, or `1.5GiB`, rounding down to whole bytes.
+/// The error lists the recognized suffixes.
+fn parse_byte_size(raw: &str) -> Result<u64, String> {
+    let split = raw
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(raw.len());
+    let (number, suffix) = raw.split_at(split);
+    let scale = if suffix.is_empty() {
+        1
+    } else {
+        BYTE_SIZE_UNITS
+            .iter()
+            .find(|(unit, _)| unit.eq_ignore_ascii_case(suffix))
+            .map(|(_, scale)| *scale)
+            .ok_or_else(|| {
+                let units: Vec<&str> = BYTE_SIZE_UNITS.iter().map(|(unit, _)| *unit).collect();
+                format!(
+                    "'{suffix}' is not a valid byte size suffix, expected one of {}",
+                    units.join(", ")
+                )
+            })?
+    };
+    let too_large = || format!("'{raw}' is too large to be represented as a byte size");
+    if let Ok(whole) = u64::from_str(number) {
+        whole.checked_mul(scale).ok_or_else(too_large)
+    } else if let Ok(fractional) = f64::from_str(number) {
+        let bytes = (fractional * scale as f64).floor();
+        if bytes < u64::MAX as f64 {
+            Ok(bytes as u64)
+        } else {
+            Err(too_large())
+        }
+    } else {
+        Err(format!("'{raw}' is not a valid byte size"))
+    }
+}
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of [`days_from_civil`], returning (year, month, day).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parses RFC 3339 timestamps, or a bare date as midnight UTC.
+fn parse_datetime(raw: &str) -> Result<SystemTime, String> {
+    let invalid = || {
+        format!(
+            "'{raw}' is not a valid date/time, expected RFC 3339 like 2024-06-01T12:00:00Z or a date like 2024-06-01"
+        )
+    };
+    let number = |digits: &str| -> Result<i64, String> {
+        if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+            i64::from_str(digits).map_err(|_| invalid())
+        } else {
+            Err(invalid())
+        }
+    };
+    let field = |start: usize, end: usize| raw.get(start..end).ok_or_else(invalid);
+
+    if !raw.is_ascii() || raw.len() < 10 || raw.as_bytes()[4] != b'-' || raw.as_bytes()[7] != b'-' {
+        return Err(invalid());
+    }
+    let year = number(field(0, 4)?)?;
+    let month = number(field(5, 7)?)?;
+    let day = number(field(8, 10)?)?;
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return Err(format!("'{raw}' is not a valid calendar date"));
+    }
+    let mut seconds = days_from_civil(year, month, day) * SECONDS_PER_DAY;
+    let mut nanos = 0;
+
+    if raw.len() > 10 {
+        let time = raw.get(11..).ok_or_else(invalid)?;
+        if !matches!(raw.as_bytes()[10], b'T' | b't' | b' ')
+            || time.len() < 8
+            || time.as_bytes()[2] != b':'
+            || time.as_bytes()[5] != b':'
+        {
+            return Err(invalid());
+        }
+        let hour = number(&time[0..2])?;
+        let minute = number(&time[3..5])?;
+        let second = number(&time[6..8])?;
+        // RFC 3339 allows a leap second of 60
+        if hour > 23 || minute > 59 || second > 60 {
+            return Err(format!("'{raw}' is not a valid time of day"));
+        }
+        seconds += hour * 3600 + minute * 60 + second;
+
+        let mut rest = &time[8..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let digits = fraction
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(fraction.len());
+            if digits == 0 {
+                return Err(invalid());
+            }
+            // Finer than nanoseconds is dropped
+            let padded = format!("{:0<9}", &fraction[..digits.min(9)]);
+            nanos = u32::from_str(&padded).map_err(|_| invalid())?;
+            rest = &fraction[digits..];
+        }
+        match rest {
+            "Z" | "z" => {}
+            offset if offset.len() == 6 && offset.as_bytes()[3] == b':' => {
+                let sign = match offset.as_bytes()[0] {
+                    b'+' => 1,
+                    b'-' => -1,
+                    _ => return Err(invalid()),
+                };
+                let offset_hours = number(&offset[1..3])?;
+                let offset_minutes = number(&offset[4..6])?;
+                if offset_hours > 23 || offset_minutes > 59 {
+                    return Err(format!("'{raw}' has an invalid UTC offset"));
+                }
+                seconds -= sign * (offset_hours * 3600 + offset_minutes * 60);
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    let since_epoch = Duration::new(seconds.unsigned_abs(), 0);
+    let time = if seconds >= 0 {
+        UNIX_EPOCH.checked_add(since_epoch)
+    } else {
+        UNIX_EPOCH.checked_sub(since_epoch)
+    };
+    time.and_then(|time| time.checked_add(Duration::from_nanos(u64::from(nanos))))
+        .ok_or_else(|| format!("'{raw}' is out of the supported range"))
+}
+
+/// Writes a time as an RFC 3339 timestamp in UTC, such as `2024-06-01T12:00:00Z`.
+fn format_datetime(time: &SystemTime, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let (seconds, nanos) = match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => (after.as_secs() as i64, after.subsec_nanos()),
+        Err(before) => {
+            let before = before.duration();
+            match before.subsec_nanos() {
+                0 => (-(before.as_secs() as i64), 0),
+                nanos => (-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+            }
+        }
+    };
+    let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+    let time_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+    write!(
+        f,
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+        time_of_day / 3600,
+        time_of_day / 60 % 60,
+        time_of_day % 60
+    )?;
+    if nanos != 0 {
+        let fraction = format!("{nanos:09}");
+        write!(f, ".{}", fraction.trim_end_matches('0'))?;
     }
+    write!(f, "Z")
 }
@@ -0,0 +1,139 @@
+//! Loads the same definitions from TOML and JSON fixtures, checking them against a golden help text and a command line using every flag.
+
+use flagged_cl_args::{
+    Args, FlagDefinition, ParserConfig, PositionalDefinition, VariantFlag, definitions_from_json,
+    definitions_from_toml, validate_definitions,
+};
+
+const TOML: &str = include_str!("fixtures/definitions.toml");
+const JSON: &str = include_str!("fixtures/definitions.json");
+const HELP: &str = include_str!("fixtures/definitions.help.txt");
+
+fn help_text(positional: &[VariantFlag], flags: &[FlagDefinition]) -> String {
+    let positional: Vec<PositionalDefinition> =
+        positional.iter().cloned().map(Into::into).collect();
+    Args::help_text("my-tool", &ParserConfig::default(), &positional, flags)
+}
+
+#[test]
+fn toml_matches_golden_help() {
+    let (positional, flags) = definitions_from_toml(TOML).unwrap();
+    validate_definitions(&flags).unwrap();
+    assert_eq!(help_text(&positional, &flags), HELP);
+}
+
+#[test]
+fn json_matches_toml() {
+    let (toml_positional, toml_flags) = definitions_from_toml(TOML).unwrap();
+    let (json_positional, json_flags) = definitions_from_json(JSON).unwrap();
+    assert_eq!(json_positional, toml_positional);
+    assert_eq!(format!("{json_flags:?}"), format!("{toml_flags:?}"));
+    assert_eq!(help_text(&json_positional, &json_flags), HELP);
+}
+
+#[test]
+fn parses_every_kind() {
+    let (positional, flags) = definitions_from_toml(TOML).unwrap();
+    let args = Args::from_slice(
+        &[
+            "my-tool",
+            "in.txt",
+            "1.5",
+            "rest",
+            "--yes",
+            "-vv",
+            "--color",
+            "false",
+            "-t",
+            "8",
+            "--seed=-5000000000",
+            "--limit",
+            "18446744073709551615",
+            "--port",
+            "443",
+            "--ratio",
+            "0.5",
+            "--precise",
+            "0.1",
+            "--timeout",
+            "1h30m",
+            "--cache",
+            "1KiB",
+            "--since",
+            "2024-06-01",
+            "--listen",
+            "127.0.0.1:80",
+            "--upstream",
+            "example.com:443",
+            "--tags",
+            "a,b",
+            "--define",
+            "level=3",
+            "--filter",
+            "^a+$",
+            "--out",
+            "out.txt",
+        ],
+        &positional,
+        &flags,
+    )
+    .unwrap();
+    let rendered: Vec<String> = flags
+        .iter()
+        .map(|flag| format!("{}={}", flag.name, args.get_named(&flag.name).unwrap()))
+        .collect();
+    assert_eq!(
+        rendered,
+        [
+            "force=true",
+            "verbose=2",
+            "color=false",
+            "threads=8",
+            "seed=-5000000000",
+            "limit=18446744073709551615",
+            "port=443",
+            "ratio=0.5",
+            "precise=0.1",
+            "timeout=1h30m",
+            "cache=1024",
+            "since=2024-06-01T00:00:00Z",
+            "listen=127.0.0.1:80",
+            "upstream=example.com:443",
+            "tags=a,b",
+            "define=level=3",
+            "filter=^a+$",
+            "out=out.txt",
+        ]
+    );
+    let rendered: Vec<String> = (0..3)
+        .map(|index| args.get_positional(index).unwrap().to_string())
+        .collect();
+    assert_eq!(rendered, ["in.txt", "1.5", "rest"]);
+}
+
+#[test]
+fn errors_name_the_key() {
+    let error =
+        definitions_from_toml(&TOML.replace("type = \"uint\"", "type = \"unsigned\"")).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("Definition key flags[5].type: Unknown type 'unsigned'")
+    );
+
+    let error = definitions_from_json(&JSON.replace("\"30s\"", "\"soon\"")).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("Definition key flags[9].default: 'soon' is not a valid duration")
+    );
+
+    // Problems that validate_definitions finds are reported too
+    let error =
+        definitions_from_toml(&TOML.replace("abbreviation = \"t\"", "abbreviation = \"v\""))
+            .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The abbreviation -v of --threads is already used by --verbose"
+    );
+}
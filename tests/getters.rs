@@ -0,0 +1,120 @@
+//! Checks the typed getters on parsed arguments, for values that are there, missing, or a different type.
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use flagged_cl_args::{Args, FlagDefinition, VariantFlag};
+
+fn parsed() -> Args {
+    let flags = [
+        FlagDefinition::new("verbose", VariantFlag::new_unit()),
+        FlagDefinition::new("threads", VariantFlag::int()),
+        FlagDefinition::new("ratio", VariantFlag::float()),
+        FlagDefinition::new("listen", VariantFlag::socket()),
+        FlagDefinition::new("output", VariantFlag::path()),
+        FlagDefinition::new("name", VariantFlag::string()),
+        FlagDefinition::new("missing", VariantFlag::int()),
+    ];
+    Args::from_slice(
+        &[
+            "my-tool",
+            "4",
+            "in.txt",
+            "--verbose",
+            "--threads",
+            "8",
+            "--ratio",
+            "0.5",
+            "--listen",
+            "127.0.0.1:80",
+            "--output",
+            "out.txt",
+            "--name",
+            "12",
+        ],
+        &[VariantFlag::int(), VariantFlag::path()],
+        &flags,
+    )
+    .unwrap()
+}
+
+#[test]
+fn typed_getters_find_their_type() {
+    let args = parsed();
+    assert_eq!(args.get_named_bool("verbose"), Some(true));
+    assert_eq!(args.get_named_int("threads"), Some(8));
+    assert_eq!(args.get_named_float("ratio"), Some(0.5));
+    assert_eq!(
+        args.get_named_socket("listen"),
+        Some(SocketAddr::from(([127, 0, 0, 1], 80)))
+    );
+    assert_eq!(
+        args.get_named_path("output"),
+        Some(&PathBuf::from("out.txt"))
+    );
+    assert_eq!(args.get_named_string("name"), Some("12"));
+    assert_eq!(args.get_positional_int(0), Some(4));
+    assert_eq!(args.get_positional_path(1), Some(&PathBuf::from("in.txt")));
+}
+
+#[test]
+fn typed_getters_are_none_for_another_type() {
+    let args = parsed();
+    // A string that looks like a number is still a string
+    assert_eq!(args.get_named_int("name"), None);
+    assert_eq!(args.get_named_string("threads"), None);
+    assert_eq!(args.get_named_bool("threads"), None);
+    assert_eq!(args.get_named_path("name"), None);
+    assert_eq!(args.get_named_socket("output"), None);
+    assert_eq!(args.get_positional_path(0), None);
+    assert_eq!(args.get_positional_int(1), None);
+}
+
+#[test]
+fn typed_getters_are_none_when_absent() {
+    let args = parsed();
+    assert_eq!(args.get_named_int("missing"), None);
+    assert_eq!(args.get_named_int("not-a-flag"), None);
+    assert_eq!(args.get_positional_int(2), None);
+    assert_eq!(args.get_positional_string(2), None);
+}
+
+#[test]
+fn defaults_fill_in_for_absent_and_mismatched_values() {
+    let args = parsed();
+    // Present, so the default is ignored
+    assert_eq!(args.get_named_int_or("threads", 4), 8);
+    assert_eq!(
+        args.get_named_path_or("output", Path::new("-")),
+        Path::new("out.txt")
+    );
+    assert_eq!(args.get_positional_int_or(0, 1), 4);
+    // Absent
+    assert_eq!(args.get_named_int_or("missing", 4), 4);
+    assert!(!args.get_named_bool_or("quiet", false));
+    assert_eq!(args.get_positional_string_or(2, "none"), "none");
+    // A different type
+    assert_eq!(args.get_named_int_or("name", 4), 4);
+    assert_eq!(args.get_named_string_or("threads", "none"), "none");
+    assert_eq!(
+        args.get_positional_path_or(0, Path::new("-")),
+        Path::new("-")
+    );
+}
+
+#[test]
+fn try_getters_tell_absent_from_mismatched() {
+    let args = parsed();
+    assert_eq!(args.try_get_named_int("threads").unwrap(), Some(8));
+    assert_eq!(args.try_get_named_string("name").unwrap(), Some("12"));
+    assert_eq!(args.try_get_named_int("missing").unwrap(), None);
+    assert_eq!(args.try_get_named_path("not-a-flag").unwrap(), None);
+    assert_eq!(
+        args.try_get_named_int("name").unwrap_err().to_string(),
+        "--name is 12, which is not an int"
+    );
+    assert_eq!(
+        args.try_get_named_bool("output").unwrap_err().to_string(),
+        "--output is out.txt, which is not a bool"
+    );
+}
@@ -0,0 +1,310 @@
+//! Checks the layout of help text, and which flags ask for help or the version.
+
+use flagged_cl_args::{
+    Args, FlagDefinition, ParseOutcome, Parser, ParserConfig, PositionalDefinition, ReservedNames,
+    Variant, VariantFlag,
+};
+
+fn input() -> PositionalDefinition {
+    let mut input = PositionalDefinition::from(VariantFlag::path()).name("input");
+    input.help = Some("The file to read".to_string());
+    input
+}
+
+#[test]
+fn columns_line_up_across_tables() {
+    let flags = [
+        FlagDefinition::new("verbose", VariantFlag::new_unit())
+            .abbrev('v')
+            .help("Print more"),
+        FlagDefinition::new("output-directory", VariantFlag::path()).help("Where to write"),
+    ];
+    assert_eq!(
+        Args::help_text("my-tool", &ParserConfig::default(), &[input()], &flags),
+        "\
+usage: my-tool <input> [-v] [--output-directory <path>]
+
+Arguments:
+  <input>                        The file to read
+
+Flags:
+  -v, --verbose                  Print more
+      --output-directory <path>  Where to write
+  -h, --help                     Print this help
+"
+    );
+}
+
+#[test]
+fn help_wraps_at_help_width() {
+    let flags = [
+        FlagDefinition::new("threads", VariantFlag::int())
+            .abbrev('t')
+            .help(
+                "How many threads to use while reading the file, which is usually best left alone",
+            )
+            .default(Variant::Int(4)),
+        FlagDefinition::new("output-directory", VariantFlag::path()).help("Where to write"),
+    ];
+    let config = ParserConfig {
+        help_width: 60,
+        ..Default::default()
+    };
+    let help = Args::help_text("my-tool", &config, &[input()], &flags);
+    assert_eq!(
+        help,
+        "\
+usage: my-tool <input> [--threads <int>]
+               [--output-directory <path>]
+
+Arguments:
+  <input>                        The file to read
+
+Flags:
+  -t, --threads <int>            How many threads to use
+                                 while reading the file,
+                                 which is usually best left
+                                 alone [default: 4]
+      --output-directory <path>  Where to write
+  -h, --help                     Print this help
+"
+    );
+    assert!(help.lines().all(|line| line.chars().count() <= 60));
+}
+
+#[test]
+fn narrow_help_still_has_room_for_words() {
+    let flags = [FlagDefinition::new("url", VariantFlag::string()).help(
+        "For example https://example.com/a/very/long/path/that/does/not/fit then more words",
+    )];
+    let config = ParserConfig {
+        help_width: 30,
+        ..Default::default()
+    };
+    // At least 20 columns are left for help, and words longer than that get a line of their own
+    assert_eq!(
+        Args::help_text("my-tool", &config, &[], &flags),
+        "\
+usage: my-tool [--url <string>]
+
+Flags:
+      --url <string>  For example
+                      https://example.com/a/very/long/path/that/does/not/fit
+                      then more words
+  -h, --help          Print this help
+"
+    );
+}
+
+fn help_topic_parser(auto_help: bool, reserved_names: ReservedNames) -> Parser {
+    Parser::new("my-tool")
+        .flag(FlagDefinition::new("help", VariantFlag::string()).help("Help on a topic"))
+        .config(ParserConfig {
+            auto_help,
+            reserved_names,
+            ..Default::default()
+        })
+}
+
+#[test]
+fn flag_named_help_takes_its_place() {
+    let parser = help_topic_parser(true, ReservedNames::Yield);
+    assert_eq!(
+        parser.warnings(),
+        ["--help uses --help, so it isn't used for automatic help"]
+    );
+    let args = parser.parse_from(["my-tool", "--help", "topic"]).unwrap();
+    assert_eq!(args.get_named_string("help"), Some("topic"));
+    assert!(!args.help_requested());
+    // `-h` is still free for automatic help
+    assert!(
+        parser
+            .parse_from(["my-tool", "-h"])
+            .unwrap()
+            .help_requested()
+    );
+    assert_eq!(
+        parser.help_text(),
+        "\
+usage: my-tool [--help <string>]
+
+Flags:
+      --help <string>  Help on a topic
+  -h                   Print this help
+"
+    );
+}
+
+#[test]
+fn flag_named_help_can_be_rejected() {
+    let parser = help_topic_parser(true, ReservedNames::Reject);
+    assert!(parser.warnings().is_empty());
+    let Err(error) = parser.parse_from(["my-tool", "--help", "topic"]) else {
+        panic!("--help is reserved");
+    };
+    assert_eq!(
+        error.to_string(),
+        "--help can't use --help, because it is used for automatic help"
+    );
+}
+
+#[test]
+fn flag_named_help_without_auto_help() {
+    let parser = help_topic_parser(false, ReservedNames::Reject);
+    assert!(parser.warnings().is_empty());
+    let args = parser.parse_from(["my-tool", "--help", "topic"]).unwrap();
+    assert_eq!(args.get_named_string("help"), Some("topic"));
+    assert!(!args.help_requested());
+    let Err(error) = parser.parse_from(["my-tool", "-h"]) else {
+        panic!("-h isn't anything without automatic help");
+    };
+    assert_eq!(
+        error.to_string(),
+        "-h at position 1 does not match any known flag abbreviation"
+    );
+    assert!(!parser.help_text().contains("Print this help"));
+}
+
+fn verify_parser(reserved_names: ReservedNames) -> Parser {
+    Parser::new("my-tool")
+        .flag(FlagDefinition::new("verify", VariantFlag::new_unit()).abbrev('V'))
+        .config(ParserConfig {
+            version: Some("1.0".to_string()),
+            reserved_names,
+            ..Default::default()
+        })
+}
+
+#[test]
+fn flag_abbreviated_v_takes_its_place() {
+    let parser = verify_parser(ReservedNames::Yield);
+    assert_eq!(
+        parser.warnings(),
+        ["--verify uses -V, so it isn't used for printing the version"]
+    );
+    let args = parser.parse_from(["my-tool", "-V"]).unwrap();
+    assert_eq!(args.get_named_bool("verify"), Some(true));
+    assert_eq!(args.version_requested(), None);
+    // `--version` is still free for printing the version
+    let args = parser.parse_from(["my-tool", "--version"]).unwrap();
+    assert_eq!(args.version_requested(), Some("1.0"));
+    assert_eq!(args.get_named_bool("verify"), None);
+    assert!(
+        parser
+            .help_text()
+            .contains("\n      --version  Print the version\n")
+    );
+}
+
+#[test]
+fn flag_abbreviated_v_can_be_rejected() {
+    let parser = verify_parser(ReservedNames::Reject);
+    assert!(parser.warnings().is_empty());
+    for arg in ["-V", "--version"] {
+        let Err(error) = parser.parse_from(["my-tool", arg]) else {
+            panic!("-V is reserved");
+        };
+        assert_eq!(
+            error.to_string(),
+            "--verify can't use -V, because it is used for printing the version"
+        );
+    }
+}
+
+#[test]
+fn functions_without_a_config_do_not_recognize_help() {
+    let Err(error) = Args::from_slice(&["my-tool", "--help"], &[VariantFlag::path()], &[]) else {
+        panic!("--help isn't a flag here");
+    };
+    assert_eq!(
+        error.to_string(),
+        "--help at position 1 does not match any known flag name"
+    );
+    let Err(error) = Args::from_slice(&["my-tool", "-h"], &[VariantFlag::path()], &[]) else {
+        panic!("-h isn't a flag here");
+    };
+    assert_eq!(
+        error.to_string(),
+        "-h at position 1 does not match any known flag abbreviation"
+    );
+
+    // A parser recognizes it
+    let parser = Parser::new("my-tool").positional(VariantFlag::path());
+    assert!(
+        parser
+            .parse_from(["my-tool", "--help"])
+            .unwrap()
+            .help_requested()
+    );
+}
+
+#[test]
+fn unit_flag_named_help_is_not_a_request_for_help() {
+    let parser = Parser::new("my-tool").flag(FlagDefinition::new("help", VariantFlag::new_unit()));
+    let args = parser.parse_from(["my-tool", "--help"]).unwrap();
+    assert_eq!(args.get_named_bool("help"), Some(true));
+    assert!(!args.help_requested());
+    assert!(matches!(
+        parser.parse_outcome_from(["my-tool", "--help"]),
+        Ok(ParseOutcome::Parsed(_))
+    ));
+    assert!(
+        parser
+            .parse_from(["my-tool", "-h"])
+            .unwrap()
+            .help_requested()
+    );
+
+    let flags = [FlagDefinition::new("help", VariantFlag::new_unit())];
+    let args = Args::from_slice(&["my-tool", "--help"], &[], &flags).unwrap();
+    assert!(!args.help_requested());
+}
+
+#[test]
+fn help_starts_with_the_usage_line() {
+    let parser = Parser::new("my-tool")
+        .positional(VariantFlag::path())
+        .flag(FlagDefinition::new("threads", VariantFlag::int()).abbrev('t'))
+        .config(ParserConfig {
+            usage_in_errors: true,
+            ..Default::default()
+        });
+    let help = parser.help_text();
+    assert_eq!(help.lines().next(), Some(parser.usage().as_str()));
+    let Err(error) = parser.parse_from(["my-tool"]) else {
+        panic!("the path is missing");
+    };
+    assert!(error.to_string().ends_with(&parser.usage()));
+}
+
+#[test]
+fn negatable_flags_show_both_spellings() {
+    let flags = [
+        FlagDefinition::new("cache", VariantFlag::new_unit())
+            .negatable()
+            .help("Cache what was read"),
+        FlagDefinition::new("verbose", VariantFlag::new_unit()),
+    ];
+    let help = Args::help_text("my-tool", &ParserConfig::default(), &[], &flags);
+    assert!(
+        help.contains("\n      --[no-]cache  Cache what was read\n"),
+        "{help}"
+    );
+    assert!(help.contains("\n      --verbose\n"), "{help}");
+}
+
+#[test]
+fn sensitive_defaults_are_redacted() {
+    let flags = [
+        FlagDefinition::new("token", VariantFlag::string())
+            .sensitive()
+            .default(Variant::String("hunter2".to_string()))
+            .help("The API token"),
+        FlagDefinition::new("user", VariantFlag::string())
+            .default(Variant::String("me".to_string())),
+    ];
+    let help = Args::help_text("my-tool", &ParserConfig::default(), &[], &flags);
+    assert!(!help.contains("hunter2"), "{help}");
+    assert!(help.contains("  The API token [default: ***]\n"), "{help}");
+    assert!(help.contains("  [default: me]\n"), "{help}");
+}
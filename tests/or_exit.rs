@@ -0,0 +1,70 @@
+//! Checks what `gather_command_line_flags_or_exit` prints, and the status it exits with.
+//!
+//! It ends the process, so this test runs itself again as the program being parsed for, with `CHILD` set.
+
+use std::process::{Command, Output};
+
+use flagged_cl_args::{FlagDefinition, VariantFlag, gather_command_line_flags_or_exit};
+
+const CHILD: &str = "FLAGGED_CL_ARGS_OR_EXIT_CHILD";
+
+fn main() {
+    if std::env::var_os(CHILD).is_some() {
+        let args = gather_command_line_flags_or_exit(
+            &[VariantFlag::path()],
+            &[FlagDefinition::new("threads", VariantFlag::int()).help("How many threads to use")],
+        );
+        println!("threads: {:?}", args.get_named_int("threads"));
+        return;
+    }
+    let binary = std::env::current_exe()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned();
+
+    let output = run(&["in.txt", "--threads", "4"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "threads: Some(4)\n"
+    );
+    assert!(output.stderr.is_empty());
+
+    let output = run(&["in.txt", "--threads", "many"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        format!(
+            "--threads at position 3: 'many' cannot be parsed as type int: invalid digit found in string\nusage: {binary} <path> [--threads <int>]\n"
+        )
+    );
+
+    let output = run(&[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .starts_with("Not enough positional arguments were supplied")
+    );
+
+    let output = run(&["--threads", "many", "--help"]);
+    assert_eq!(output.status.code(), Some(0));
+    let help = String::from_utf8_lossy(&output.stdout);
+    // The binary is a long path, so the usage line may wrap before the flag
+    assert!(help.starts_with(&format!("usage: {binary} <path>")));
+    assert!(help.contains(" [--threads <int>]\n"));
+    assert!(help.contains("How many threads to use"));
+    assert!(output.stderr.is_empty());
+
+    println!("gather_command_line_flags_or_exit: ok");
+}
+
+/// Runs this test as the child, with `args` after the binary.
+fn run(args: &[&str]) -> Output {
+    Command::new(std::env::current_exe().unwrap())
+        .args(args)
+        .env(CHILD, "1")
+        .output()
+        .unwrap()
+}
@@ -0,0 +1,790 @@
+//! Checks how command lines are read, for the cases where flags, values, and other arguments are easy to confuse.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use flagged_cl_args::{
+    Args, ArgumentError, FlagDefinition, NoPrompt, Parser, ParserConfig, PositionalDefinition,
+    PromptSource, RepeatPolicy, ResponseFiles, ValueSource, Variant, VariantFlag,
+    validate_definitions,
+};
+
+/// A file in the temporary directory, removed when dropped.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str, contents: &str) -> TempFile {
+        let path = std::env::temp_dir().join(format!("flagged_cl_args_test_{name}"));
+        std::fs::write(&path, contents).unwrap();
+        TempFile(path)
+    }
+
+    fn at(&self) -> String {
+        format!("@{}", self.0.display())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn value_from_file_with_response_files() {
+    let token = TempFile::new("token.txt", "hunter2\n");
+    let parser = Parser::new("my-tool")
+        .flag(FlagDefinition::new("token", VariantFlag::string()).value_from_file())
+        .flag(FlagDefinition::new("name", VariantFlag::string()))
+        .config(ParserConfig {
+            response_files: Some(ResponseFiles::Lines),
+            ..Default::default()
+        });
+
+    // The flag reads its own file, so the path is what was given
+    let args = parser
+        .parse_from(["my-tool", "--token", &token.at()])
+        .unwrap();
+    assert_eq!(args.get_named_string("token"), Some("hunter2"));
+    assert_eq!(args.get_named_raw("token"), Some(token.at().as_str()));
+
+    // `@@` is unescaped once, by the flag
+    let escaped = format!("@{}", token.at());
+    let args = parser.parse_from(["my-tool", "--token", &escaped]).unwrap();
+    assert_eq!(args.get_named_string("token"), Some(token.at().as_str()));
+
+    // Even when the flag is in a response file
+    let response = TempFile::new(
+        "token_response.txt",
+        &format!("--token\n{}\n--name\n@@me\n", token.at()),
+    );
+    let args = parser.parse_from(["my-tool", &response.at()]).unwrap();
+    assert_eq!(args.get_named_string("token"), Some("hunter2"));
+    assert_eq!(args.get_named_string("name"), Some("@me"));
+
+    // Other flags' values are still response files
+    let name = TempFile::new("name_response.txt", "someone\n");
+    let args = parser
+        .parse_from(["my-tool", "--name", &name.at()])
+        .unwrap();
+    assert_eq!(args.get_named_string("name"), Some("someone"));
+}
+
+#[test]
+fn separator_as_the_last_argument() {
+    let flags = [FlagDefinition::new("verbose", VariantFlag::new_unit())];
+    let args =
+        Args::from_slice(&["my-tool", "in.txt", "--"], &[VariantFlag::path()], &flags).unwrap();
+    assert_eq!(args.get_positional_path(0), Some(&PathBuf::from("in.txt")));
+    assert!(args.trailing().is_empty());
+    assert_eq!(args.to_argv(&flags)[1..], ["in.txt"]);
+
+    // A flag before it is still a flag
+    let args = Args::from_slice(&["my-tool", "--verbose", "--"], &[], &flags).unwrap();
+    assert_eq!(args.get_named_bool("verbose"), Some(true));
+    assert!(args.trailing().is_empty());
+}
+
+#[test]
+fn repeated_separator_is_an_argument() {
+    let flags = [FlagDefinition::new("verbose", VariantFlag::new_unit())];
+    let args = Args::from_slice(
+        &["my-tool", "in.txt", "--", "--", "--verbose", "--"],
+        &[VariantFlag::path()],
+        &flags,
+    )
+    .unwrap();
+    assert_eq!(args.get_named_bool("verbose"), None);
+    assert_eq!(args.trailing(), ["--", "--verbose", "--"]);
+
+    // Only after the positional arguments are filled
+    let args = Args::from_slice(
+        &["my-tool", "--", "--", "x"],
+        &[VariantFlag::string()],
+        &flags,
+    )
+    .unwrap();
+    assert_eq!(args.get_positional_string(0), Some("--"));
+    assert_eq!(args.trailing(), ["x"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn trailing_arguments_keep_invalid_utf8() {
+    use std::os::unix::ffi::OsStringExt;
+
+    let invalid = OsString::from_vec(vec![b'-', b'-', 0xff, b'x']);
+    let args = Parser::new("my-tool")
+        .parse_from([
+            OsString::from("my-tool"),
+            OsString::from("--"),
+            invalid.clone(),
+        ])
+        .unwrap();
+    assert_eq!(args.trailing_os(), [invalid]);
+    assert_eq!(args.trailing(), ["--\u{fffd}x"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn paths_keep_invalid_utf8() {
+    use std::os::unix::ffi::OsStringExt;
+
+    let path = OsString::from_vec(vec![b'i', b'n', 0xff, b'.', b't', b'x', b't']);
+    let parser = Parser::new("my-tool")
+        .positional(VariantFlag::path())
+        .flag(FlagDefinition::new("output", VariantFlag::path()))
+        .flag(FlagDefinition::new("name", VariantFlag::string()));
+    let args = parser
+        .parse_from([
+            OsString::from("my-tool"),
+            path.clone(),
+            OsString::from("--output"),
+            path.clone(),
+        ])
+        .unwrap();
+    assert_eq!(args.get_positional_path(0), Some(&PathBuf::from(&path)));
+    assert_eq!(args.get_named_path("output"), Some(&PathBuf::from(&path)));
+
+    let Err(error) = parser.parse_from([
+        OsString::from("my-tool"),
+        OsString::from("in.txt"),
+        OsString::from("--name"),
+        path,
+    ]) else {
+        panic!("a string has to be valid UTF-8");
+    };
+    assert_eq!(
+        error.to_string(),
+        "--name at position 3: 'in\u{fffd}.txt' is not valid UTF-8"
+    );
+}
+
+fn short_flags() -> [FlagDefinition; 4] {
+    [
+        FlagDefinition::new("recursive", VariantFlag::new_unit()).abbrev('r'),
+        FlagDefinition::new("force", VariantFlag::new_unit()).abbrev('f'),
+        FlagDefinition::new("verbose", VariantFlag::count()).abbrev('v'),
+        FlagDefinition::new("lines", VariantFlag::int()).abbrev('n'),
+    ]
+}
+
+#[test]
+fn combined_abbreviations() {
+    let flags = short_flags();
+    let args = Args::from_slice(&["my-tool", "-rfv"], &[], &flags).unwrap();
+    assert_eq!(args.get_named_bool("recursive"), Some(true));
+    assert_eq!(args.get_named_bool("force"), Some(true));
+    assert_eq!(args.get_named_int("verbose"), Some(1));
+
+    let args = Args::from_slice(&["my-tool", "-rvv"], &[], &flags).unwrap();
+    assert_eq!(args.get_named_int("verbose"), Some(2));
+
+    let Err(error) = Args::from_slice(&["my-tool", "-rx"], &[], &flags) else {
+        panic!("there is no -x");
+    };
+    assert_eq!(
+        error.to_string(),
+        "-x at position 1 does not match any known flag abbreviation"
+    );
+}
+
+#[test]
+fn combined_abbreviations_with_a_value() {
+    let flags = short_flags();
+    // The rest of the argument is the value
+    let args = Args::from_slice(&["my-tool", "-rn5"], &[], &flags).unwrap();
+    assert_eq!(args.get_named_bool("recursive"), Some(true));
+    assert_eq!(args.get_named_int("lines"), Some(5));
+
+    let args = Args::from_slice(&["my-tool", "-n=4"], &[], &flags).unwrap();
+    assert_eq!(args.get_named_int("lines"), Some(4));
+
+    // Even when it starts with a dash
+    let args = Args::from_slice(&["my-tool", "-n-3"], &[], &flags).unwrap();
+    assert_eq!(args.get_named_int("lines"), Some(-3));
+
+    // Or it is the next argument, when the flag is last
+    let args = Args::from_slice(&["my-tool", "-rn", "7"], &[], &flags).unwrap();
+    assert_eq!(args.get_named_bool("recursive"), Some(true));
+    assert_eq!(args.get_named_int("lines"), Some(7));
+
+    let Err(error) = Args::from_slice(&["my-tool", "-rn"], &[], &flags) else {
+        panic!("-n needs a value");
+    };
+    assert_eq!(
+        error.to_string(),
+        "Unexpected end of arguments, --lines needs a value"
+    );
+}
+
+#[test]
+fn negative_numbers_are_not_flags() {
+    let number = [VariantFlag::float().or_int()];
+    let flags = short_flags();
+    for (arg, value) in [
+        ("-5", Variant::Int(-5)),
+        ("-5.0", Variant::Float(-5.0)),
+        ("-.5", Variant::Float(-0.5)),
+    ] {
+        let args = Args::from_slice(&["my-tool", arg], &number, &flags).unwrap();
+        assert_eq!(args.get_positional(0), Some(&value), "{arg}");
+    }
+    // Or values
+    let args = Args::from_slice(&["my-tool", "-n", "-5", "-5"], &number, &flags).unwrap();
+    assert_eq!(args.get_named_int("lines"), Some(-5));
+    assert_eq!(args.get_positional(0), Some(&Variant::Int(-5)));
+}
+
+#[test]
+fn digit_abbreviation_is_a_flag() {
+    let number = [VariantFlag::float().or_int()];
+    let flags = [FlagDefinition::new("five", VariantFlag::new_unit()).abbrev('5')];
+    let args = Args::from_slice(&["my-tool", "-5", "-6"], &number, &flags).unwrap();
+    assert_eq!(args.get_named_bool("five"), Some(true));
+    assert_eq!(args.get_positional(0), Some(&Variant::Int(-6)));
+
+    // Starting with the digit, the rest is read as abbreviations too
+    let Err(error) = Args::from_slice(&["my-tool", "-5.0"], &number, &flags) else {
+        panic!("-5 is a flag");
+    };
+    assert_eq!(
+        error.to_string(),
+        "-. at position 1 does not match any known flag abbreviation"
+    );
+    let args = Args::from_slice(&["my-tool", "-.5"], &number, &flags).unwrap();
+    assert_eq!(args.get_named_bool("five"), None);
+    assert_eq!(args.get_positional(0), Some(&Variant::Float(-0.5)));
+}
+
+#[test]
+fn unit_flag_does_not_take_the_next_argument() {
+    let flags = short_flags();
+    let Err(error) = Args::from_slice(&["my-tool", "--force", "true"], &[], &flags) else {
+        panic!("true is a positional argument");
+    };
+    assert_eq!(
+        error.to_string(),
+        "There are too many positional arguments, expected 0 but got 1"
+    );
+    let args = Args::from_slice(
+        &["my-tool", "--force", "true"],
+        &[VariantFlag::string()],
+        &flags,
+    )
+    .unwrap();
+    assert_eq!(args.get_named_bool("force"), Some(true));
+    assert_eq!(args.get_positional_string(0), Some("true"));
+
+    // Attached, it is the flag's value
+    let args = Args::from_slice(&["my-tool", "--force=false"], &[], &flags).unwrap();
+    assert_eq!(args.get_named_bool("force"), Some(false));
+    let Err(error) = Args::from_slice(&["my-tool", "--force=banana"], &[], &flags) else {
+        panic!("banana isn't true or false");
+    };
+    assert_eq!(
+        error.to_string(),
+        "--force at position 1 does not take a value other than true or false, like --force=false"
+    );
+}
+
+/// Parses `args` with a fake environment holding only `vars`.
+fn parse_with_env(
+    args: &[&str],
+    config: &ParserConfig,
+    flags: &[FlagDefinition],
+    vars: &[(&str, &str)],
+) -> Result<Args, ArgumentError> {
+    Args::from_iter_with_env(
+        args.iter().map(|arg| arg.to_string()),
+        config,
+        &[],
+        flags,
+        |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| OsString::from(value))
+        },
+        &mut NoPrompt,
+    )
+}
+
+fn threads_flag() -> [FlagDefinition; 1] {
+    [FlagDefinition::new("threads", VariantFlag::int())
+        .env_var("THREADS")
+        .default(Variant::Int(1))]
+}
+
+#[test]
+fn command_line_then_environment_then_default() {
+    let config = ParserConfig::default();
+    let flags = threads_flag();
+
+    let args = parse_with_env(
+        &["my-tool", "--threads", "8"],
+        &config,
+        &flags,
+        &[("THREADS", "4")],
+    )
+    .unwrap();
+    assert_eq!(args.get_named_int("threads"), Some(8));
+    assert_eq!(
+        args.source_of("threads"),
+        Some(ValueSource::CommandLine { index: 1 })
+    );
+
+    let args = parse_with_env(&["my-tool"], &config, &flags, &[("THREADS", "4")]).unwrap();
+    assert_eq!(args.get_named_int("threads"), Some(4));
+    assert_eq!(
+        args.source_of("threads"),
+        Some(ValueSource::Environment {
+            var: "THREADS".to_string()
+        })
+    );
+
+    let args = parse_with_env(&["my-tool"], &config, &flags, &[]).unwrap();
+    assert_eq!(args.get_named_int("threads"), Some(1));
+    assert_eq!(args.source_of("threads"), Some(ValueSource::Default));
+}
+
+#[test]
+fn environment_value_that_does_not_parse() {
+    let Err(error) = parse_with_env(
+        &["my-tool"],
+        &ParserConfig::default(),
+        &threads_flag(),
+        &[("THREADS", "four")],
+    ) else {
+        panic!("four isn't an int");
+    };
+    assert_eq!(
+        error.to_string(),
+        "Environment variable THREADS=four is not a valid type for --threads"
+    );
+
+    // It isn't looked at when the command line has a value
+    let args = parse_with_env(
+        &["my-tool", "--threads", "2"],
+        &ParserConfig::default(),
+        &threads_flag(),
+        &[("THREADS", "four")],
+    )
+    .unwrap();
+    assert_eq!(args.get_named_int("threads"), Some(2));
+}
+
+#[test]
+fn skip_env_ignores_the_prefix() {
+    let config = ParserConfig {
+        env_prefix: Some("MYTOOL_".to_string()),
+        ..Default::default()
+    };
+    let flags = [
+        FlagDefinition::new("log-level", VariantFlag::string()).skip_env(),
+        FlagDefinition::new("color", VariantFlag::string()),
+    ];
+    let vars = [("MYTOOL_LOG_LEVEL", "debug"), ("MYTOOL_COLOR", "never")];
+    let args = parse_with_env(&["my-tool"], &config, &flags, &vars).unwrap();
+    assert_eq!(args.get_named_string("log-level"), None);
+    assert_eq!(args.get_named_string("color"), Some("never"));
+}
+
+#[test]
+fn default_of_the_wrong_type_is_a_definition_problem() {
+    let flags = [FlagDefinition::new("threads", VariantFlag::int())
+        .default(Variant::String("four".to_string()))];
+    let expected = "The default value four for --threads is not a valid type for it, which is int";
+    assert_eq!(
+        validate_definitions(&flags).unwrap_err().to_string(),
+        expected
+    );
+    // Even when the flag is given, so it is found before anyone leaves it out
+    let Err(error) = Args::from_slice(&["my-tool", "--threads", "2"], &[], &flags) else {
+        panic!("the default is a string");
+    };
+    assert_eq!(error.to_string(), expected);
+
+    let parser = Parser::new("my-tool").positional(
+        PositionalDefinition::from(VariantFlag::int())
+            .name("count")
+            .default(Variant::Bool(true)),
+    );
+    let Err(error) = parser.parse_from(["my-tool", "3"]) else {
+        panic!("the default is a bool");
+    };
+    assert_eq!(
+        error.to_string(),
+        "<count>: the default value true is not a valid type for it, which is int"
+    );
+}
+
+fn key_file_flags() -> [FlagDefinition; 3] {
+    [
+        FlagDefinition::new("key-file", VariantFlag::path()).requires("user"),
+        FlagDefinition::new("user", VariantFlag::string())
+            .alias("login")
+            .env_var("USER_X")
+            .requires("host"),
+        FlagDefinition::new("host", VariantFlag::string())
+            .default(Variant::String("localhost".to_string())),
+    ]
+}
+
+#[test]
+fn requirement_met_by_the_environment() {
+    let config = ParserConfig::default();
+    let mut flags = key_file_flags().to_vec();
+    flags[1].requires.clear();
+    let args = &["my-tool", "--key-file", "id_rsa"];
+    let Err(error) = parse_with_env(args, &config, &flags, &[]) else {
+        panic!("--key-file needs --user");
+    };
+    assert_eq!(error.to_string(), "--key-file requires --user");
+    let args = parse_with_env(args, &config, &flags, &[("USER_X", "me")]).unwrap();
+    assert_eq!(args.get_named_string("user"), Some("me"));
+}
+
+#[test]
+fn requirements_are_checked_for_every_flag_given() {
+    let config = ParserConfig::default();
+    let mut flags = key_file_flags().to_vec();
+    flags[2].default = None;
+    let Err(error) = parse_with_env(&["my-tool", "--key-file", "id_rsa"], &config, &flags, &[])
+    else {
+        panic!("--key-file needs --user");
+    };
+    // --user isn't given, so what it requires doesn't matter yet
+    assert_eq!(error.to_string(), "--key-file requires --user");
+    let Err(error) = parse_with_env(
+        &["my-tool", "--key-file", "id_rsa"],
+        &config,
+        &flags,
+        &[("USER_X", "me")],
+    ) else {
+        panic!("--user needs --host");
+    };
+    assert_eq!(error.to_string(), "--user requires --host");
+    parse_with_env(
+        &["my-tool", "--key-file", "id_rsa", "--host", "example.com"],
+        &config,
+        &flags,
+        &[("USER_X", "me")],
+    )
+    .unwrap();
+
+    // A default doesn't count as given
+    let Err(error) = parse_with_env(
+        &["my-tool", "--login", "me"],
+        &config,
+        &key_file_flags(),
+        &[],
+    ) else {
+        panic!("--host only has its default");
+    };
+    assert_eq!(error.to_string(), "--user requires --host");
+}
+
+#[test]
+fn constraints_can_name_aliases() {
+    let flags = [
+        FlagDefinition::new("key-file", VariantFlag::path()).requires("login"),
+        FlagDefinition::new("user", VariantFlag::string()).alias("login"),
+        FlagDefinition::new("anonymous", VariantFlag::new_unit()).conflicts_with("login"),
+    ];
+    assert!(validate_definitions(&flags).is_ok());
+    let args = Args::from_slice(
+        &["my-tool", "--key-file", "id_rsa", "--user", "me"],
+        &[],
+        &flags,
+    )
+    .unwrap();
+    assert_eq!(args.get_named_string("user"), Some("me"));
+    let Err(error) = Args::from_slice(&["my-tool", "--anonymous", "--login", "me"], &[], &flags)
+    else {
+        panic!("--anonymous can't have a user");
+    };
+    assert_eq!(error.to_string(), "--anonymous cannot be used with --user");
+}
+
+#[test]
+fn every_unknown_constraint_is_reported() {
+    let flags = [
+        FlagDefinition::new("key-file", VariantFlag::path())
+            .requires("usr")
+            .conflicts_with("anonymus")
+            .requires("host"),
+        FlagDefinition::new("host", VariantFlag::string()).conflicts_with("socket"),
+    ];
+    assert_eq!(
+        validate_definitions(&flags).unwrap_err().to_string(),
+        "--key-file has a constraint on --anonymus, which does not match any known flag name\n\
+         --key-file has a constraint on --usr, which does not match any known flag name\n\
+         --host has a constraint on --socket, which does not match any known flag name"
+    );
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn config_values_count_for_conflicts() {
+    let flags = [
+        FlagDefinition::new("json", VariantFlag::new_unit()).conflicts_with("plain"),
+        FlagDefinition::new("plain", VariantFlag::new_unit()),
+    ];
+    let mut args = Args::from_slice(&["my-tool", "--json"], &[], &flags).unwrap();
+    let Err(error) = args.overlay_config(&"plain = true".parse().unwrap(), &flags) else {
+        panic!("--json and --plain conflict");
+    };
+    assert_eq!(error.to_string(), "--json cannot be used with --plain");
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn config_values_are_validated_one_at_a_time() {
+    let flags = [FlagDefinition::new("inc", VariantFlag::int())
+        .repeat(RepeatPolicy::Collect)
+        .validator(|value| match value {
+            Variant::Int(1..) => Ok(()),
+            value => Err(format!("not a positive int: {value:?}")),
+        })];
+    let mut args = Args::from_slice(&["my-tool"], &[], &flags).unwrap();
+    args.overlay_config(&"inc = [1, 2]".parse().unwrap(), &flags)
+        .unwrap();
+    assert_eq!(
+        args.get_named("inc"),
+        Some(&Variant::List(vec![Variant::Int(1), Variant::Int(2)]))
+    );
+
+    let mut args = Args::from_slice(&["my-tool"], &[], &flags).unwrap();
+    assert_eq!(
+        args.overlay_config(&"inc = [1, 0]".parse().unwrap(), &flags)
+            .unwrap_err()
+            .to_string(),
+        "Config key inc value 0: not a positive int: Int(0)"
+    );
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn config_floats_stay_floats() {
+    let flags = [
+        FlagDefinition::new("ratio", VariantFlag::int().or_float()),
+        FlagDefinition::new("precise", VariantFlag::double()),
+        FlagDefinition::new("threads", VariantFlag::int()),
+        FlagDefinition::new("name", VariantFlag::string()),
+    ];
+    let mut args = Args::from_slice(&["my-tool"], &[], &flags).unwrap();
+    args.overlay_config(
+        &"ratio = 1.0\nprecise = 0.1\nname = 1.0".parse().unwrap(),
+        &flags,
+    )
+    .unwrap();
+    assert_eq!(args.get_named("ratio"), Some(&Variant::Float(1.0)));
+    assert_eq!(args.get_named("precise"), Some(&Variant::Double(0.1)));
+    assert_eq!(args.get_named_string("name"), Some("1.0"));
+    // Integers are still ints
+    let mut args = Args::from_slice(&["my-tool"], &[], &flags).unwrap();
+    args.overlay_config(&"ratio = 2".parse().unwrap(), &flags)
+        .unwrap();
+    assert_eq!(args.get_named("ratio"), Some(&Variant::Int(2)));
+
+    let Err(error) = args.overlay_config(&"threads = 4.0".parse().unwrap(), &flags) else {
+        panic!("4.0 isn't an int");
+    };
+    assert_eq!(
+        error.to_string(),
+        "Config key threads: '4.0' cannot be parsed as type int"
+    );
+}
+
+/// Answers every prompt with the same answer, counting how many were asked.
+struct Answer(&'static str, usize);
+
+impl PromptSource for Answer {
+    fn is_interactive(&self) -> bool {
+        true
+    }
+
+    fn read_line(&mut self, _prompt: &str, _hidden: bool) -> std::io::Result<String> {
+        self.1 += 1;
+        Ok(self.0.to_string())
+    }
+}
+
+fn prompted_flags() -> [FlagDefinition; 2] {
+    [
+        FlagDefinition::new("user", VariantFlag::string()).prompt("Username: "),
+        FlagDefinition::new("port", VariantFlag::int())
+            .default(Variant::Int(22))
+            .prompt("Port: "),
+    ]
+}
+
+#[test]
+fn parsing_given_arguments_never_prompts() {
+    let flags = prompted_flags();
+    let Err(error) = Args::from_slice(&["my-tool"], &[], &flags) else {
+        panic!("--user is missing");
+    };
+    assert_eq!(
+        error.to_string(),
+        "--user was not given, and there is no terminal to ask for it"
+    );
+    let args = Args::from_slice(&["my-tool", "--user", "me"], &[], &flags).unwrap();
+    assert_eq!(args.get_named_int("port"), Some(22));
+
+    let parser = Parser::new("my-tool").flag(flags[0].clone());
+    assert!(parser.parse_from(["my-tool"]).is_err());
+}
+
+#[test]
+fn prompts_can_be_answered_by_a_test() {
+    let mut answer = Answer("42", 0);
+    let args = Args::from_iter_with_env(
+        ["my-tool"].map(String::from).into_iter(),
+        &ParserConfig::default(),
+        &[],
+        &prompted_flags(),
+        |_| None,
+        &mut answer,
+    )
+    .unwrap();
+    assert_eq!(args.get_named_string("user"), Some("42"));
+    assert_eq!(args.get_named_int("port"), Some(42));
+    assert_eq!(answer.1, 2);
+}
+
+#[test]
+fn aliases_resolve_to_the_name() {
+    let flags = [FlagDefinition::new("color", VariantFlag::string())
+        .alias("colour")
+        .abbrev('c')];
+    for given in [
+        ["my-tool", "--color", "red"],
+        ["my-tool", "--colour", "red"],
+        ["my-tool", "-c", "red"],
+    ] {
+        let args = Args::from_slice(&given, &[], &flags).unwrap();
+        assert_eq!(args.get_named_string("color"), Some("red"), "{given:?}");
+        assert_eq!(args.get_named("colour"), None);
+        assert_eq!(
+            args.named_iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            ["color"]
+        );
+    }
+}
+
+#[test]
+fn deprecated_flags_warn_with_the_spelling_given() {
+    let flags = [
+        FlagDefinition::new("color", VariantFlag::string())
+            .alias("colour")
+            .abbrev('c')
+            .deprecated("use --theme instead"),
+        FlagDefinition::new("theme", VariantFlag::string()),
+    ];
+    for (given, warning) in [
+        (
+            ["my-tool", "--colour", "red"],
+            "--colour at position 1 is deprecated: use --theme instead",
+        ),
+        (
+            ["my-tool", "-c", "red"],
+            "-c at position 1 is deprecated: use --theme instead",
+        ),
+    ] {
+        let args = Args::from_slice(&given, &[], &flags).unwrap();
+        assert_eq!(args.warnings(), [warning]);
+        assert_eq!(args.get_named_string("color"), Some("red"));
+    }
+    // Flags that aren't given don't warn
+    let args = Args::from_slice(&["my-tool", "--theme", "dark"], &[], &flags).unwrap();
+    assert!(args.warnings().is_empty());
+}
+
+#[test]
+fn collected_flags_mix_long_and_short_forms() {
+    let flags = [FlagDefinition::new("include", VariantFlag::string())
+        .abbrev('I')
+        .repeat(RepeatPolicy::Collect)];
+    let args = Args::from_slice(
+        &["my-tool", "-I", "a", "--include", "b", "-Ic", "--include=d"],
+        &[],
+        &flags,
+    )
+    .unwrap();
+    assert_eq!(
+        args.get_named("include"),
+        Some(&Variant::List(
+            ["a", "b", "c", "d"]
+                .map(|value| Variant::String(value.to_string()))
+                .to_vec()
+        ))
+    );
+    let args = Args::from_slice(&["my-tool", "-Ia"], &[], &flags).unwrap();
+    assert_eq!(
+        args.get_named("include"),
+        Some(&Variant::List(vec![Variant::String("a".to_string())]))
+    );
+}
+
+#[test]
+fn repeats_are_rejected_or_replaced() {
+    let flag = FlagDefinition::new("include", VariantFlag::string()).abbrev('I');
+    let given = ["my-tool", "-I", "a", "--include", "b"];
+    let Err(error) = Args::from_slice(&given, &[], std::slice::from_ref(&flag)) else {
+        panic!("--include can only be given once");
+    };
+    assert_eq!(error.to_string(), "--include was given more than once");
+    let args = Args::from_slice(&given, &[], &[flag.repeat(RepeatPolicy::LastWins)]).unwrap();
+    assert_eq!(args.get_named_string("include"), Some("b"));
+}
+
+fn cache_flags() -> [FlagDefinition; 2] {
+    [
+        FlagDefinition::new("cache", VariantFlag::new_unit())
+            .negatable()
+            .default(Variant::Bool(true)),
+        FlagDefinition::new("verbose", VariantFlag::new_unit()),
+    ]
+}
+
+#[test]
+fn negatable_flags_can_be_turned_off() {
+    let flags = cache_flags();
+    for (given, expected) in [
+        (&["my-tool"][..], true),
+        (&["my-tool", "--cache"], true),
+        (&["my-tool", "--no-cache"], false),
+    ] {
+        let args = Args::from_slice(given, &[], &flags).unwrap();
+        assert_eq!(args.get_named_bool("cache"), Some(expected), "{given:?}");
+    }
+}
+
+#[test]
+fn both_spellings_of_a_negatable_flag_conflict() {
+    let flags = cache_flags();
+    for given in [
+        ["my-tool", "--cache", "--no-cache"],
+        ["my-tool", "--no-cache", "--cache"],
+    ] {
+        let Err(error) = Args::from_slice(&given, &[], &flags) else {
+            panic!("{given:?} turns --cache on and off");
+        };
+        assert_eq!(
+            error.to_string(),
+            "--cache and --no-cache cannot both be given"
+        );
+    }
+}
+
+#[test]
+fn only_negatable_flags_take_no() {
+    let Err(error) = Args::from_slice(&["my-tool", "--no-verbose"], &[], &cache_flags()) else {
+        panic!("--verbose isn't negatable");
+    };
+    assert_eq!(
+        error.to_string(),
+        "--no-verbose at position 1 does not match any known flag name (did you mean --verbose?)"
+    );
+}
@@ -0,0 +1,115 @@
+//! Checks how a subcommand is picked, which flags belong to it or to the program, and its help.
+
+use flagged_cl_args::{Args, ArgumentError, FlagDefinition, ParserConfig, Subcommand, VariantFlag};
+
+fn subcommands() -> [Subcommand; 2] {
+    [
+        Subcommand {
+            name: "fetch".to_string(),
+            positional_definitions: vec![VariantFlag::string().into()],
+            ..Default::default()
+        },
+        Subcommand {
+            name: "push".to_string(),
+            aliases: vec!["up".to_string()],
+            positional_definitions: vec![VariantFlag::path().into()],
+            flag_definitions: vec![
+                FlagDefinition::new("force", VariantFlag::new_unit()).abbrev('f'),
+            ],
+        },
+    ]
+}
+
+fn parse(args: &[&str]) -> Result<(Args, Option<(String, Args)>), ArgumentError> {
+    let global = [FlagDefinition::new("verbose", VariantFlag::new_unit()).abbrev('v')];
+    Args::from_iter_with_subcommands(
+        args.iter().map(|arg| arg.to_string()),
+        &ParserConfig::default(),
+        &global,
+        &subcommands(),
+    )
+}
+
+#[test]
+fn the_first_other_argument_picks_the_subcommand() {
+    let (global, subcommand) = parse(&["my-tool", "-v", "fetch", "https://example.com"]).unwrap();
+    assert_eq!(global.get_named_bool("verbose"), Some(true));
+    let (name, args) = subcommand.unwrap();
+    assert_eq!(name, "fetch");
+    assert_eq!(args.binary(), "my-tool fetch");
+    assert_eq!(args.get_positional_string(0), Some("https://example.com"));
+
+    // An alias gives the subcommand's name
+    let (global, subcommand) = parse(&["my-tool", "up", "out.txt", "-f"]).unwrap();
+    assert_eq!(global.get_named_bool("verbose"), None);
+    let (name, args) = subcommand.unwrap();
+    assert_eq!(name, "push");
+    assert_eq!(args.get_named_bool("force"), Some(true));
+}
+
+#[test]
+fn flags_belong_to_one_side_of_the_subcommand() {
+    // Global flags are only recognized before the subcommand, whose positions count from itself
+    let Err(error) = parse(&["my-tool", "push", "out.txt", "--verbose"]) else {
+        panic!("--verbose is global");
+    };
+    assert_eq!(
+        error.to_string(),
+        "--verbose at position 2 does not match any known flag name"
+    );
+    // And the subcommand's flags only after it
+    let Err(error) = parse(&["my-tool", "--force", "push", "out.txt"]) else {
+        panic!("--force belongs to push");
+    };
+    assert_eq!(
+        error.to_string(),
+        "--force at position 1 does not match any known flag name"
+    );
+}
+
+#[test]
+fn missing_and_unknown_subcommands() {
+    // No subcommand isn't an error, so the program can decide
+    let (global, subcommand) = parse(&["my-tool", "-v"]).unwrap();
+    assert_eq!(global.get_named_bool("verbose"), Some(true));
+    assert!(subcommand.is_none());
+
+    let Err(error) = parse(&["my-tool", "pushh", "out.txt"]) else {
+        panic!("pushh isn't a subcommand");
+    };
+    assert_eq!(
+        error.to_string(),
+        "Unknown subcommand pushh at position 1, expected one of: fetch, push (did you mean push?)"
+    );
+    let Err(error) = parse(&["my-tool", "delete"]) else {
+        panic!("delete isn't a subcommand");
+    };
+    assert_eq!(
+        error.to_string(),
+        "Unknown subcommand delete at position 1, expected one of: fetch, push"
+    );
+}
+
+#[test]
+fn help_is_for_the_subcommand_it_follows() {
+    let (global, subcommand) = parse(&["my-tool", "push", "--help"]).unwrap();
+    assert!(!global.help_requested());
+    let (_, args) = subcommand.unwrap();
+    assert!(args.help_requested());
+    let push = &subcommands()[1];
+    let help = Args::help_text(
+        args.binary(),
+        &ParserConfig::default(),
+        &push.positional_definitions,
+        &push.flag_definitions,
+    );
+    assert!(
+        help.starts_with("usage: my-tool push <path> [-f]\n"),
+        "{help}"
+    );
+    assert!(help.contains("\n  -f, --force\n"), "{help}");
+
+    let (global, subcommand) = parse(&["my-tool", "--help"]).unwrap();
+    assert!(global.help_requested());
+    assert!(subcommand.is_none());
+}
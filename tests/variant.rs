@@ -0,0 +1,887 @@
+//! Checks how values are parsed into each type, and the limits put on them.
+
+use std::net::{Ipv6Addr, SocketAddr};
+use std::ops::Bound;
+use std::path::PathBuf;
+use std::time::{Duration, UNIX_EPOCH};
+
+use flagged_cl_args::{Args, FlagDefinition, Variant, VariantFlag};
+
+/// Whether `value` is accepted as the default of a flag of type `allowed_type`.
+fn allows_default(allowed_type: &VariantFlag, value: Variant) -> bool {
+    let flags = [FlagDefinition::new("value", allowed_type.clone()).default(value)];
+    Args::from_slice(&["my-tool"], &[], &flags).is_ok()
+}
+
+#[test]
+fn int_range_includes_its_edges() {
+    let threads = VariantFlag::int_in(1..=256);
+    assert_eq!(threads.parse("1"), Some(Variant::Int(1)));
+    assert_eq!(threads.parse("256"), Some(Variant::Int(256)));
+    assert_eq!(threads.parse("0"), None);
+    assert_eq!(
+        threads.try_parse("257").unwrap_err().to_string(),
+        "tried int: value 257 is out of range 1..=256"
+    );
+
+    let exclusive = VariantFlag::int_in(1..256);
+    assert_eq!(exclusive.parse("255"), Some(Variant::Int(255)));
+    assert_eq!(
+        exclusive.try_parse("256").unwrap_err().to_string(),
+        "tried int: value 256 is out of range 1..256"
+    );
+}
+
+#[test]
+fn int_range_can_be_open_ended() {
+    let at_least = VariantFlag::int_in(1..);
+    assert_eq!(at_least.parse("2147483647"), Some(Variant::Int(i32::MAX)));
+    assert_eq!(
+        at_least.try_parse("0").unwrap_err().to_string(),
+        "tried int: value 0 is out of range 1.."
+    );
+
+    let at_most = VariantFlag::int_in(..=10);
+    assert_eq!(at_most.parse("-2147483648"), Some(Variant::Int(i32::MIN)));
+    assert_eq!(
+        at_most.try_parse("11").unwrap_err().to_string(),
+        "tried int: value 11 is out of range ..=10"
+    );
+}
+
+#[test]
+fn out_of_range_falls_back_to_other_types() {
+    let level = VariantFlag::int_in(0..=9).or_string();
+    assert_eq!(level.parse("9"), Some(Variant::Int(9)));
+    assert_eq!(level.parse("10"), Some(Variant::String("10".to_string())));
+    assert_eq!(level.parse("max"), Some(Variant::String("max".to_string())));
+    assert!(allows_default(&level, Variant::Int(9)));
+    assert!(!allows_default(&level, Variant::Int(10)));
+
+    let ratio = VariantFlag::float_in(0.0..=1.0).or_string();
+    assert_eq!(ratio.parse("1"), Some(Variant::Float(1.0)));
+    assert_eq!(ratio.parse("1.5"), Some(Variant::String("1.5".to_string())));
+}
+
+#[test]
+fn float_range_includes_its_edges() {
+    let ratio = VariantFlag::float_in(0.0..=0.1);
+    assert_eq!(ratio.parse("0"), Some(Variant::Float(0.0)));
+    // 0.1 as an f32 is a little more than 0.1 as an f64, but it is still the edge
+    assert_eq!(ratio.parse("0.1"), Some(Variant::Float(0.1)));
+    assert!(allows_default(&ratio, Variant::Float(0.1)));
+    assert_eq!(ratio.parse("0.11"), None);
+    assert_eq!(ratio.parse("-0.01"), None);
+
+    let ratio = VariantFlag::double().float_range(0.0..=0.1);
+    assert_eq!(ratio.parse("0.1"), Some(Variant::Double(0.1)));
+    assert_eq!(ratio.parse("0.1000001"), None);
+
+    let exclusive = VariantFlag::float_in(0.0..1.0);
+    assert_eq!(exclusive.parse("0.5"), Some(Variant::Float(0.5)));
+    assert_eq!(
+        exclusive.try_parse("1.0").unwrap_err().to_string(),
+        "tried float: value 1.0 is out of range 0.0..1.0"
+    );
+}
+
+#[test]
+fn float_range_can_be_open_ended() {
+    let positive = VariantFlag::float_in(0.0..);
+    assert_eq!(positive.parse("1e30"), Some(Variant::Float(1e30)));
+    assert_eq!(
+        positive.try_parse("-1").unwrap_err().to_string(),
+        "tried float: value -1 is out of range 0.0.."
+    );
+
+    let at_most = VariantFlag::float_in(..=1.0);
+    assert_eq!(at_most.parse("-1e30"), Some(Variant::Float(-1e30)));
+    assert_eq!(at_most.parse("1.01"), None);
+}
+
+#[test]
+fn excluded_start_is_shown() {
+    let above_zero = VariantFlag::int_in((Bound::Excluded(0), Bound::Included(10)));
+    assert_eq!(above_zero.parse("1"), Some(Variant::Int(1)));
+    assert_eq!(
+        above_zero.try_parse("0").unwrap_err().to_string(),
+        "tried int: value 0 is out of range 0 (excluded)..=10"
+    );
+
+    let above_zero = VariantFlag::float_in((Bound::Excluded(0.0), Bound::Unbounded));
+    assert_eq!(
+        above_zero.try_parse("0").unwrap_err().to_string(),
+        "tried float: value 0 is out of range 0.0 (excluded).."
+    );
+}
+
+#[test]
+fn radix_prefixes_ignore_case() {
+    let int = VariantFlag::int();
+    for raw in ["0xff", "0xFF", "0XFF", "0Xff"] {
+        assert_eq!(int.parse(raw), Some(Variant::Int(255)), "{raw}");
+    }
+    assert_eq!(int.parse("0B101"), Some(Variant::Int(5)));
+    assert_eq!(int.parse("0O17"), Some(Variant::Int(15)));
+    assert_eq!(int.parse("-0X10"), Some(Variant::Int(-16)));
+}
+
+#[test]
+fn radix_values_overflow_like_decimal_ones() {
+    let int = VariantFlag::int();
+    for (largest, too_large) in [
+        ("0x7fffffff", "0x80000000"),
+        (
+            "0b1111111111111111111111111111111",
+            "0b10000000000000000000000000000000",
+        ),
+        ("0o17777777777", "0o20000000000"),
+    ] {
+        assert_eq!(
+            int.parse(largest),
+            Some(Variant::Int(i32::MAX)),
+            "{largest}"
+        );
+        assert_eq!(
+            int.try_parse(too_large).unwrap_err().to_string(),
+            "tried int: number too large to fit in target type",
+            "{too_large}"
+        );
+    }
+    assert_eq!(int.parse("-0x80000000"), Some(Variant::Int(i32::MIN)));
+    assert_eq!(
+        int.try_parse("-0x80000001").unwrap_err().to_string(),
+        "tried int: number too small to fit in target type"
+    );
+
+    // A wider type takes over where int overflows
+    let number = VariantFlag::int().or_long();
+    assert_eq!(number.parse("0x80000000"), Some(Variant::Long(0x8000_0000)));
+    assert_eq!(
+        number
+            .try_parse("0x8000000000000000")
+            .unwrap_err()
+            .to_string(),
+        "tried int: number too large to fit in target type; tried long: number too large to fit in target type"
+    );
+    assert_eq!(
+        VariantFlag::uint().parse("0xffffffffffffffff"),
+        Some(Variant::Uint(u64::MAX))
+    );
+}
+
+#[test]
+fn int_rejects_values_past_i32() {
+    let int = VariantFlag::int();
+    assert_eq!(int.parse("2147483647"), Some(Variant::Int(i32::MAX)));
+    assert_eq!(int.parse("-2147483648"), Some(Variant::Int(i32::MIN)));
+    assert_eq!(
+        int.try_parse("2147483648").unwrap_err().to_string(),
+        "tried int: number too large to fit in target type"
+    );
+    assert_eq!(
+        int.try_parse("-2147483649").unwrap_err().to_string(),
+        "tried int: number too small to fit in target type"
+    );
+
+    let flags = [FlagDefinition::new("count", VariantFlag::int())];
+    let Err(error) = Args::from_slice(&["my-tool", "--count", "2147483648"], &[], &flags) else {
+        panic!("2147483648 doesn't fit in an int");
+    };
+    assert_eq!(
+        error.to_string(),
+        "--count at position 2: '2147483648' cannot be parsed as type int: number too large to fit in target type"
+    );
+
+    // A long holds it
+    assert_eq!(
+        VariantFlag::long().parse("2147483648"),
+        Some(Variant::Long(2_147_483_648))
+    );
+}
+
+/// A directory in the temporary directory holding `file.txt` and `dir`, removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> TempDir {
+        let path = std::env::temp_dir().join(format!("flagged_cl_args_test_{name}"));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(path.join("dir")).unwrap();
+        std::fs::write(path.join("file.txt"), "").unwrap();
+        TempDir(path)
+    }
+
+    fn join(&self, name: &str) -> String {
+        self.0.join(name).to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn path_checks() {
+    let temp = TempDir::new("path_checks");
+    let (file, dir, missing) = (
+        temp.join("file.txt"),
+        temp.join("dir"),
+        temp.join("missing"),
+    );
+
+    let exists = VariantFlag::path().must_exist();
+    assert_eq!(
+        exists.parse(&file),
+        Some(Variant::Path(PathBuf::from(&file)))
+    );
+    assert_eq!(exists.parse(&dir), Some(Variant::Path(PathBuf::from(&dir))));
+    assert_eq!(
+        exists.try_parse(&missing).unwrap_err().to_string(),
+        format!("tried path: path '{missing}' does not exist")
+    );
+
+    let is_file = VariantFlag::path().must_be_file();
+    assert!(is_file.parse(&file).is_some());
+    assert_eq!(
+        is_file.try_parse(&dir).unwrap_err().to_string(),
+        format!("tried path: path '{dir}' is not a file")
+    );
+    assert_eq!(
+        is_file.try_parse(&missing).unwrap_err().to_string(),
+        format!("tried path: path '{missing}' does not exist")
+    );
+
+    let is_dir = VariantFlag::path().must_be_dir();
+    assert!(is_dir.parse(&dir).is_some());
+    assert_eq!(
+        is_dir.try_parse(&file).unwrap_err().to_string(),
+        format!("tried path: path '{file}' is not a directory")
+    );
+
+    // Without a check, anything goes
+    assert!(VariantFlag::path().parse(&missing).is_some());
+
+    let flags = [FlagDefinition::new(
+        "config",
+        VariantFlag::path().must_be_file(),
+    )];
+    let Err(error) = Args::from_slice(&["my-tool", "--config", &missing], &[], &flags) else {
+        panic!("the config doesn't exist");
+    };
+    assert_eq!(
+        error.to_string(),
+        format!("--config at position 2: path '{missing}' does not exist")
+    );
+}
+
+#[test]
+fn relative_paths_are_checked_from_the_current_directory() {
+    // Tests run in the package's directory
+    assert!(
+        VariantFlag::path()
+            .must_be_file()
+            .parse("Cargo.toml")
+            .is_some()
+    );
+    assert!(VariantFlag::path().must_be_dir().parse("src").is_some());
+    assert!(
+        VariantFlag::path()
+            .must_be_dir()
+            .parse("./src/../tests")
+            .is_some()
+    );
+    assert_eq!(
+        VariantFlag::path().must_exist().parse("no-such-file.txt"),
+        None
+    );
+    // The path is kept as it was given
+    assert_eq!(
+        VariantFlag::path().must_exist().parse("src/../Cargo.toml"),
+        Some(Variant::Path(PathBuf::from("src/../Cargo.toml")))
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_are_followed() {
+    let temp = TempDir::new("symlinks");
+    std::os::unix::fs::symlink(temp.join("file.txt"), temp.join("to_file")).unwrap();
+    std::os::unix::fs::symlink(temp.join("dir"), temp.join("to_dir")).unwrap();
+    std::os::unix::fs::symlink(temp.join("missing"), temp.join("dangling")).unwrap();
+
+    assert!(
+        VariantFlag::path()
+            .must_be_file()
+            .parse(&temp.join("to_file"))
+            .is_some()
+    );
+    assert!(
+        VariantFlag::path()
+            .must_be_dir()
+            .parse(&temp.join("to_dir"))
+            .is_some()
+    );
+    assert!(
+        VariantFlag::path()
+            .must_be_dir()
+            .parse(&temp.join("to_file"))
+            .is_none()
+    );
+    let dangling = temp.join("dangling");
+    assert_eq!(
+        VariantFlag::path()
+            .must_exist()
+            .try_parse(&dangling)
+            .unwrap_err()
+            .to_string(),
+        format!("tried path: path '{dangling}' does not exist")
+    );
+}
+
+#[test]
+fn coerce_float_widens_any_number() {
+    assert_eq!(Variant::Int(2).coerce_float(), Some(2.0));
+    assert_eq!(Variant::Long(-3).coerce_float(), Some(-3.0));
+    assert_eq!(Variant::Uint(4).coerce_float(), Some(4.0));
+    assert_eq!(Variant::Port(8080).coerce_float(), Some(8080.0));
+    assert_eq!(Variant::Float(2.5).coerce_float(), Some(2.5));
+    assert_eq!(Variant::Double(0.1).coerce_float(), Some(0.1));
+    // Rounded to the nearest float
+    assert_eq!(Variant::Int(16_777_217).coerce_float(), Some(16_777_216.0));
+    assert!(Variant::Double(f64::NAN).coerce_float().unwrap().is_nan());
+    assert_eq!(Variant::Double(1e300).coerce_float(), Some(f32::INFINITY));
+
+    assert_eq!(Variant::String("2".to_string()).coerce_float(), None);
+    assert_eq!(Variant::Bool(true).coerce_float(), None);
+}
+
+#[test]
+fn coerce_int_only_takes_whole_numbers_that_fit() {
+    assert_eq!(Variant::Int(-2).coerce_int(), Some(-2));
+    assert_eq!(Variant::Long(7).coerce_int(), Some(7));
+    assert_eq!(Variant::Uint(7).coerce_int(), Some(7));
+    assert_eq!(Variant::Port(65535).coerce_int(), Some(65535));
+    assert_eq!(Variant::Float(2.0).coerce_int(), Some(2));
+    assert_eq!(Variant::Double(-2147483648.0).coerce_int(), Some(i32::MIN));
+
+    // Fractions aren't truncated
+    assert_eq!(Variant::Float(2.5).coerce_int(), None);
+    assert_eq!(Variant::Double(-0.1).coerce_int(), None);
+    assert_eq!(Variant::Float(f32::NAN).coerce_int(), None);
+    assert_eq!(Variant::Double(f64::INFINITY).coerce_int(), None);
+    assert_eq!(Variant::Double(f64::NEG_INFINITY).coerce_int(), None);
+    // Out of range
+    assert_eq!(Variant::Long(i64::from(i32::MAX) + 1).coerce_int(), None);
+    assert_eq!(Variant::Long(i64::from(i32::MIN) - 1).coerce_int(), None);
+    assert_eq!(Variant::Uint(u64::MAX).coerce_int(), None);
+    assert_eq!(Variant::Double(2147483648.0).coerce_int(), None);
+
+    assert_eq!(Variant::String("2".to_string()).coerce_int(), None);
+    assert_eq!(Variant::Bool(true).coerce_int(), None);
+}
+
+#[test]
+fn coerce_string_uses_display() {
+    assert_eq!(Variant::Int(-2).coerce_string(), "-2");
+    assert_eq!(Variant::Bool(false).coerce_string(), "false");
+    assert_eq!(Variant::Float(2.5).coerce_string(), "2.5");
+    assert_eq!(
+        Variant::String("as is".to_string()).coerce_string(),
+        "as is"
+    );
+    assert_eq!(
+        Variant::Path(PathBuf::from("dir/file.txt")).coerce_string(),
+        "dir/file.txt"
+    );
+    assert_eq!(
+        Variant::List(vec![Variant::Int(1), Variant::Int(2)]).coerce_string(),
+        "1,2"
+    );
+}
+
+#[test]
+fn coerce_bool_takes_zero_and_one() {
+    assert_eq!(Variant::Bool(true).coerce_bool(), Some(true));
+    assert_eq!(Variant::Int(0).coerce_bool(), Some(false));
+    assert_eq!(Variant::Int(1).coerce_bool(), Some(true));
+    assert_eq!(Variant::Long(1).coerce_bool(), Some(true));
+    assert_eq!(Variant::Uint(0).coerce_bool(), Some(false));
+    assert_eq!(Variant::Port(1).coerce_bool(), Some(true));
+
+    assert_eq!(Variant::Int(2).coerce_bool(), None);
+    assert_eq!(Variant::Int(-1).coerce_bool(), None);
+    assert_eq!(Variant::Float(1.0).coerce_bool(), None);
+    assert_eq!(Variant::String("true".to_string()).coerce_bool(), None);
+}
+
+#[test]
+fn duration_units() {
+    let duration = VariantFlag::duration();
+    for (raw, expected) in [
+        ("250ms", Duration::from_millis(250)),
+        ("30s", Duration::from_secs(30)),
+        ("5m", Duration::from_secs(5 * 60)),
+        ("2h", Duration::from_secs(2 * 60 * 60)),
+        ("0s", Duration::ZERO),
+    ] {
+        assert_eq!(
+            duration.parse(raw),
+            Some(Variant::Duration(expected)),
+            "{raw}"
+        );
+    }
+}
+
+#[test]
+fn compound_durations() {
+    let duration = VariantFlag::duration();
+    assert_eq!(
+        duration.parse("1h30m"),
+        Some(Variant::Duration(Duration::from_secs(90 * 60)))
+    );
+    assert_eq!(
+        duration.parse("1m30s500ms"),
+        Some(Variant::Duration(Duration::from_millis(90_500)))
+    );
+    // Units can be repeated and come in any order
+    assert_eq!(
+        duration.parse("30s1m30s"),
+        Some(Variant::Duration(Duration::from_secs(120)))
+    );
+    // Display writes the same form back
+    assert_eq!(
+        Variant::Duration(Duration::from_millis(5_400_250)).to_string(),
+        "1h30m250ms"
+    );
+}
+
+#[test]
+fn durations_need_a_known_unit() {
+    let duration = VariantFlag::duration();
+    assert_eq!(
+        duration.try_parse("30").unwrap_err().to_string(),
+        "tried duration: '30' is missing a unit, expected a number followed by one of ms, s, m, h"
+    );
+    assert_eq!(
+        duration.try_parse("1h30").unwrap_err().to_string(),
+        "tried duration: '1h30' is missing a unit, expected a number followed by one of ms, s, m, h"
+    );
+    assert_eq!(
+        duration.try_parse("3d").unwrap_err().to_string(),
+        "tried duration: 'd' is not a valid duration unit, expected a number followed by one of ms, s, m, h"
+    );
+    assert_eq!(
+        duration.try_parse("h").unwrap_err().to_string(),
+        "tried duration: 'h' is not a valid duration, expected a number followed by one of ms, s, m, h"
+    );
+    assert_eq!(
+        duration.try_parse("").unwrap_err().to_string(),
+        "tried duration: '' is not a valid duration, expected a number followed by one of ms, s, m, h"
+    );
+}
+
+#[test]
+fn long_durations() {
+    let duration = VariantFlag::duration();
+    // About 58 days, more milliseconds than fit in a u32
+    assert_eq!(
+        duration.parse("5000000000ms"),
+        Some(Variant::Duration(Duration::from_millis(5_000_000_000)))
+    );
+    assert_eq!(
+        duration.parse("18446744073709551615s"),
+        Some(Variant::Duration(Duration::from_secs(u64::MAX)))
+    );
+    for raw in [
+        "18446744073709551616s",
+        "18446744073709551615h",
+        "18446744073709551615s1s",
+    ] {
+        assert_eq!(
+            duration.try_parse(raw).unwrap_err().to_string(),
+            format!("tried duration: '{raw}' is too long to be represented as a duration")
+        );
+    }
+}
+
+/// Parses `raw` as a date/time, as nanoseconds from the Unix epoch, negative before it.
+fn since_epoch(raw: &str) -> Option<i128> {
+    let Variant::DateTime(time) = VariantFlag::datetime().parse(raw)? else {
+        unreachable!()
+    };
+    Some(match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => after.as_nanos() as i128,
+        Err(before) => -(before.duration().as_nanos() as i128),
+    })
+}
+
+const SECOND: i128 = 1_000_000_000;
+
+#[test]
+fn calendar_dates() {
+    assert_eq!(since_epoch("1970-01-01"), Some(0));
+    assert_eq!(since_epoch("2024-02-29"), Some(1_709_164_800 * SECOND));
+    assert_eq!(since_epoch("2000-02-29"), Some(951_782_400 * SECOND));
+    let datetime = VariantFlag::datetime();
+    for raw in [
+        "2023-02-29",
+        "1900-02-29",
+        "2024-02-30",
+        "2024-04-31",
+        "2024-13-01",
+        "2024-00-10",
+        "2024-01-00",
+    ] {
+        assert_eq!(
+            datetime.try_parse(raw).unwrap_err().to_string(),
+            format!("tried datetime: '{raw}' is not a valid calendar date")
+        );
+    }
+}
+
+#[test]
+fn times_and_offsets() {
+    assert_eq!(
+        since_epoch("2024-06-01T12:00:00Z"),
+        Some(1_717_243_200 * SECOND)
+    );
+    // Lowercase and a space are allowed too
+    assert_eq!(
+        since_epoch("2024-06-01t12:00:00z"),
+        Some(1_717_243_200 * SECOND)
+    );
+    assert_eq!(
+        since_epoch("2024-06-01 12:00:00Z"),
+        Some(1_717_243_200 * SECOND)
+    );
+    assert_eq!(
+        since_epoch("2024-06-01T14:30:00+02:30"),
+        Some(1_717_243_200 * SECOND)
+    );
+    assert_eq!(
+        since_epoch("2024-06-01T09:00:00-03:00"),
+        Some(1_717_243_200 * SECOND)
+    );
+    // Crossing into the day before
+    assert_eq!(
+        since_epoch("2024-06-02T01:00:00+13:00"),
+        Some(1_717_243_200 * SECOND)
+    );
+
+    let datetime = VariantFlag::datetime();
+    assert_eq!(
+        datetime
+            .try_parse("2024-06-01T24:00:00Z")
+            .unwrap_err()
+            .to_string(),
+        "tried datetime: '2024-06-01T24:00:00Z' is not a valid time of day"
+    );
+    assert_eq!(
+        datetime
+            .try_parse("2024-06-01T12:00:00+24:00")
+            .unwrap_err()
+            .to_string(),
+        "tried datetime: '2024-06-01T12:00:00+24:00' has an invalid UTC offset"
+    );
+    for raw in [
+        "2024-06-01T12:00:00",
+        "2024-06-01T12:00:00+0200",
+        "2024-06-01T12:00Z",
+    ] {
+        assert_eq!(
+            datetime.try_parse(raw).unwrap_err().to_string(),
+            format!(
+                "tried datetime: '{raw}' is not a valid date/time, expected RFC 3339 like 2024-06-01T12:00:00Z or a date like 2024-06-01"
+            )
+        );
+    }
+}
+
+#[test]
+fn fractions_of_a_second() {
+    assert_eq!(since_epoch("1970-01-01T00:00:00.5Z"), Some(500_000_000));
+    assert_eq!(since_epoch("1970-01-01T00:00:00.000000001Z"), Some(1));
+    // Anything past nanoseconds is dropped
+    assert_eq!(
+        since_epoch("1970-01-01T00:00:00.1234567899Z"),
+        Some(123_456_789)
+    );
+    assert_eq!(
+        VariantFlag::datetime()
+            .try_parse("2024-06-01T12:00:00.Z")
+            .unwrap_err()
+            .to_string(),
+        "tried datetime: '2024-06-01T12:00:00.Z' is not a valid date/time, expected RFC 3339 like 2024-06-01T12:00:00Z or a date like 2024-06-01"
+    );
+}
+
+#[test]
+fn before_the_epoch() {
+    assert_eq!(since_epoch("1969-12-31T23:59:59Z"), Some(-SECOND));
+    assert_eq!(since_epoch("1969-12-31T23:59:59.75Z"), Some(-SECOND / 4));
+    assert_eq!(since_epoch("1900-01-01"), Some(-2_208_988_800 * SECOND));
+}
+
+#[test]
+fn datetime_display_round_trips() {
+    let datetime = VariantFlag::datetime();
+    for raw in [
+        "2024-06-01T12:00:00Z",
+        "2024-02-29T23:59:59.5Z",
+        "1969-12-31T23:59:59.75Z",
+        "1900-01-01T00:00:00Z",
+        "2024-06-01T12:00:00.000000001Z",
+    ] {
+        let parsed = datetime.parse(raw).unwrap();
+        assert_eq!(parsed.to_string(), raw);
+        assert_eq!(datetime.parse(&parsed.to_string()), Some(parsed));
+    }
+    // Offsets are written in UTC
+    assert_eq!(
+        datetime
+            .parse("2024-06-01T14:00:00+02:00")
+            .unwrap()
+            .to_string(),
+        "2024-06-01T12:00:00Z"
+    );
+}
+
+#[test]
+fn host_port_keeps_the_host_as_written() {
+    let host_port = VariantFlag::host_port();
+    for (raw, host, port) in [
+        ("localhost:80", "localhost", 80),
+        ("example.com:8080", "example.com", 8080),
+        ("127.0.0.1:0", "127.0.0.1", 0),
+        ("[::1]:8080", "::1", 8080),
+        ("[fe80::1%eth0]:65535", "fe80::1%eth0", 65535),
+    ] {
+        let parsed = host_port.parse(raw).unwrap();
+        assert_eq!(parsed.as_host_port(), Some((host, port)), "{raw}");
+        // IPv6 hosts are bracketed again when written back
+        assert_eq!(parsed.to_string(), raw);
+    }
+}
+
+#[test]
+fn host_port_errors() {
+    let host_port = VariantFlag::host_port();
+    for (raw, expected) in [
+        (
+            "localhost",
+            "tried host:port: 'localhost' is missing a port, expected host:port",
+        ),
+        (
+            "[::1]",
+            "tried host:port: '[::1]' is missing a port, expected [host]:port",
+        ),
+        (
+            "[::1:8080",
+            "tried host:port: '[::1:8080' is missing a closing ']'",
+        ),
+        (
+            "::1:8080",
+            "tried host:port: '::1:8080' is ambiguous, IPv6 hosts must be written as [host]:port",
+        ),
+        (":8080", "tried host:port: ':8080' is missing a host"),
+        (
+            "localhost:65536",
+            "tried host:port: '65536' in 'localhost:65536' is not a valid port, expected 0-65535",
+        ),
+        (
+            "localhost:http",
+            "tried host:port: 'http' in 'localhost:http' is not a valid port, expected 0-65535",
+        ),
+    ] {
+        assert_eq!(
+            host_port.try_parse(raw).unwrap_err().to_string(),
+            expected,
+            "{raw}"
+        );
+    }
+}
+
+#[test]
+fn double_display_round_trips() {
+    let double = VariantFlag::double();
+    for value in [
+        0.1,
+        -0.1,
+        0.1 + 0.2,
+        51.477_928_123_456_7,
+        123_456_789.123_456_79,
+        1e300,
+        -1e300,
+        f64::MAX,
+        1e-300,
+        f64::MIN_POSITIVE,
+        // The smallest subnormal
+        5e-324,
+    ] {
+        let written = Variant::Double(value).to_string();
+        assert_eq!(
+            double.parse(&written),
+            Some(Variant::Double(value)),
+            "{written}"
+        );
+    }
+    // Display never uses exponents, and writes the shortest digits that read back the same
+    assert_eq!(Variant::Double(0.1).to_string(), "0.1");
+    assert_eq!(Variant::Double(1e21).to_string(), "1000000000000000000000");
+}
+
+#[test]
+fn double_keeps_precision_float_loses() {
+    let raw = "51.4779281234567";
+    assert_eq!(
+        VariantFlag::double().parse(raw),
+        Some(Variant::Double(51.477_928_123_456_7))
+    );
+    let float = VariantFlag::float().parse(raw).unwrap();
+    assert_ne!(float.as_double(), Some(51.477_928_123_456_7));
+    assert_eq!(Variant::Int(3).as_double(), Some(3.0));
+    assert_eq!(Variant::Float(0.5).as_double(), Some(0.5));
+}
+
+#[test]
+fn sockets_are_literal_by_default() {
+    let socket = VariantFlag::socket();
+    assert_eq!(
+        socket.parse("127.0.0.1:8080"),
+        Some(Variant::Socket(SocketAddr::from(([127, 0, 0, 1], 8080))))
+    );
+    assert_eq!(
+        socket.parse("[::1]:443"),
+        Some(Variant::Socket(SocketAddr::from((
+            Ipv6Addr::LOCALHOST,
+            443
+        ))))
+    );
+    // Hostnames are rejected without being looked up, so this returns at once even offline
+    for raw in ["localhost:80", "example.invalid:80"] {
+        assert_eq!(
+            socket.try_parse(raw).unwrap_err().to_string(),
+            "tried socket: invalid socket address syntax",
+            "{raw}"
+        );
+    }
+}
+
+#[test]
+fn resolving_sockets_accept_hostnames() {
+    let socket = VariantFlag::socket_resolving();
+    // Literal addresses never reach the resolver
+    assert_eq!(
+        socket.parse("10.0.0.1:53"),
+        Some(Variant::Socket(SocketAddr::from(([10, 0, 0, 1], 53))))
+    );
+    // localhost comes from the hosts file, so no network is needed
+    let resolved = socket.parse("localhost:80").unwrap().as_socket().unwrap();
+    assert!(resolved.ip().is_loopback());
+    assert_eq!(resolved.port(), 80);
+    assert!(socket.parse("localhost").is_none());
+}
+
+#[test]
+fn byte_size_suffixes() {
+    let byte_size = VariantFlag::byte_size();
+    for (raw, expected) in [
+        ("512", 512),
+        ("512B", 512),
+        ("10KB", 10_000),
+        ("10KiB", 10_240),
+        ("3MB", 3_000_000),
+        ("3MiB", 3 << 20),
+        ("2GB", 2_000_000_000),
+        ("2GiB", 2 << 30),
+        ("1TB", 1_000_000_000_000),
+        ("1TiB", 1 << 40),
+        // Suffixes ignore case
+        ("10kb", 10_000),
+        ("10kib", 10_240),
+        ("3Mib", 3 << 20),
+    ] {
+        assert_eq!(
+            byte_size.parse(raw).and_then(|value| value.as_bytes()),
+            Some(expected),
+            "{raw}"
+        );
+    }
+}
+
+#[test]
+fn fractional_byte_sizes_round_down() {
+    let byte_size = VariantFlag::byte_size();
+    for (raw, expected) in [
+        ("1.5GB", 1_500_000_000),
+        ("1.5KiB", 1_536),
+        ("0.5B", 0),
+        ("1.9999B", 1),
+        ("0.0001KB", 0),
+    ] {
+        assert_eq!(
+            byte_size.parse(raw).and_then(|value| value.as_bytes()),
+            Some(expected),
+            "{raw}"
+        );
+    }
+}
+
+#[test]
+fn byte_size_errors() {
+    let byte_size = VariantFlag::byte_size();
+    assert_eq!(
+        byte_size.try_parse("10XB").unwrap_err().to_string(),
+        "tried bytes: 'XB' is not a valid byte size suffix, expected one of B, KB, MB, GB, TB, KiB, MiB, GiB, TiB"
+    );
+    assert_eq!(
+        byte_size.try_parse("1.2.3MB").unwrap_err().to_string(),
+        "tried bytes: '1.2.3MB' is not a valid byte size"
+    );
+    assert_eq!(
+        byte_size.try_parse("20000000TiB").unwrap_err().to_string(),
+        "tried bytes: '20000000TiB' is too large to be represented as a byte size"
+    );
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn regex_patterns_compile_when_parsed() {
+    let flags = [FlagDefinition::new("match", VariantFlag::regex())];
+    let args = Args::from_slice(&["my-tool", "--match", "^ab+c$"], &[], &flags).unwrap();
+    let pattern = args.get_named("match").and_then(Variant::as_regex).unwrap();
+    assert_eq!(pattern.as_str(), "^ab+c$");
+    assert!(pattern.is_match("abbbc"));
+    assert!(!pattern.is_match("ac"));
+    // Patterns compare by their text
+    assert_eq!(
+        VariantFlag::regex().parse("^ab+c$"),
+        args.get_named("match").cloned()
+    );
+    assert_ne!(
+        VariantFlag::regex().parse("^ab*c$"),
+        args.get_named("match").cloned()
+    );
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn invalid_regex_names_the_flag() {
+    let flags = [FlagDefinition::new("match", VariantFlag::regex())];
+    let Err(error) = Args::from_slice(&["my-tool", "--match", "(unclosed"], &[], &flags) else {
+        panic!("the pattern doesn't compile");
+    };
+    let message = error.to_string();
+    assert!(message.starts_with("--match at position 2: "), "{message}");
+    assert!(message.contains("unclosed group"), "{message}");
+    assert!(
+        VariantFlag::regex()
+            .try_parse("a{2,1}")
+            .unwrap_err()
+            .to_string()
+            .contains("invalid repetition count range")
+    );
+}